@@ -1,4 +1,5 @@
 export const idlFactory = ({ IDL }) => {
+  const Result = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
   const TokenLocation = IDL.Variant({
     'ICRC151' : IDL.Record({
       'token_id' : IDL.Vec(IDL.Nat8),
@@ -22,7 +23,53 @@ export const idlFactory = ({ IDL }) => {
     'logo_url' : IDL.Opt(IDL.Text),
     'category' : AssetCategory,
   });
-  const Result = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const ProposalStatus = IDL.Variant({
+    'Failed' : IDL.Record({ 'error' : IDL.Text }),
+    'Executed' : IDL.Null,
+    'Cancelled' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const GovernanceConfig = IDL.Record({
+    'delay_ns' : IDL.Nat64,
+    'required_approvals' : IDL.Nat32,
+  });
+  const ValuationMode = IDL.Variant({
+    'Spot' : IDL.Null,
+    'Twap' : IDL.Record({ 'window_ns' : IDL.Nat64 }),
+  });
+  const GovernedAction = IDL.Variant({
+    'SetAdmin' : IDL.Record({ 'new_admin' : IDL.Principal }),
+    'SetPlatformTreasury' : IDL.Record({ 'treasury' : IDL.Principal }),
+    'SetCoordinatorPublicKey' : IDL.Record({ 'public_key_hex' : IDL.Text }),
+    'SetDefaultPlatformFeeBps' : IDL.Record({ 'fee_bps' : IDL.Nat64 }),
+    'UpdateAssetTokenLocation' : IDL.Record({
+      'token_location' : TokenLocation,
+      'asset_id' : IDL.Text,
+    }),
+    'SetCkusdcLedger' : IDL.Record({ 'ledger' : IDL.Principal }),
+    'SetBundlePlatformFee' : IDL.Record({
+      'bundle_id' : IDL.Nat64,
+      'fee_bps' : IDL.Nat64,
+    }),
+    'SetGovernanceConfig' : IDL.Record({ 'config' : GovernanceConfig }),
+    'SetBundleValuationMode' : IDL.Record({
+      'bundle_id' : IDL.Nat64,
+      'mode' : ValuationMode,
+    }),
+  });
+  const Proposal = IDL.Record({
+    'id' : IDL.Nat64,
+    'status' : ProposalStatus,
+    'action' : GovernedAction,
+    'executable_at' : IDL.Nat64,
+    'required_approvals' : IDL.Nat32,
+    'created_at' : IDL.Nat64,
+    'proposer' : IDL.Principal,
+    'approvals' : IDL.Vec(IDL.Principal),
+    'resolved_at' : IDL.Opt(IDL.Nat64),
+    'resolved_by' : IDL.Opt(IDL.Principal),
+  });
+  const Result_1 = IDL.Variant({ 'Ok' : Proposal, 'Err' : IDL.Text });
   const AssetValue = IDL.Record({
     'value_usd' : IDL.Nat64,
     'asset_id' : IDL.Text,
@@ -34,12 +81,13 @@ export const idlFactory = ({ IDL }) => {
     'nav_per_token' : IDL.Nat64,
     'bundle_id' : IDL.Nat64,
     'total_nav_usd' : IDL.Nat64,
+    'fallback_assets' : IDL.Vec(IDL.Text),
     'calculated_at' : IDL.Nat64,
     'total_tokens' : IDL.Nat64,
   });
-  const Result_1 = IDL.Variant({ 'Ok' : BundleNAV, 'Err' : IDL.Text });
-  const Result_2 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : IDL.Text });
-  const Result_3 = IDL.Variant({ 'Ok' : IDL.Nat32, 'Err' : IDL.Text });
+  const Result_2 = IDL.Variant({ 'Ok' : BundleNAV, 'Err' : IDL.Text });
+  const Result_3 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : IDL.Text });
+  const Result_4 = IDL.Variant({ 'Ok' : IDL.Nat32, 'Err' : IDL.Text });
   const AssetAllocationInput = IDL.Record({
     'asset_id' : IDL.Text,
     'percentage' : IDL.Nat8,
@@ -51,14 +99,50 @@ export const idlFactory = ({ IDL }) => {
     'allocations' : IDL.Vec(AssetAllocationInput),
     'symbol' : IDL.Text,
   });
+  const MigrationStep = IDL.Record({
+    'region' : IDL.Text,
+    'records' : IDL.Nat64,
+    'to_version' : IDL.Nat32,
+    'description' : IDL.Text,
+    'from_version' : IDL.Nat32,
+  });
+  const RegionCheck = IDL.Record({
+    'region' : IDL.Text,
+    'records' : IDL.Nat64,
+    'error' : IDL.Opt(IDL.Text),
+  });
+  const MigrationReport = IDL.Record({
+    'steps' : IDL.Vec(MigrationStep),
+    'checks' : IDL.Vec(RegionCheck),
+    'dry_run' : IDL.Bool,
+  });
+  const Result_5 = IDL.Variant({ 'Ok' : MigrationReport, 'Err' : IDL.Text });
+  const RecoveryOutcome = IDL.Variant({
+    'CkUSDCRefunded' : IDL.Record({ 'amount' : IDL.Nat64 }),
+    'ResolverRefunded' : IDL.Record({
+      'ckusdc' : IDL.Nat64,
+      'nav_tokens' : IDL.Nat64,
+    }),
+    'NothingToRecover' : IDL.Null,
+    'DissolutionCompleted' : IDL.Record({
+      'assets' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+    }),
+    'NavTokensReturned' : IDL.Record({ 'amount' : IDL.Nat64 }),
+    'BuyCompleted' : IDL.Null,
+    'CollateralReturned' : IDL.Record({
+      'assets' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+      'ckusdc' : IDL.Nat64,
+    }),
+  });
   const RecoveryReport = IDL.Record({
     'recovered_assets' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
     'recovered_ckusdc' : IDL.Nat64,
     'user' : IDL.Principal,
+    'outcomes' : IDL.Vec(IDL.Tuple(IDL.Nat64, RecoveryOutcome)),
     'timestamp' : IDL.Nat64,
     'recovered_nav_tokens' : IDL.Nat64,
   });
-  const Result_4 = IDL.Variant({ 'Ok' : RecoveryReport, 'Err' : IDL.Text });
+  const Result_6 = IDL.Variant({ 'Ok' : RecoveryReport, 'Err' : IDL.Text });
   const AssetAmount = IDL.Record({
     'asset_id' : IDL.Text,
     'amount' : IDL.Nat64,
@@ -82,10 +166,11 @@ export const idlFactory = ({ IDL }) => {
     'nonce' : IDL.Nat64,
     'operation' : OperationType,
     'nav_tokens' : IDL.Nat64,
+    'payment_asset' : IDL.Opt(IDL.Text),
   });
   const LockedFundType = IDL.Variant({
     'NAVTokens' : IDL.Record({ 'bundle_id' : IDL.Nat64 }),
-    'CkUSDC' : IDL.Null,
+    'Payment' : IDL.Record({ 'asset_id' : IDL.Text }),
   });
   const ResolverInfo = IDL.Record({
     'principal' : IDL.Principal,
@@ -98,6 +183,27 @@ export const idlFactory = ({ IDL }) => {
     'registered_at' : IDL.Nat64,
     'successful_transactions' : IDL.Nat64,
   });
+  const AdminActionFilter = IDL.Record({
+    'admin' : IDL.Opt(IDL.Principal),
+    'from_time' : IDL.Opt(IDL.Nat64),
+    'to_time' : IDL.Opt(IDL.Nat64),
+    'action_type' : IDL.Opt(IDL.Text),
+    'target' : IDL.Opt(IDL.Text),
+  });
+  const AdminAction = IDL.Record({
+    'id' : IDL.Nat64,
+    'admin' : IDL.Principal,
+    'after' : IDL.Opt(IDL.Text),
+    'action_type' : IDL.Text,
+    'target' : IDL.Text,
+    'before' : IDL.Opt(IDL.Text),
+    'timestamp' : IDL.Nat64,
+    'details' : IDL.Opt(IDL.Text),
+  });
+  const AnalyticsConfig = IDL.Record({
+    'benchmark_bundle_id' : IDL.Opt(IDL.Nat64),
+    'risk_free_rate_bps' : IDL.Nat64,
+  });
   const AssetInfo = IDL.Record({
     'id' : IDL.Text,
     'decimals' : IDL.Nat8,
@@ -109,7 +215,7 @@ export const idlFactory = ({ IDL }) => {
     'oracle_ticker' : IDL.Opt(IDL.Text),
     'symbol' : IDL.Text,
   });
-  const Result_5 = IDL.Variant({ 'Ok' : AssetInfo, 'Err' : IDL.Text });
+  const Result_7 = IDL.Variant({ 'Ok' : AssetInfo, 'Err' : IDL.Text });
   const AssetPrice = IDL.Record({
     'source' : IDL.Text,
     'timestamp' : IDL.Nat64,
@@ -117,7 +223,7 @@ export const idlFactory = ({ IDL }) => {
     'confidence' : IDL.Nat8,
     'price_usd' : IDL.Nat64,
   });
-  const Result_6 = IDL.Variant({ 'Ok' : AssetPrice, 'Err' : IDL.Text });
+  const Result_8 = IDL.Variant({ 'Ok' : AssetPrice, 'Err' : IDL.Text });
   const AssetSummary = IDL.Record({
     'id' : IDL.Text,
     'bundles_using' : IDL.Nat32,
@@ -137,8 +243,9 @@ export const idlFactory = ({ IDL }) => {
     'valid_until' : IDL.Nat64,
     'estimated_nav' : IDL.Nat64,
     'nav_tokens' : IDL.Nat64,
+    'payment_asset' : IDL.Opt(IDL.Text),
   });
-  const Result_7 = IDL.Variant({ 'Ok' : QuoteAssignment, 'Err' : IDL.Text });
+  const Result_9 = IDL.Variant({ 'Ok' : QuoteAssignment, 'Err' : IDL.Text });
   const AssetAllocation = IDL.Record({
     'token_location' : TokenLocation,
     'asset_id' : IDL.Text,
@@ -153,16 +260,39 @@ export const idlFactory = ({ IDL }) => {
     'platform_fee_bps' : IDL.Opt(IDL.Nat64),
     'token_location' : TokenLocation,
     'allocations' : IDL.Vec(AssetAllocation),
+    'valuation_mode' : IDL.Opt(ValuationMode),
     'is_active' : IDL.Bool,
     'symbol' : IDL.Text,
   });
-  const Result_8 = IDL.Variant({ 'Ok' : BundleConfig, 'Err' : IDL.Text });
+  const Result_10 = IDL.Variant({ 'Ok' : BundleConfig, 'Err' : IDL.Text });
+  const BundleAnalytics = IDL.Record({
+    'benchmark_bundle_id' : IDL.Opt(IDL.Nat64),
+    'return_7d_bps' : IDL.Opt(IDL.Int64),
+    'bundle_id' : IDL.Nat64,
+    'return_ytd_bps' : IDL.Opt(IDL.Int64),
+    'correlation' : IDL.Opt(IDL.Float64),
+    'return_24h_bps' : IDL.Opt(IDL.Int64),
+    'return_inception_bps' : IDL.Opt(IDL.Int64),
+    'volatility_bps' : IDL.Opt(IDL.Nat64),
+    'return_30d_bps' : IDL.Opt(IDL.Int64),
+    'calculated_at' : IDL.Nat64,
+    'max_drawdown_bps' : IDL.Opt(IDL.Nat64),
+    'sharpe_ratio' : IDL.Opt(IDL.Float64),
+  });
+  const Result_11 = IDL.Variant({ 'Ok' : BundleAnalytics, 'Err' : IDL.Text });
   const BundleHolding = IDL.Record({
     'bundle_id' : IDL.Nat64,
     'last_updated' : IDL.Nat64,
     'asset_id' : IDL.Text,
     'amount' : IDL.Nat64,
   });
+  const BundleReturn = IDL.Record({
+    'end' : BundleNAV,
+    'bundle_id' : IDL.Nat64,
+    'start' : BundleNAV,
+    'return_bps' : IDL.Int64,
+  });
+  const Result_12 = IDL.Variant({ 'Ok' : BundleReturn, 'Err' : IDL.Text });
   const BundleSummary = IDL.Record({
     'id' : IDL.Nat64,
     'creator' : IDL.Principal,
@@ -173,7 +303,7 @@ export const idlFactory = ({ IDL }) => {
     'is_active' : IDL.Bool,
     'total_nav_tokens' : IDL.Nat64,
   });
-  const Result_9 = IDL.Variant({ 'Ok' : BundleSummary, 'Err' : IDL.Text });
+  const Result_13 = IDL.Variant({ 'Ok' : BundleSummary, 'Err' : IDL.Text });
   const BundleTransactionHistory = IDL.Record({
     'total_sell_transactions' : IDL.Nat64,
     'total_buy_transactions' : IDL.Nat64,
@@ -188,6 +318,7 @@ export const idlFactory = ({ IDL }) => {
     'nav_per_token' : IDL.Nat64,
     'name' : IDL.Text,
     'description' : IDL.Opt(IDL.Text),
+    'analytics' : BundleAnalytics,
     'created_at' : IDL.Nat64,
     'total_nav_usd' : IDL.Nat64,
     'token_location' : TokenLocation,
@@ -196,8 +327,23 @@ export const idlFactory = ({ IDL }) => {
     'is_active' : IDL.Bool,
     'symbol' : IDL.Text,
   });
+  const BundleSortKey = IDL.Variant({
+    'Volatility' : IDL.Null,
+    'ReturnInception' : IDL.Null,
+    'TotalNav' : IDL.Null,
+    'Sharpe' : IDL.Null,
+    'Holders' : IDL.Null,
+    'Return24h' : IDL.Null,
+    'Return30d' : IDL.Null,
+    'ReturnYtd' : IDL.Null,
+    'Return7d' : IDL.Null,
+    'MaxDrawdown' : IDL.Null,
+    'NavPerToken' : IDL.Null,
+  });
   const CacheStatistics = IDL.Record({
     'total_entries' : IDL.Nat32,
+    'max_staleness_seconds' : IDL.Nat64,
+    'oracle_canister' : IDL.Principal,
     'cache_hit_rate' : IDL.Float64,
     'valid_entries' : IDL.Nat32,
     'expired_entries' : IDL.Nat32,
@@ -221,6 +367,15 @@ export const idlFactory = ({ IDL }) => {
     'memory_usage' : MemoryUsage,
     'total_nav_tokens' : IDL.Nat64,
   });
+  const CandleInterval = IDL.Variant({
+    'Day' : IDL.Null,
+    'Hour' : IDL.Null,
+    'Minute' : IDL.Null,
+  });
+  const Result_14 = IDL.Variant({
+    'Ok' : IDL.Vec(BundleNAV),
+    'Err' : IDL.Text,
+  });
   const NAVPrecisionReport = IDL.Record({
     'nav_per_token_18_decimals' : IDL.Nat64,
     'bundle_id' : IDL.Nat64,
@@ -231,16 +386,83 @@ export const idlFactory = ({ IDL }) => {
     'total_tokens' : IDL.Nat64,
     'precision_loss_amount' : IDL.Nat64,
   });
-  const Result_10 = IDL.Variant({
+  const Result_15 = IDL.Variant({
     'Ok' : NAVPrecisionReport,
     'Err' : IDL.Text,
   });
   const OracleConfig = IDL.Record({
+    'max_price_move_bps' : IDL.Opt(IDL.Nat64),
     'oracle_canister' : IDL.Principal,
     'max_staleness_ns' : IDL.Nat64,
     'cache_duration_ns' : IDL.Nat64,
     'fallback_enabled' : IDL.Bool,
   });
+  const PauseState = IDL.Record({
+    'initial_funding' : IDL.Bool,
+    'updated_at' : IDL.Nat64,
+    'updated_by' : IDL.Opt(IDL.Principal),
+    'paused_bundles' : IDL.Vec(IDL.Nat64),
+    'bundle_creation' : IDL.Bool,
+    'resolver_confirmations' : IDL.Bool,
+    'new_buys' : IDL.Bool,
+    'new_sells' : IDL.Bool,
+  });
+  const Nomination = IDL.Record({
+    'nominee' : IDL.Principal,
+    'nominated_at' : IDL.Nat64,
+    'nominated_by' : IDL.Principal,
+    'expires_at' : IDL.Nat64,
+  });
+  const PortfolioPosition = IDL.Record({
+    'nav_per_token' : IDL.Opt(IDL.Nat64),
+    'bundle_name' : IDL.Text,
+    'balance' : IDL.Nat64,
+    'bundle_id' : IDL.Nat64,
+    'cost_basis_usd' : IDL.Opt(IDL.Nat64),
+    'value_usd' : IDL.Opt(IDL.Nat64),
+    'unrealized_pnl_usd' : IDL.Opt(IDL.Int64),
+    'symbol' : IDL.Text,
+  });
+  const PortfolioValuation = IDL.Record({
+    'total_cost_basis_usd' : IDL.Opt(IDL.Nat64),
+    'user' : IDL.Principal,
+    'valued_at' : IDL.Nat64,
+    'total_value_usd' : IDL.Nat64,
+    'positions' : IDL.Vec(PortfolioPosition),
+    'fully_valued' : IDL.Bool,
+  });
+  const Result_16 = IDL.Variant({
+    'Ok' : PortfolioValuation,
+    'Err' : IDL.Text,
+  });
+  const PriceAggregationConfig = IDL.Record({
+    'min_sources' : IDL.Nat32,
+    'additional_oracles' : IDL.Vec(IDL.Principal),
+    'max_deviation_bps' : IDL.Nat64,
+  });
+  const PriceCandle = IDL.Record({
+    'low' : IDL.Nat64,
+    'high' : IDL.Nat64,
+    'close' : IDL.Nat64,
+    'open' : IDL.Nat64,
+    'samples' : IDL.Nat32,
+    'open_time' : IDL.Nat64,
+  });
+  const Result_17 = IDL.Variant({
+    'Ok' : IDL.Vec(PriceCandle),
+    'Err' : IDL.Text,
+  });
+  const PriceObservation = IDL.Record({
+    'timestamp' : IDL.Nat64,
+    'price_usd' : IDL.Nat64,
+  });
+  const PricePolicy = IDL.Record({
+    'max_price_move_bps' : IDL.Opt(IDL.Nat64),
+    'max_staleness_ns' : IDL.Nat64,
+    'stablecoin_fallback' : IDL.Bool,
+    'cache_duration_ns' : IDL.Nat64,
+    'fallback_enabled' : IDL.Bool,
+  });
   const TransactionStatus = IDL.Variant({
     'Failed' : IDL.Null,
     'FundsLocked' : IDL.Null,
@@ -261,20 +483,75 @@ export const idlFactory = ({ IDL }) => {
     'operation' : OperationType,
     'duration_ms' : IDL.Opt(IDL.Nat64),
     'nav_tokens' : IDL.Nat64,
+    'payment_asset' : IDL.Opt(IDL.Text),
+  });
+  const RecoveryResult = IDL.Variant({
+    'Failed' : IDL.Record({ 'reason' : IDL.Text }),
+    'Success' : IDL.Null,
+    'Partial' : IDL.Record({ 'reason' : IDL.Text }),
+  });
+  const RecoveryLogEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'transaction_id' : IDL.Nat64,
+    'result' : RecoveryResult,
+    'assets_moved' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+    'nav_tokens_moved' : IDL.Nat64,
+    'user' : IDL.Principal,
+    'ckusdc_moved' : IDL.Nat64,
+    'operation' : OperationType,
+    'ledger_calls_succeeded' : IDL.Nat32,
+    'completed_at' : IDL.Nat64,
+    'outcome' : IDL.Opt(RecoveryOutcome),
+    'started_at' : IDL.Nat64,
   });
   const RecoveryStatistics = IDL.Record({
+    'partial_recoveries' : IDL.Nat32,
     'recoveries_last_24h' : IDL.Nat32,
+    'failed_recoveries' : IDL.Nat32,
     'success_rate' : IDL.Float64,
     'average_recovery_time_ms' : IDL.Nat64,
     'total_recoveries' : IDL.Nat32,
+    'successful_recoveries' : IDL.Nat32,
   });
-  const Result_11 = IDL.Variant({ 'Ok' : ResolverInfo, 'Err' : IDL.Text });
+  const ReferencePrice = IDL.Record({
+    'set_by' : IDL.Principal,
+    'timestamp' : IDL.Nat64,
+    'asset_id' : IDL.Text,
+    'price_usd' : IDL.Nat64,
+  });
+  const Result_18 = IDL.Variant({ 'Ok' : ResolverInfo, 'Err' : IDL.Text });
   const ResolverStatistics = IDL.Record({
     'total_resolvers' : IDL.Nat64,
     'active_resolvers' : IDL.Nat64,
     'total_volume_processed' : IDL.Nat64,
     'total_transactions' : IDL.Nat64,
   });
+  const Role = IDL.Variant({
+    'Operator' : IDL.Null,
+    'Pauser' : IDL.Null,
+    'FeeManager' : IDL.Null,
+    'Owner' : IDL.Null,
+    'ResolverManager' : IDL.Null,
+    'AssetManager' : IDL.Null,
+  });
+  const SchemaVersion = IDL.Record({
+    'region' : IDL.Text,
+    'version' : IDL.Nat32,
+    'latest_version' : IDL.Nat32,
+  });
+  const SettlementStepState = IDL.Variant({
+    'Failed' : IDL.Record({ 'error' : IDL.Text }),
+    'Succeeded' : IDL.Record({ 'block_index' : IDL.Nat64 }),
+    'Pending' : IDL.Null,
+  });
+  const SettlementStep = IDL.Record({
+    'transaction_id' : IDL.Nat64,
+    'updated_at' : IDL.Nat64,
+    'step' : IDL.Text,
+    'attempts' : IDL.Nat32,
+    'state' : SettlementStepState,
+    'created_at_time' : IDL.Nat64,
+  });
   const Transaction = IDL.Record({
     'id' : IDL.Nat64,
     'request_id' : IDL.Nat64,
@@ -289,8 +566,36 @@ export const idlFactory = ({ IDL }) => {
     'operation' : OperationType,
     'completed_at' : IDL.Opt(IDL.Nat64),
     'nav_tokens' : IDL.Nat64,
+    'payment_asset' : IDL.Opt(IDL.Text),
+  });
+  const Result_19 = IDL.Variant({ 'Ok' : Transaction, 'Err' : IDL.Text });
+  const LedgerCallKind = IDL.Variant({
+    'Icrc1Transfer' : IDL.Null,
+    'Icrc2TransferFrom' : IDL.Null,
+    'Icrc151Burn' : IDL.Null,
+    'Icrc151Mint' : IDL.Null,
+    'Icrc151Transfer' : IDL.Null,
+    'Icrc151TransferFrom' : IDL.Null,
+  });
+  const LedgerCall = IDL.Record({
+    'to' : IDL.Opt(IDL.Principal),
+    'token_id' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'to_subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'from' : IDL.Opt(IDL.Principal),
+    'kind' : LedgerCallKind,
+    'memo' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'from_subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'ledger' : IDL.Principal,
+    'amount' : IDL.Nat64,
+  });
+  const TransactionEvent = IDL.Record({
+    'transaction_id' : IDL.Nat64,
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'call' : LedgerCall,
+    'error' : IDL.Opt(IDL.Text),
+    'timestamp' : IDL.Nat64,
+    'sequence' : IDL.Nat32,
   });
-  const Result_12 = IDL.Variant({ 'Ok' : Transaction, 'Err' : IDL.Text });
   const TransactionStats = IDL.Record({
     'total_nav_tokens_burned' : IDL.Nat64,
     'failed_transactions' : IDL.Nat64,
@@ -301,10 +606,18 @@ export const idlFactory = ({ IDL }) => {
     'total_transactions' : IDL.Nat64,
     'completed_transactions' : IDL.Nat64,
   });
-  const Result_13 = IDL.Variant({
+  const Result_20 = IDL.Variant({
     'Ok' : TransactionSummary,
     'Err' : IDL.Text,
   });
+  const TransactionTransition = IDL.Record({
+    'to' : TransactionStatus,
+    'transaction_id' : IDL.Nat64,
+    'actor' : IDL.Principal,
+    'from' : TransactionStatus,
+    'timestamp' : IDL.Nat64,
+    'sequence' : IDL.Nat32,
+  });
   const LockedFunds = IDL.Record({
     'transaction_id' : IDL.Nat64,
     'fund_type' : LockedFundType,
@@ -326,6 +639,7 @@ export const idlFactory = ({ IDL }) => {
     'created_bundles' : IDL.Vec(BundleConfig),
     'total_nav_tokens_held' : IDL.Nat64,
   });
+  const Result_21 = IDL.Variant({ 'Ok' : UserPortfolio, 'Err' : IDL.Text });
   const UserTransactionSummary = IDL.Record({
     'buy_transactions' : IDL.Nat64,
     'sell_transactions' : IDL.Nat64,
@@ -335,12 +649,52 @@ export const idlFactory = ({ IDL }) => {
     'last_transaction_at' : IDL.Opt(IDL.Nat64),
     'total_transactions' : IDL.Nat64,
   });
+  const Result_22 = IDL.Variant({ 'Ok' : IDL.Vec(Role), 'Err' : IDL.Text });
+  const AssetPricePolicy = IDL.Record({
+    'max_price_move_bps' : IDL.Opt(IDL.Nat64),
+    'max_staleness_ns' : IDL.Opt(IDL.Nat64),
+    'stablecoin_fallback' : IDL.Opt(IDL.Bool),
+    'cache_duration_ns' : IDL.Opt(IDL.Nat64),
+  });
   const AssetFilter = IDL.Record({
     'payment_tokens_only' : IDL.Opt(IDL.Bool),
     'category' : IDL.Opt(AssetCategory),
     'active_only' : IDL.Bool,
   });
-  const Result_14 = IDL.Variant({
+  const PaymentToken = IDL.Record({
+    'decimals' : IDL.Nat8,
+    'ledger' : IDL.Principal,
+    'asset_id' : IDL.Text,
+  });
+  const PriceHaltReason = IDL.Variant({
+    'PriceMove' : IDL.Record({
+      'observed_usd' : IDL.Nat64,
+      'move_bps' : IDL.Nat64,
+      'previous_usd' : IDL.Nat64,
+    }),
+    'NoValidPrice' : IDL.Record({ 'detail' : IDL.Text }),
+  });
+  const PriceHalt = IDL.Record({
+    'overridden_by' : IDL.Opt(IDL.Principal),
+    'halted_at' : IDL.Nat64,
+    'asset_id' : IDL.Text,
+    'reason' : PriceHaltReason,
+  });
+  const RoleAssignment = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'updated_by' : IDL.Principal,
+    'principal' : IDL.Principal,
+    'roles' : IDL.Vec(Role),
+  });
+  const Result_23 = IDL.Variant({ 'Ok' : PauseState, 'Err' : IDL.Text });
+  const PausableOperation = IDL.Variant({
+    'BundleCreation' : IDL.Null,
+    'ResolverConfirmations' : IDL.Null,
+    'NewSells' : IDL.Null,
+    'InitialFunding' : IDL.Null,
+    'NewBuys' : IDL.Null,
+  });
+  const Result_24 = IDL.Variant({
     'Ok' : IDL.Vec(IDL.Tuple(LockedFundType, IDL.Nat64)),
     'Err' : IDL.Text,
   });
@@ -351,6 +705,8 @@ export const idlFactory = ({ IDL }) => {
     'oracle_ticker' : IDL.Opt(IDL.Text),
   });
   return IDL.Service({
+    'accept_admin' : IDL.Func([], [Result], []),
+    'accept_treasury' : IDL.Func([], [Result], []),
     'add_asset' : IDL.Func(
         [
           IDL.Text,
@@ -364,17 +720,19 @@ export const idlFactory = ({ IDL }) => {
         [Result],
         [],
       ),
-    'calculate_bundle_nav' : IDL.Func([IDL.Nat64], [Result_1], []),
+    'approve_proposal' : IDL.Func([IDL.Nat64], [Result_1], []),
+    'calculate_bundle_nav' : IDL.Func([IDL.Nat64], [Result_2], []),
+    'cancel_proposal' : IDL.Func([IDL.Nat64], [Result_1], []),
     'check_asset_allowance' : IDL.Func(
         [IDL.Text, IDL.Principal],
-        [Result_2],
+        [Result_3],
         ['query'],
       ),
-    'check_ckusdc_allowance' : IDL.Func([IDL.Principal], [Result_2], ['query']),
-    'cleanup_expired_locks' : IDL.Func([], [Result_3], []),
-    'cleanup_expired_transactions' : IDL.Func([], [Result_3], []),
-    'cleanup_inactive_bundles' : IDL.Func([], [Result_3], []),
-    'clear_price_cache' : IDL.Func([], [Result_3], []),
+    'check_ckusdc_allowance' : IDL.Func([IDL.Principal], [Result_3], ['query']),
+    'cleanup_expired_locks' : IDL.Func([], [Result_4], []),
+    'cleanup_expired_transactions' : IDL.Func([], [Result_4], []),
+    'cleanup_inactive_bundles' : IDL.Func([], [Result_4], []),
+    'clear_price_cache' : IDL.Func([], [Result_4], []),
     'confirm_asset_deposit' : IDL.Func([IDL.Nat64], [Result], []),
     'confirm_ckusdc_payment' : IDL.Func([IDL.Nat64], [Result], []),
     'confirm_resolver_payment_and_complete_sell' : IDL.Func(
@@ -382,14 +740,15 @@ export const idlFactory = ({ IDL }) => {
         [Result],
         [],
       ),
-    'create_bundle' : IDL.Func([BundleCreationRequest], [Result_2], []),
+    'create_bundle' : IDL.Func([BundleCreationRequest], [Result_3], []),
     'deactivate_asset' : IDL.Func([IDL.Text], [Result], []),
-    'detect_and_recover_timeouts' : IDL.Func([], [Result_3], []),
+    'detect_and_recover_timeouts' : IDL.Func([], [Result_4], []),
     'dissolve_nav_tokens' : IDL.Func([IDL.Nat64], [Result], []),
+    'dry_run_migrations' : IDL.Func([], [Result_5], ['query']),
     'emergency_pause_canister' : IDL.Func([], [Result], []),
-    'emergency_recovery' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_4], []),
+    'emergency_recovery' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_6], []),
     'emergency_unpause_canister' : IDL.Func([], [Result], []),
-    'execute_quote' : IDL.Func([QuoteObject], [Result_2], []),
+    'execute_quote' : IDL.Func([QuoteObject], [Result_3], []),
     'extend_lock_expiration' : IDL.Func(
         [IDL.Nat64, LockedFundType, IDL.Nat64],
         [Result],
@@ -403,29 +762,53 @@ export const idlFactory = ({ IDL }) => {
       ),
     'get_active_resolvers' : IDL.Func([], [IDL.Vec(ResolverInfo)], ['query']),
     'get_admin' : IDL.Func([], [IDL.Opt(IDL.Principal)], ['query']),
-    'get_asset' : IDL.Func([IDL.Text], [Result_5], ['query']),
-    'get_asset_price' : IDL.Func([IDL.Text], [Result_6], []),
+    'get_admin_actions' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Nat64, IDL.Opt(AdminActionFilter)],
+        [IDL.Vec(AdminAction)],
+        ['query'],
+      ),
+    'get_analytics_config' : IDL.Func([], [AnalyticsConfig], ['query']),
+    'get_asset' : IDL.Func([IDL.Text], [Result_7], ['query']),
+    'get_asset_price' : IDL.Func([IDL.Text], [Result_8], []),
     'get_asset_summary' : IDL.Func([], [IDL.Vec(AssetSummary)], ['query']),
-    'get_assignment' : IDL.Func([IDL.Nat64], [Result_7], ['query']),
-    'get_bundle' : IDL.Func([IDL.Nat64], [Result_8], ['query']),
+    'get_assignment' : IDL.Func([IDL.Nat64], [Result_9], ['query']),
+    'get_bundle' : IDL.Func([IDL.Nat64], [Result_10], ['query']),
+    'get_bundle_analytics' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(IDL.Nat64)],
+        [Result_11],
+        ['query'],
+      ),
     'get_bundle_holdings' : IDL.Func(
         [IDL.Nat64],
         [IDL.Vec(BundleHolding)],
         ['query'],
       ),
-    'get_bundle_summary' : IDL.Func([IDL.Nat64], [Result_9], []),
+    'get_bundle_return' : IDL.Func(
+        [IDL.Nat64, IDL.Nat64, IDL.Nat64],
+        [Result_12],
+        ['query'],
+      ),
+    'get_bundle_summary' : IDL.Func([IDL.Nat64], [Result_13], []),
     'get_bundle_transaction_history' : IDL.Func(
         [IDL.Nat64],
         [BundleTransactionHistory],
         ['query'],
       ),
     'get_bundles_list' : IDL.Func([], [IDL.Vec(BundleListItem)], []),
+    'get_bundles_list_sorted' : IDL.Func(
+        [BundleSortKey, IDL.Bool],
+        [IDL.Vec(BundleListItem)],
+        [],
+      ),
     'get_cache_statistics' : IDL.Func([], [CacheStatistics], ['query']),
     'get_cached_price' : IDL.Func([IDL.Text], [IDL.Opt(AssetPrice)], ['query']),
     'get_canister_info' : IDL.Func([], [CanisterInfo], ['query']),
     'get_canister_status' : IDL.Func([], [CanisterStatus], ['query']),
+    'get_ckusdc_ledger' : IDL.Func([], [IDL.Opt(IDL.Principal)], ['query']),
     'get_current_week' : IDL.Func([], [IDL.Nat64], ['query']),
     'get_default_platform_fee_bps' : IDL.Func([], [IDL.Nat64], ['query']),
+    'get_ema' : IDL.Func([IDL.Text, IDL.Nat64], [Result_3], ['query']),
+    'get_governance_settings' : IDL.Func([], [GovernanceConfig], ['query']),
     'get_leaderboard' : IDL.Func(
         [IDL.Opt(IDL.Nat64), IDL.Nat64],
         [IDL.Vec(IDL.Tuple(IDL.Principal, IDL.Nat64))],
@@ -433,29 +816,100 @@ export const idlFactory = ({ IDL }) => {
       ),
     'get_lock_expiration_time' : IDL.Func(
         [IDL.Nat64, LockedFundType],
-        [Result_2],
+        [Result_3],
+        ['query'],
+      ),
+    'get_nav_history' : IDL.Func(
+        [IDL.Nat64, CandleInterval, IDL.Nat64, IDL.Nat64],
+        [Result_14],
         ['query'],
       ),
-    'get_nav_precision_report' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'get_nav_precision_report' : IDL.Func([IDL.Nat64], [Result_15], []),
     'get_oracle_config' : IDL.Func([], [IDL.Opt(OracleConfig)], ['query']),
+    'get_pause_state' : IDL.Func([], [PauseState], ['query']),
+    'get_pending_admin_nomination' : IDL.Func(
+        [],
+        [IDL.Opt(Nomination)],
+        ['query'],
+      ),
+    'get_pending_treasury_nomination' : IDL.Func(
+        [],
+        [IDL.Opt(Nomination)],
+        ['query'],
+      ),
     'get_platform_treasury' : IDL.Func([], [IDL.Opt(IDL.Principal)], ['query']),
-    'get_portfolio_value' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_2], []),
+    'get_portfolio_valuation' : IDL.Func(
+        [IDL.Opt(IDL.Principal)],
+        [Result_16],
+        [],
+      ),
+    'get_portfolio_value' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_3], []),
+    'get_price_aggregation_config' : IDL.Func(
+        [],
+        [PriceAggregationConfig],
+        ['query'],
+      ),
+    'get_price_history' : IDL.Func(
+        [IDL.Text, CandleInterval, IDL.Nat64, IDL.Nat64],
+        [Result_17],
+        ['query'],
+      ),
+    'get_price_observations' : IDL.Func(
+        [IDL.Text, IDL.Nat64],
+        [IDL.Vec(PriceObservation)],
+        ['query'],
+      ),
+    'get_price_policy' : IDL.Func([IDL.Text], [PricePolicy], ['query']),
+    'get_proposal' : IDL.Func([IDL.Nat64], [Result_1], ['query']),
     'get_recent_transactions' : IDL.Func(
         [IDL.Nat64],
         [IDL.Vec(TransactionSummary)],
         ['query'],
       ),
+    'get_recovery_log' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Nat64],
+        [IDL.Vec(RecoveryLogEntry)],
+        ['query'],
+      ),
     'get_recovery_statistics' : IDL.Func([], [RecoveryStatistics], ['query']),
-    'get_resolver' : IDL.Func([IDL.Principal], [Result_11], ['query']),
+    'get_reference_price' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(ReferencePrice)],
+        ['query'],
+      ),
+    'get_resolver' : IDL.Func([IDL.Principal], [Result_18], ['query']),
     'get_resolver_statistics' : IDL.Func([], [ResolverStatistics], ['query']),
-    'get_transaction' : IDL.Func([IDL.Nat64], [Result_12], ['query']),
+    'get_roles' : IDL.Func([IDL.Principal], [IDL.Vec(Role)], ['query']),
+    'get_schema_versions' : IDL.Func([], [IDL.Vec(SchemaVersion)], ['query']),
+    'get_settlement_steps' : IDL.Func(
+        [IDL.Nat64],
+        [IDL.Vec(SettlementStep)],
+        ['query'],
+      ),
+    'get_transaction' : IDL.Func([IDL.Nat64], [Result_19], ['query']),
+    'get_transaction_events' : IDL.Func(
+        [IDL.Nat64],
+        [IDL.Vec(TransactionEvent)],
+        ['query'],
+      ),
+    'get_transaction_recovery_log' : IDL.Func(
+        [IDL.Nat64],
+        [IDL.Vec(RecoveryLogEntry)],
+        ['query'],
+      ),
     'get_transaction_stats' : IDL.Func([], [TransactionStats], ['query']),
-    'get_transaction_summary' : IDL.Func([IDL.Nat64], [Result_13], ['query']),
+    'get_transaction_summary' : IDL.Func([IDL.Nat64], [Result_20], ['query']),
+    'get_transaction_transitions' : IDL.Func(
+        [IDL.Nat64],
+        [IDL.Vec(TransactionTransition)],
+        ['query'],
+      ),
     'get_transactions_by_status' : IDL.Func(
         [TransactionStatus],
         [IDL.Vec(Transaction)],
         ['query'],
       ),
+    'get_twap' : IDL.Func([IDL.Text, IDL.Nat64], [Result_3], ['query']),
     'get_user_bundles' : IDL.Func(
         [IDL.Principal],
         [IDL.Vec(BundleConfig)],
@@ -471,11 +925,7 @@ export const idlFactory = ({ IDL }) => {
         [IDL.Nat64],
         ['query'],
       ),
-    'get_user_portfolio' : IDL.Func(
-        [IDL.Principal],
-        [UserPortfolio],
-        ['query'],
-      ),
+    'get_user_portfolio' : IDL.Func([IDL.Principal], [Result_21], []),
     'get_user_total_locked_amount' : IDL.Func(
         [IDL.Principal, LockedFundType],
         [IDL.Nat64],
@@ -496,43 +946,105 @@ export const idlFactory = ({ IDL }) => {
         [IDL.Nat64],
         ['query'],
       ),
+    'grant_role' : IDL.Func([IDL.Principal, Role], [Result_22], []),
     'is_fund_already_locked' : IDL.Func(
         [IDL.Nat64, LockedFundType],
         [IDL.Bool],
         ['query'],
       ),
     'list_active_bundles' : IDL.Func([], [IDL.Vec(BundleConfig)], ['query']),
+    'list_asset_price_policies' : IDL.Func(
+        [],
+        [IDL.Vec(IDL.Tuple(IDL.Text, AssetPricePolicy))],
+        ['query'],
+      ),
     'list_assets' : IDL.Func(
         [IDL.Opt(AssetFilter)],
         [IDL.Vec(AssetInfo)],
         ['query'],
       ),
     'list_cached_prices' : IDL.Func([], [IDL.Vec(AssetPrice)], ['query']),
+    'list_payment_tokens' : IDL.Func([], [IDL.Vec(PaymentToken)], ['query']),
+    'list_price_halts' : IDL.Func([], [IDL.Vec(PriceHalt)], ['query']),
+    'list_proposals' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Nat64, IDL.Bool],
+        [IDL.Vec(Proposal)],
+        ['query'],
+      ),
+    'list_role_assignments' : IDL.Func(
+        [],
+        [IDL.Vec(RoleAssignment)],
+        ['query'],
+      ),
     'list_valid_cached_prices' : IDL.Func([], [IDL.Vec(AssetPrice)], ['query']),
     'lock_user_funds_with_validation' : IDL.Func(
         [IDL.Nat64, LockedFundType, IDL.Nat64],
         [Result],
         [],
       ),
+    'override_price_halt' : IDL.Func([IDL.Text, IDL.Text], [Result], []),
+    'pause_bundle' : IDL.Func([IDL.Nat64], [Result_23], []),
+    'propose_admin_action' : IDL.Func([GovernedAction], [Result_3], []),
+    'recover_paused_transaction' : IDL.Func([IDL.Nat64], [Result], []),
     'register_resolver' : IDL.Func(
         [IDL.Principal, IDL.Text, IDL.Nat64],
         [Result],
         [],
       ),
+    'resolve_settlement_step' : IDL.Func(
+        [IDL.Nat64, IDL.Text, IDL.Opt(IDL.Nat64)],
+        [Result],
+        [],
+      ),
+    'revoke_admin_nomination' : IDL.Func([], [Result], []),
+    'revoke_role' : IDL.Func([IDL.Principal, Role], [Result_22], []),
+    'revoke_treasury_nomination' : IDL.Func([], [Result], []),
     'search_assets' : IDL.Func([IDL.Text], [IDL.Vec(AssetInfo)], ['query']),
     'search_bundles' : IDL.Func([IDL.Text], [IDL.Vec(BundleConfig)], ['query']),
-    'set_admin' : IDL.Func([IDL.Principal], [Result], []),
-    'set_bundle_platform_fee' : IDL.Func([IDL.Nat64, IDL.Nat64], [Result], []),
-    'set_coordinator_public_key' : IDL.Func([IDL.Text], [Result], []),
-    'set_default_platform_fee_bps' : IDL.Func([IDL.Nat64], [Result], []),
+    'set_admin' : IDL.Func([IDL.Principal], [Result_3], []),
+    'set_analytics_config' : IDL.Func([AnalyticsConfig], [Result], []),
+    'set_asset_price_policy' : IDL.Func(
+        [IDL.Text, IDL.Opt(AssetPricePolicy)],
+        [Result],
+        [],
+      ),
+    'set_bundle_platform_fee' : IDL.Func(
+        [IDL.Nat64, IDL.Nat64],
+        [Result_3],
+        [],
+      ),
+    'set_bundle_valuation_mode' : IDL.Func(
+        [IDL.Nat64, ValuationMode],
+        [Result_3],
+        [],
+      ),
+    'set_ckusdc_ledger' : IDL.Func([IDL.Principal], [Result_3], []),
+    'set_coordinator_public_key' : IDL.Func([IDL.Text], [Result_3], []),
+    'set_default_platform_fee_bps' : IDL.Func([IDL.Nat64], [Result_3], []),
+    'set_operation_paused' : IDL.Func(
+        [PausableOperation, IDL.Bool],
+        [Result_23],
+        [],
+      ),
     'set_oracle_config' : IDL.Func([OracleConfig], [Result], []),
-    'set_platform_treasury' : IDL.Func([IDL.Principal], [Result], []),
+    'set_platform_treasury' : IDL.Func([IDL.Principal], [Result_3], []),
+    'set_price_aggregation_config' : IDL.Func(
+        [PriceAggregationConfig],
+        [Result],
+        [],
+      ),
     'set_quote_api_principal' : IDL.Func([IDL.Principal], [Result], []),
-    'unlock_all_transaction_funds' : IDL.Func([IDL.Nat64], [Result_14], []),
+    'set_reference_price' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat64)],
+        [Result],
+        [],
+      ),
+    'unlock_all_transaction_funds' : IDL.Func([IDL.Nat64], [Result_24], []),
+    'unpause_bundle' : IDL.Func([IDL.Nat64], [Result_23], []),
     'update_asset' : IDL.Func([IDL.Text, AssetInfoUpdate], [Result], []),
     'update_asset_token_location' : IDL.Func(
         [IDL.Text, TokenLocation],
-        [Result],
+        [Result_3],
         [],
       ),
     'update_resolver_status' : IDL.Func(
@@ -2,6 +2,27 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface AdminAction {
+  'id' : bigint,
+  'admin' : Principal,
+  'after' : [] | [string],
+  'action_type' : string,
+  'target' : string,
+  'before' : [] | [string],
+  'timestamp' : bigint,
+  'details' : [] | [string],
+}
+export interface AdminActionFilter {
+  'admin' : [] | [Principal],
+  'from_time' : [] | [bigint],
+  'to_time' : [] | [bigint],
+  'action_type' : [] | [string],
+  'target' : [] | [string],
+}
+export interface AnalyticsConfig {
+  'benchmark_bundle_id' : [] | [bigint],
+  'risk_free_rate_bps' : bigint,
+}
 export interface AssetAllocation {
   'token_location' : TokenLocation,
   'asset_id' : string,
@@ -55,6 +76,12 @@ export interface AssetPrice {
   'confidence' : number,
   'price_usd' : bigint,
 }
+export interface AssetPricePolicy {
+  'max_price_move_bps' : [] | [bigint],
+  'max_staleness_ns' : [] | [bigint],
+  'stablecoin_fallback' : [] | [boolean],
+  'cache_duration_ns' : [] | [bigint],
+}
 export interface AssetSummary {
   'id' : string,
   'bundles_using' : number,
@@ -70,6 +97,20 @@ export interface AssetValue {
   'amount' : bigint,
   'percentage' : number,
 }
+export interface BundleAnalytics {
+  'benchmark_bundle_id' : [] | [bigint],
+  'return_7d_bps' : [] | [bigint],
+  'bundle_id' : bigint,
+  'return_ytd_bps' : [] | [bigint],
+  'correlation' : [] | [number],
+  'return_24h_bps' : [] | [bigint],
+  'return_inception_bps' : [] | [bigint],
+  'volatility_bps' : [] | [bigint],
+  'return_30d_bps' : [] | [bigint],
+  'calculated_at' : bigint,
+  'max_drawdown_bps' : [] | [bigint],
+  'sharpe_ratio' : [] | [number],
+}
 export interface BundleConfig {
   'id' : bigint,
   'creator' : Principal,
@@ -79,6 +120,7 @@ export interface BundleConfig {
   'platform_fee_bps' : [] | [bigint],
   'token_location' : TokenLocation,
   'allocations' : Array<AssetAllocation>,
+  'valuation_mode' : [] | [ValuationMode],
   'is_active' : boolean,
   'symbol' : string,
 }
@@ -100,6 +142,7 @@ export interface BundleListItem {
   'nav_per_token' : bigint,
   'name' : string,
   'description' : [] | [string],
+  'analytics' : BundleAnalytics,
   'created_at' : bigint,
   'total_nav_usd' : bigint,
   'token_location' : TokenLocation,
@@ -113,9 +156,27 @@ export interface BundleNAV {
   'nav_per_token' : bigint,
   'bundle_id' : bigint,
   'total_nav_usd' : bigint,
+  'fallback_assets' : Array<string>,
   'calculated_at' : bigint,
   'total_tokens' : bigint,
 }
+export interface BundleReturn {
+  'end' : BundleNAV,
+  'bundle_id' : bigint,
+  'start' : BundleNAV,
+  'return_bps' : bigint,
+}
+export type BundleSortKey = { 'Volatility' : null } |
+  { 'ReturnInception' : null } |
+  { 'TotalNav' : null } |
+  { 'Sharpe' : null } |
+  { 'Holders' : null } |
+  { 'Return24h' : null } |
+  { 'Return30d' : null } |
+  { 'ReturnYtd' : null } |
+  { 'Return7d' : null } |
+  { 'MaxDrawdown' : null } |
+  { 'NavPerToken' : null };
 export interface BundleSummary {
   'id' : bigint,
   'creator' : Principal,
@@ -137,12 +198,17 @@ export interface BundleTransactionHistory {
 }
 export interface CacheStatistics {
   'total_entries' : number,
+  'max_staleness_seconds' : bigint,
+  'oracle_canister' : Principal,
   'cache_hit_rate' : number,
   'valid_entries' : number,
   'expired_entries' : number,
   'oldest_entry_age_seconds' : bigint,
   'cache_duration_seconds' : bigint,
 }
+export type CandleInterval = { 'Day' : null } |
+  { 'Hour' : null } |
+  { 'Minute' : null };
 export interface CanisterInfo {
   'version' : string,
   'total_assets' : bigint,
@@ -156,8 +222,45 @@ export interface CanisterStatus {
   'memory_usage' : MemoryUsage,
   'total_nav_tokens' : bigint,
 }
+export interface GovernanceConfig {
+  'delay_ns' : bigint,
+  'required_approvals' : number,
+}
+export type GovernedAction = { 'SetAdmin' : { 'new_admin' : Principal } } |
+  { 'SetPlatformTreasury' : { 'treasury' : Principal } } |
+  { 'SetCoordinatorPublicKey' : { 'public_key_hex' : string } } |
+  { 'SetDefaultPlatformFeeBps' : { 'fee_bps' : bigint } } |
+  {
+    'UpdateAssetTokenLocation' : {
+      'token_location' : TokenLocation,
+      'asset_id' : string,
+    }
+  } |
+  { 'SetCkusdcLedger' : { 'ledger' : Principal } } |
+  { 'SetBundlePlatformFee' : { 'bundle_id' : bigint, 'fee_bps' : bigint } } |
+  { 'SetGovernanceConfig' : { 'config' : GovernanceConfig } } |
+  {
+    'SetBundleValuationMode' : { 'bundle_id' : bigint, 'mode' : ValuationMode }
+  };
+export interface LedgerCall {
+  'to' : [] | [Principal],
+  'token_id' : [] | [Uint8Array | number[]],
+  'to_subaccount' : [] | [Uint8Array | number[]],
+  'from' : [] | [Principal],
+  'kind' : LedgerCallKind,
+  'memo' : [] | [Uint8Array | number[]],
+  'from_subaccount' : [] | [Uint8Array | number[]],
+  'ledger' : Principal,
+  'amount' : bigint,
+}
+export type LedgerCallKind = { 'Icrc1Transfer' : null } |
+  { 'Icrc2TransferFrom' : null } |
+  { 'Icrc151Burn' : null } |
+  { 'Icrc151Mint' : null } |
+  { 'Icrc151Transfer' : null } |
+  { 'Icrc151TransferFrom' : null };
 export type LockedFundType = { 'NAVTokens' : { 'bundle_id' : bigint } } |
-  { 'CkUSDC' : null };
+  { 'Payment' : { 'asset_id' : string } };
 export interface LockedFunds {
   'transaction_id' : bigint,
   'fund_type' : LockedFundType,
@@ -170,6 +273,18 @@ export interface MemoryUsage {
   'asset_registry_entries' : bigint,
   'bundle_storage_entries' : bigint,
 }
+export interface MigrationReport {
+  'steps' : Array<MigrationStep>,
+  'checks' : Array<RegionCheck>,
+  'dry_run' : boolean,
+}
+export interface MigrationStep {
+  'region' : string,
+  'records' : bigint,
+  'to_version' : number,
+  'description' : string,
+  'from_version' : number,
+}
 export interface NAVPrecisionReport {
   'nav_per_token_18_decimals' : bigint,
   'bundle_id' : bigint,
@@ -186,15 +301,111 @@ export interface NAVTokenHolding {
   'last_updated' : bigint,
   'amount' : bigint,
 }
+export interface Nomination {
+  'nominee' : Principal,
+  'nominated_at' : bigint,
+  'nominated_by' : Principal,
+  'expires_at' : bigint,
+}
 export type OperationType = { 'Buy' : { 'ckusdc_amount' : bigint } } |
   { 'Sell' : { 'nav_tokens' : bigint } } |
   { 'InitialBuy' : { 'usd_amount' : bigint, 'nav_tokens' : bigint } };
 export interface OracleConfig {
+  'max_price_move_bps' : [] | [bigint],
   'oracle_canister' : Principal,
   'max_staleness_ns' : bigint,
   'cache_duration_ns' : bigint,
   'fallback_enabled' : boolean,
 }
+export type PausableOperation = { 'BundleCreation' : null } |
+  { 'ResolverConfirmations' : null } |
+  { 'NewSells' : null } |
+  { 'InitialFunding' : null } |
+  { 'NewBuys' : null };
+export interface PauseState {
+  'initial_funding' : boolean,
+  'updated_at' : bigint,
+  'updated_by' : [] | [Principal],
+  'paused_bundles' : BigUint64Array | bigint[],
+  'bundle_creation' : boolean,
+  'resolver_confirmations' : boolean,
+  'new_buys' : boolean,
+  'new_sells' : boolean,
+}
+export interface PaymentToken {
+  'decimals' : number,
+  'ledger' : Principal,
+  'asset_id' : string,
+}
+export interface PortfolioPosition {
+  'nav_per_token' : [] | [bigint],
+  'bundle_name' : string,
+  'balance' : bigint,
+  'bundle_id' : bigint,
+  'cost_basis_usd' : [] | [bigint],
+  'value_usd' : [] | [bigint],
+  'unrealized_pnl_usd' : [] | [bigint],
+  'symbol' : string,
+}
+export interface PortfolioValuation {
+  'total_cost_basis_usd' : [] | [bigint],
+  'user' : Principal,
+  'valued_at' : bigint,
+  'total_value_usd' : bigint,
+  'positions' : Array<PortfolioPosition>,
+  'fully_valued' : boolean,
+}
+export interface PriceAggregationConfig {
+  'min_sources' : number,
+  'additional_oracles' : Array<Principal>,
+  'max_deviation_bps' : bigint,
+}
+export interface PriceCandle {
+  'low' : bigint,
+  'high' : bigint,
+  'close' : bigint,
+  'open' : bigint,
+  'samples' : number,
+  'open_time' : bigint,
+}
+export interface PriceHalt {
+  'overridden_by' : [] | [Principal],
+  'halted_at' : bigint,
+  'asset_id' : string,
+  'reason' : PriceHaltReason,
+}
+export type PriceHaltReason = {
+    'PriceMove' : {
+      'observed_usd' : bigint,
+      'move_bps' : bigint,
+      'previous_usd' : bigint,
+    }
+  } |
+  { 'NoValidPrice' : { 'detail' : string } };
+export interface PriceObservation { 'timestamp' : bigint, 'price_usd' : bigint }
+export interface PricePolicy {
+  'max_price_move_bps' : [] | [bigint],
+  'max_staleness_ns' : bigint,
+  'stablecoin_fallback' : boolean,
+  'cache_duration_ns' : bigint,
+  'fallback_enabled' : boolean,
+}
+export interface Proposal {
+  'id' : bigint,
+  'status' : ProposalStatus,
+  'action' : GovernedAction,
+  'executable_at' : bigint,
+  'required_approvals' : number,
+  'created_at' : bigint,
+  'proposer' : Principal,
+  'approvals' : Array<Principal>,
+  'resolved_at' : [] | [bigint],
+  'resolved_by' : [] | [Principal],
+}
+export type ProposalStatus = { 'Failed' : { 'error' : string } } |
+  { 'Executed' : null } |
+  { 'Cancelled' : null } |
+  { 'Pending' : null };
 export interface QuoteAssignment {
   'request_id' : bigint,
   'resolver' : Principal,
@@ -205,6 +416,7 @@ export interface QuoteAssignment {
   'valid_until' : bigint,
   'estimated_nav' : bigint,
   'nav_tokens' : bigint,
+  'payment_asset' : [] | [string],
 }
 export interface QuoteObject {
   'resolver' : Principal,
@@ -217,19 +429,64 @@ export interface QuoteObject {
   'nonce' : bigint,
   'operation' : OperationType,
   'nav_tokens' : bigint,
+  'payment_asset' : [] | [string],
 }
+export interface RecoveryLogEntry {
+  'id' : bigint,
+  'transaction_id' : bigint,
+  'result' : RecoveryResult,
+  'assets_moved' : Array<[string, bigint]>,
+  'nav_tokens_moved' : bigint,
+  'user' : Principal,
+  'ckusdc_moved' : bigint,
+  'operation' : OperationType,
+  'ledger_calls_succeeded' : number,
+  'completed_at' : bigint,
+  'outcome' : [] | [RecoveryOutcome],
+  'started_at' : bigint,
+}
+export type RecoveryOutcome = { 'CkUSDCRefunded' : { 'amount' : bigint } } |
+  { 'ResolverRefunded' : { 'ckusdc' : bigint, 'nav_tokens' : bigint } } |
+  { 'NothingToRecover' : null } |
+  { 'DissolutionCompleted' : { 'assets' : Array<[string, bigint]> } } |
+  { 'NavTokensReturned' : { 'amount' : bigint } } |
+  { 'BuyCompleted' : null } |
+  {
+    'CollateralReturned' : {
+      'assets' : Array<[string, bigint]>,
+      'ckusdc' : bigint,
+    }
+  };
 export interface RecoveryReport {
   'recovered_assets' : Array<[string, bigint]>,
   'recovered_ckusdc' : bigint,
   'user' : Principal,
+  'outcomes' : Array<[bigint, RecoveryOutcome]>,
   'timestamp' : bigint,
   'recovered_nav_tokens' : bigint,
 }
+export type RecoveryResult = { 'Failed' : { 'reason' : string } } |
+  { 'Success' : null } |
+  { 'Partial' : { 'reason' : string } };
 export interface RecoveryStatistics {
+  'partial_recoveries' : number,
   'recoveries_last_24h' : number,
+  'failed_recoveries' : number,
   'success_rate' : number,
   'average_recovery_time_ms' : bigint,
   'total_recoveries' : number,
+  'successful_recoveries' : number,
+}
+export interface ReferencePrice {
+  'set_by' : Principal,
+  'timestamp' : bigint,
+  'asset_id' : string,
+  'price_usd' : bigint,
+}
+export interface RegionCheck {
+  'region' : string,
+  'records' : bigint,
+  'error' : [] | [string],
 }
 export interface ResolverInfo {
   'principal' : Principal,
@@ -250,34 +507,82 @@ export interface ResolverStatistics {
 }
 export type Result = { 'Ok' : null } |
   { 'Err' : string };
-export type Result_1 = { 'Ok' : BundleNAV } |
+export type Result_1 = { 'Ok' : Proposal } |
   { 'Err' : string };
-export type Result_10 = { 'Ok' : NAVPrecisionReport } |
+export type Result_10 = { 'Ok' : BundleConfig } |
   { 'Err' : string };
-export type Result_11 = { 'Ok' : ResolverInfo } |
+export type Result_11 = { 'Ok' : BundleAnalytics } |
   { 'Err' : string };
-export type Result_12 = { 'Ok' : Transaction } |
+export type Result_12 = { 'Ok' : BundleReturn } |
   { 'Err' : string };
-export type Result_13 = { 'Ok' : TransactionSummary } |
+export type Result_13 = { 'Ok' : BundleSummary } |
   { 'Err' : string };
-export type Result_14 = { 'Ok' : Array<[LockedFundType, bigint]> } |
+export type Result_14 = { 'Ok' : Array<BundleNAV> } |
   { 'Err' : string };
-export type Result_2 = { 'Ok' : bigint } |
+export type Result_15 = { 'Ok' : NAVPrecisionReport } |
   { 'Err' : string };
-export type Result_3 = { 'Ok' : number } |
+export type Result_16 = { 'Ok' : PortfolioValuation } |
   { 'Err' : string };
-export type Result_4 = { 'Ok' : RecoveryReport } |
+export type Result_17 = { 'Ok' : Array<PriceCandle> } |
   { 'Err' : string };
-export type Result_5 = { 'Ok' : AssetInfo } |
+export type Result_18 = { 'Ok' : ResolverInfo } |
   { 'Err' : string };
-export type Result_6 = { 'Ok' : AssetPrice } |
+export type Result_19 = { 'Ok' : Transaction } |
   { 'Err' : string };
-export type Result_7 = { 'Ok' : QuoteAssignment } |
+export type Result_2 = { 'Ok' : BundleNAV } |
   { 'Err' : string };
-export type Result_8 = { 'Ok' : BundleConfig } |
+export type Result_20 = { 'Ok' : TransactionSummary } |
   { 'Err' : string };
-export type Result_9 = { 'Ok' : BundleSummary } |
+export type Result_21 = { 'Ok' : UserPortfolio } |
   { 'Err' : string };
+export type Result_22 = { 'Ok' : Array<Role> } |
+  { 'Err' : string };
+export type Result_23 = { 'Ok' : PauseState } |
+  { 'Err' : string };
+export type Result_24 = { 'Ok' : Array<[LockedFundType, bigint]> } |
+  { 'Err' : string };
+export type Result_3 = { 'Ok' : bigint } |
+  { 'Err' : string };
+export type Result_4 = { 'Ok' : number } |
+  { 'Err' : string };
+export type Result_5 = { 'Ok' : MigrationReport } |
+  { 'Err' : string };
+export type Result_6 = { 'Ok' : RecoveryReport } |
+  { 'Err' : string };
+export type Result_7 = { 'Ok' : AssetInfo } |
+  { 'Err' : string };
+export type Result_8 = { 'Ok' : AssetPrice } |
+  { 'Err' : string };
+export type Result_9 = { 'Ok' : QuoteAssignment } |
+  { 'Err' : string };
+export type Role = { 'Operator' : null } |
+  { 'Pauser' : null } |
+  { 'FeeManager' : null } |
+  { 'Owner' : null } |
+  { 'ResolverManager' : null } |
+  { 'AssetManager' : null };
+export interface RoleAssignment {
+  'updated_at' : bigint,
+  'updated_by' : Principal,
+  'principal' : Principal,
+  'roles' : Array<Role>,
+}
+export interface SchemaVersion {
+  'region' : string,
+  'version' : number,
+  'latest_version' : number,
+}
+export interface SettlementStep {
+  'transaction_id' : bigint,
+  'updated_at' : bigint,
+  'step' : string,
+  'attempts' : number,
+  'state' : SettlementStepState,
+  'created_at_time' : bigint,
+}
+export type SettlementStepState = { 'Failed' : { 'error' : string } } |
+  { 'Succeeded' : { 'block_index' : bigint } } |
+  { 'Pending' : null };
 export type TokenLocation = {
     'ICRC151' : { 'token_id' : Uint8Array | number[], 'ledger' : Principal }
   } |
@@ -296,6 +601,15 @@ export interface Transaction {
   'operation' : OperationType,
   'completed_at' : [] | [bigint],
   'nav_tokens' : bigint,
+  'payment_asset' : [] | [string],
+}
+export interface TransactionEvent {
+  'transaction_id' : bigint,
+  'block_index' : [] | [bigint],
+  'call' : LedgerCall,
+  'error' : [] | [string],
+  'timestamp' : bigint,
+  'sequence' : number,
 }
 export interface TransactionStats {
   'total_nav_tokens_burned' : bigint,
@@ -325,6 +639,15 @@ export interface TransactionSummary {
   'operation' : OperationType,
   'duration_ms' : [] | [bigint],
   'nav_tokens' : bigint,
+  'payment_asset' : [] | [string],
+}
+export interface TransactionTransition {
+  'to' : TransactionStatus,
+  'transaction_id' : bigint,
+  'actor' : Principal,
+  'from' : TransactionStatus,
+  'timestamp' : bigint,
+  'sequence' : number,
 }
 export interface UserPortfolio {
   'total_bundles_created' : number,
@@ -342,7 +665,11 @@ export interface UserTransactionSummary {
   'last_transaction_at' : [] | [bigint],
   'total_transactions' : bigint,
 }
+export type ValuationMode = { 'Spot' : null } |
+  { 'Twap' : { 'window_ns' : bigint } };
 export interface _SERVICE {
+  'accept_admin' : ActorMethod<[], Result>,
+  'accept_treasury' : ActorMethod<[], Result>,
   'add_asset' : ActorMethod<
     [
       string,
@@ -355,24 +682,27 @@ export interface _SERVICE {
     ],
     Result
   >,
-  'calculate_bundle_nav' : ActorMethod<[bigint], Result_1>,
-  'check_asset_allowance' : ActorMethod<[string, Principal], Result_2>,
-  'check_ckusdc_allowance' : ActorMethod<[Principal], Result_2>,
-  'cleanup_expired_locks' : ActorMethod<[], Result_3>,
-  'cleanup_expired_transactions' : ActorMethod<[], Result_3>,
-  'cleanup_inactive_bundles' : ActorMethod<[], Result_3>,
-  'clear_price_cache' : ActorMethod<[], Result_3>,
+  'approve_proposal' : ActorMethod<[bigint], Result_1>,
+  'calculate_bundle_nav' : ActorMethod<[bigint], Result_2>,
+  'cancel_proposal' : ActorMethod<[bigint], Result_1>,
+  'check_asset_allowance' : ActorMethod<[string, Principal], Result_3>,
+  'check_ckusdc_allowance' : ActorMethod<[Principal], Result_3>,
+  'cleanup_expired_locks' : ActorMethod<[], Result_4>,
+  'cleanup_expired_transactions' : ActorMethod<[], Result_4>,
+  'cleanup_inactive_bundles' : ActorMethod<[], Result_4>,
+  'clear_price_cache' : ActorMethod<[], Result_4>,
   'confirm_asset_deposit' : ActorMethod<[bigint], Result>,
   'confirm_ckusdc_payment' : ActorMethod<[bigint], Result>,
   'confirm_resolver_payment_and_complete_sell' : ActorMethod<[bigint], Result>,
-  'create_bundle' : ActorMethod<[BundleCreationRequest], Result_2>,
+  'create_bundle' : ActorMethod<[BundleCreationRequest], Result_3>,
   'deactivate_asset' : ActorMethod<[string], Result>,
-  'detect_and_recover_timeouts' : ActorMethod<[], Result_3>,
+  'detect_and_recover_timeouts' : ActorMethod<[], Result_4>,
   'dissolve_nav_tokens' : ActorMethod<[bigint], Result>,
+  'dry_run_migrations' : ActorMethod<[], Result_5>,
   'emergency_pause_canister' : ActorMethod<[], Result>,
-  'emergency_recovery' : ActorMethod<[[] | [Principal]], Result_4>,
+  'emergency_recovery' : ActorMethod<[[] | [Principal]], Result_6>,
   'emergency_unpause_canister' : ActorMethod<[], Result>,
-  'execute_quote' : ActorMethod<[QuoteObject], Result_2>,
+  'execute_quote' : ActorMethod<[QuoteObject], Result_3>,
   'extend_lock_expiration' : ActorMethod<
     [bigint, LockedFundType, bigint],
     Result
@@ -381,48 +711,99 @@ export interface _SERVICE {
   'format_nav_display' : ActorMethod<[bigint, number], string>,
   'get_active_resolvers' : ActorMethod<[], Array<ResolverInfo>>,
   'get_admin' : ActorMethod<[], [] | [Principal]>,
-  'get_asset' : ActorMethod<[string], Result_5>,
-  'get_asset_price' : ActorMethod<[string], Result_6>,
+  'get_admin_actions' : ActorMethod<
+    [[] | [bigint], bigint, [] | [AdminActionFilter]],
+    Array<AdminAction>
+  >,
+  'get_analytics_config' : ActorMethod<[], AnalyticsConfig>,
+  'get_asset' : ActorMethod<[string], Result_7>,
+  'get_asset_price' : ActorMethod<[string], Result_8>,
   'get_asset_summary' : ActorMethod<[], Array<AssetSummary>>,
-  'get_assignment' : ActorMethod<[bigint], Result_7>,
-  'get_bundle' : ActorMethod<[bigint], Result_8>,
+  'get_assignment' : ActorMethod<[bigint], Result_9>,
+  'get_bundle' : ActorMethod<[bigint], Result_10>,
+  'get_bundle_analytics' : ActorMethod<[bigint, [] | [bigint]], Result_11>,
   'get_bundle_holdings' : ActorMethod<[bigint], Array<BundleHolding>>,
-  'get_bundle_summary' : ActorMethod<[bigint], Result_9>,
+  'get_bundle_return' : ActorMethod<[bigint, bigint, bigint], Result_12>,
+  'get_bundle_summary' : ActorMethod<[bigint], Result_13>,
   'get_bundle_transaction_history' : ActorMethod<
     [bigint],
     BundleTransactionHistory
   >,
   'get_bundles_list' : ActorMethod<[], Array<BundleListItem>>,
+  'get_bundles_list_sorted' : ActorMethod<
+    [BundleSortKey, boolean],
+    Array<BundleListItem>
+  >,
   'get_cache_statistics' : ActorMethod<[], CacheStatistics>,
   'get_cached_price' : ActorMethod<[string], [] | [AssetPrice]>,
   'get_canister_info' : ActorMethod<[], CanisterInfo>,
   'get_canister_status' : ActorMethod<[], CanisterStatus>,
+  'get_ckusdc_ledger' : ActorMethod<[], [] | [Principal]>,
   'get_current_week' : ActorMethod<[], bigint>,
   'get_default_platform_fee_bps' : ActorMethod<[], bigint>,
+  'get_ema' : ActorMethod<[string, bigint], Result_3>,
+  'get_governance_settings' : ActorMethod<[], GovernanceConfig>,
   'get_leaderboard' : ActorMethod<
     [[] | [bigint], bigint],
     Array<[Principal, bigint]>
   >,
-  'get_lock_expiration_time' : ActorMethod<[bigint, LockedFundType], Result_2>,
-  'get_nav_precision_report' : ActorMethod<[bigint], Result_10>,
+  'get_lock_expiration_time' : ActorMethod<[bigint, LockedFundType], Result_3>,
+  'get_nav_history' : ActorMethod<
+    [bigint, CandleInterval, bigint, bigint],
+    Result_14
+  >,
+  'get_nav_precision_report' : ActorMethod<[bigint], Result_15>,
   'get_oracle_config' : ActorMethod<[], [] | [OracleConfig]>,
+  'get_pause_state' : ActorMethod<[], PauseState>,
+  'get_pending_admin_nomination' : ActorMethod<[], [] | [Nomination]>,
+  'get_pending_treasury_nomination' : ActorMethod<[], [] | [Nomination]>,
   'get_platform_treasury' : ActorMethod<[], [] | [Principal]>,
-  'get_portfolio_value' : ActorMethod<[[] | [Principal]], Result_2>,
+  'get_portfolio_valuation' : ActorMethod<[[] | [Principal]], Result_16>,
+  'get_portfolio_value' : ActorMethod<[[] | [Principal]], Result_3>,
+  'get_price_aggregation_config' : ActorMethod<[], PriceAggregationConfig>,
+  'get_price_history' : ActorMethod<
+    [string, CandleInterval, bigint, bigint],
+    Result_17
+  >,
+  'get_price_observations' : ActorMethod<
+    [string, bigint],
+    Array<PriceObservation>
+  >,
+  'get_price_policy' : ActorMethod<[string], PricePolicy>,
+  'get_proposal' : ActorMethod<[bigint], Result_1>,
   'get_recent_transactions' : ActorMethod<[bigint], Array<TransactionSummary>>,
+  'get_recovery_log' : ActorMethod<
+    [[] | [bigint], bigint],
+    Array<RecoveryLogEntry>
+  >,
   'get_recovery_statistics' : ActorMethod<[], RecoveryStatistics>,
-  'get_resolver' : ActorMethod<[Principal], Result_11>,
+  'get_reference_price' : ActorMethod<[string], [] | [ReferencePrice]>,
+  'get_resolver' : ActorMethod<[Principal], Result_18>,
   'get_resolver_statistics' : ActorMethod<[], ResolverStatistics>,
-  'get_transaction' : ActorMethod<[bigint], Result_12>,
+  'get_roles' : ActorMethod<[Principal], Array<Role>>,
+  'get_schema_versions' : ActorMethod<[], Array<SchemaVersion>>,
+  'get_settlement_steps' : ActorMethod<[bigint], Array<SettlementStep>>,
+  'get_transaction' : ActorMethod<[bigint], Result_19>,
+  'get_transaction_events' : ActorMethod<[bigint], Array<TransactionEvent>>,
+  'get_transaction_recovery_log' : ActorMethod<
+    [bigint],
+    Array<RecoveryLogEntry>
+  >,
   'get_transaction_stats' : ActorMethod<[], TransactionStats>,
-  'get_transaction_summary' : ActorMethod<[bigint], Result_13>,
+  'get_transaction_summary' : ActorMethod<[bigint], Result_20>,
+  'get_transaction_transitions' : ActorMethod<
+    [bigint],
+    Array<TransactionTransition>
+  >,
   'get_transactions_by_status' : ActorMethod<
     [TransactionStatus],
     Array<Transaction>
   >,
+  'get_twap' : ActorMethod<[string, bigint], Result_3>,
   'get_user_bundles' : ActorMethod<[Principal], Array<BundleConfig>>,
   'get_user_locked_funds' : ActorMethod<[Principal], Array<LockedFunds>>,
   'get_user_points' : ActorMethod<[[] | [Principal]], bigint>,
-  'get_user_portfolio' : ActorMethod<[Principal], UserPortfolio>,
+  'get_user_portfolio' : ActorMethod<[Principal], Result_21>,
   'get_user_total_locked_amount' : ActorMethod<
     [Principal, LockedFundType],
     bigint
@@ -433,28 +814,68 @@ export interface _SERVICE {
   >,
   'get_user_transactions' : ActorMethod<[Principal], Array<Transaction>>,
   'get_user_weekly_points' : ActorMethod<[[] | [Principal], bigint], bigint>,
+  'grant_role' : ActorMethod<[Principal, Role], Result_22>,
   'is_fund_already_locked' : ActorMethod<[bigint, LockedFundType], boolean>,
   'list_active_bundles' : ActorMethod<[], Array<BundleConfig>>,
+  'list_asset_price_policies' : ActorMethod<
+    [],
+    Array<[string, AssetPricePolicy]>
+  >,
   'list_assets' : ActorMethod<[[] | [AssetFilter]], Array<AssetInfo>>,
   'list_cached_prices' : ActorMethod<[], Array<AssetPrice>>,
+  'list_payment_tokens' : ActorMethod<[], Array<PaymentToken>>,
+  'list_price_halts' : ActorMethod<[], Array<PriceHalt>>,
+  'list_proposals' : ActorMethod<
+    [[] | [bigint], bigint, boolean],
+    Array<Proposal>
+  >,
+  'list_role_assignments' : ActorMethod<[], Array<RoleAssignment>>,
   'list_valid_cached_prices' : ActorMethod<[], Array<AssetPrice>>,
   'lock_user_funds_with_validation' : ActorMethod<
     [bigint, LockedFundType, bigint],
     Result
   >,
+  'override_price_halt' : ActorMethod<[string, string], Result>,
+  'pause_bundle' : ActorMethod<[bigint], Result_23>,
+  'propose_admin_action' : ActorMethod<[GovernedAction], Result_3>,
+  'recover_paused_transaction' : ActorMethod<[bigint], Result>,
   'register_resolver' : ActorMethod<[Principal, string, bigint], Result>,
+  'resolve_settlement_step' : ActorMethod<
+    [bigint, string, [] | [bigint]],
+    Result
+  >,
+  'revoke_admin_nomination' : ActorMethod<[], Result>,
+  'revoke_role' : ActorMethod<[Principal, Role], Result_22>,
+  'revoke_treasury_nomination' : ActorMethod<[], Result>,
   'search_assets' : ActorMethod<[string], Array<AssetInfo>>,
   'search_bundles' : ActorMethod<[string], Array<BundleConfig>>,
-  'set_admin' : ActorMethod<[Principal], Result>,
-  'set_bundle_platform_fee' : ActorMethod<[bigint, bigint], Result>,
-  'set_coordinator_public_key' : ActorMethod<[string], Result>,
-  'set_default_platform_fee_bps' : ActorMethod<[bigint], Result>,
+  'set_admin' : ActorMethod<[Principal], Result_3>,
+  'set_analytics_config' : ActorMethod<[AnalyticsConfig], Result>,
+  'set_asset_price_policy' : ActorMethod<
+    [string, [] | [AssetPricePolicy]],
+    Result
+  >,
+  'set_bundle_platform_fee' : ActorMethod<[bigint, bigint], Result_3>,
+  'set_bundle_valuation_mode' : ActorMethod<[bigint, ValuationMode], Result_3>,
+  'set_ckusdc_ledger' : ActorMethod<[Principal], Result_3>,
+  'set_coordinator_public_key' : ActorMethod<[string], Result_3>,
+  'set_default_platform_fee_bps' : ActorMethod<[bigint], Result_3>,
+  'set_operation_paused' : ActorMethod<[PausableOperation, boolean], Result_23>,
   'set_oracle_config' : ActorMethod<[OracleConfig], Result>,
-  'set_platform_treasury' : ActorMethod<[Principal], Result>,
+  'set_platform_treasury' : ActorMethod<[Principal], Result_3>,
+  'set_price_aggregation_config' : ActorMethod<
+    [PriceAggregationConfig],
+    Result
+  >,
   'set_quote_api_principal' : ActorMethod<[Principal], Result>,
-  'unlock_all_transaction_funds' : ActorMethod<[bigint], Result_14>,
+  'set_reference_price' : ActorMethod<[string, [] | [bigint]], Result>,
+  'unlock_all_transaction_funds' : ActorMethod<[bigint], Result_24>,
+  'unpause_bundle' : ActorMethod<[bigint], Result_23>,
   'update_asset' : ActorMethod<[string, AssetInfoUpdate], Result>,
-  'update_asset_token_location' : ActorMethod<[string, TokenLocation], Result>,
+  'update_asset_token_location' : ActorMethod<
+    [string, TokenLocation],
+    Result_3
+  >,
   'update_resolver_status' : ActorMethod<[Principal, boolean], Result>,
   'validate_sufficient_balance' : ActorMethod<
     [Principal, LockedFundType, bigint],
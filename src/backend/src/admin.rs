@@ -1,20 +1,14 @@
 use candid::Principal;
-use ic_cdk::api::msg_caller;
+use ic_cdk::api::{msg_caller, time};
 use ic_cdk_macros::*;
 use crate::types::*;
 use crate::memory::*;
+use crate::audit::describe;
 
-#[derive(candid::CandidType, serde::Serialize, serde::Deserialize, Clone, Debug)]
-pub struct AdminAction {
-    pub action_type: String,
-    pub target: String,
-    pub admin: Principal,
-    pub timestamp: u64,
-    pub details: Option<String>,
-}
+const NOMINATION_TTL_NS: u64 = 7 * 86_400_000_000_000;
 
 pub fn is_admin(caller: Principal) -> bool {
-    get_admin_principal().map_or(false, |admin_principal| admin_principal == caller)
+    crate::roles::has_role(caller, Role::Owner)
 }
 
 pub fn require_admin() -> Result<Principal, String> {
@@ -31,28 +25,100 @@ pub fn get_admin() -> Option<Principal> {
 }
 
 #[update]
-pub fn set_admin(new_admin: Principal) -> Result<(), String> {
-    let _admin = require_admin()?;
+pub fn set_admin(new_admin: Principal) -> Result<u64, String> {
+    crate::governance::propose(GovernedAction::SetAdmin { new_admin })
+}
+
+pub fn new_nomination(nominee: Principal, nominated_by: Principal, now: u64) -> Nomination {
+    Nomination {
+        nominee,
+        nominated_by,
+        nominated_at: now,
+        expires_at: now + NOMINATION_TTL_NS,
+    }
+}
 
-    set_admin_principal(new_admin);
+pub fn check_acceptance(nomination: Option<&Nomination>, caller: Principal, now: u64) -> Result<(), String> {
+    let nomination = nomination.ok_or("No pending nomination")?;
+
+    if nomination.nominee != caller {
+        return Err("Only the nominee can accept".to_string());
+    }
+
+    if now > nomination.expires_at {
+        return Err("Nomination has expired".to_string());
+    }
 
-    ic_cdk::println!("Admin changed to: {}", new_admin);
+    Ok(())
+}
+
+pub(crate) fn nominate_admin(nominee: Principal, nominated_by: Principal) -> Result<(), String> {
+    set_pending_admin(Some(new_nomination(nominee, nominated_by, time())));
+
+    ic_cdk::println!("Admin nominee set to: {}", nominee);
     Ok(())
 }
 
+#[update]
+pub fn accept_admin() -> Result<(), String> {
+    let caller = msg_caller();
+    check_acceptance(get_pending_admin().as_ref(), caller, time())?;
+
+    let previous = get_admin_principal();
+    set_admin_principal(caller);
+    set_pending_admin(None);
+
+    crate::audit::record(caller, "accept_admin", "platform", None, Some(describe(&previous)), Some(caller.to_text()));
+
+    ic_cdk::println!("Admin changed to: {}", caller);
+    Ok(())
+}
+
+#[update]
+pub fn revoke_admin_nomination() -> Result<(), String> {
+    let admin = require_admin()?;
+    let nomination = get_pending_admin();
+    set_pending_admin(None);
+
+    crate::audit::record(
+        admin,
+        "revoke_admin_nomination",
+        "platform",
+        None,
+        nomination.map(|n| n.nominee.to_text()),
+        None,
+    );
+    Ok(())
+}
+
+#[query]
+pub fn get_pending_admin_nomination() -> Option<Nomination> {
+    get_pending_admin()
+}
+
 #[update]
 pub fn set_quote_api_principal(quote_api: Principal) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let admin = require_admin()?;
 
+    let previous = get_quote_service_principal();
     set_quote_service_principal(quote_api);
 
+    crate::audit::record(
+        admin,
+        "set_quote_api_principal",
+        "platform",
+        None,
+        Some(describe(&previous)),
+        Some(quote_api.to_text()),
+    );
+
     ic_cdk::println!("Quote API/Coordinator set to: {}", quote_api);
     Ok(())
 }
 
 #[update]
 pub fn emergency_pause_canister() -> Result<(), String> {
-    let _admin = require_admin()?;
+    crate::pause_manager::set_all_operations_paused(true)?;
 
     ic_cdk::println!("Emergency pause initiated by admin");
     Ok(())
@@ -60,7 +126,7 @@ pub fn emergency_pause_canister() -> Result<(), String> {
 
 #[update]
 pub fn emergency_unpause_canister() -> Result<(), String> {
-    let _admin = require_admin()?;
+    crate::pause_manager::set_all_operations_paused(false)?;
 
     ic_cdk::println!("Emergency unpause initiated by admin");
     Ok(())
@@ -86,7 +152,7 @@ fn get_memory_usage() -> MemoryUsage {
 
 #[update]
 pub async fn cleanup_inactive_bundles() -> Result<u32, String> {
-    let _admin = require_admin()?;
+    let operator = crate::roles::require_role(Role::Operator)?;
 
     let inactive_bundles: Vec<u64> = BUNDLE_STORAGE.with(|storage| {
         storage.borrow().iter()
@@ -110,6 +176,7 @@ pub async fn cleanup_inactive_bundles() -> Result<u32, String> {
     }
 
     let removed_count = bundles_to_remove.len() as u32;
+    let details = format!("Removed bundles: {:?}", bundles_to_remove);
 
     BUNDLE_STORAGE.with(|storage| {
         let mut storage = storage.borrow_mut();
@@ -118,45 +185,99 @@ pub async fn cleanup_inactive_bundles() -> Result<u32, String> {
         }
     });
 
+    crate::audit::record(operator, "cleanup_inactive_bundles", "bundles", Some(details), None, None);
+
     ic_cdk::println!("Removed {} inactive bundles", removed_count);
     Ok(removed_count)
 }
 
 #[update]
 pub fn force_deactivate_bundle(bundle_id: u64, reason: String) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let operator = crate::roles::require_role(Role::Operator)?;
 
-    BUNDLE_STORAGE.with(|storage| {
+    let was_active = BUNDLE_STORAGE.with(|storage| {
         let mut storage = storage.borrow_mut();
         match storage.get(&bundle_id) {
             Some(mut bundle) => {
+                let was_active = bundle.is_active;
                 bundle.is_active = false;
                 storage.insert(bundle_id, bundle);
-                ic_cdk::println!("Bundle {} deactivated by admin. Reason: {}", bundle_id, reason);
-                Ok(())
+                Ok(was_active)
             }
             None => Err(format!("Bundle {} not found", bundle_id))
         }
-    })
+    })?;
+
+    crate::audit::record(
+        operator,
+        "force_deactivate_bundle",
+        format!("bundle:{}", bundle_id),
+        Some(reason.clone()),
+        Some(format!("is_active: {}", was_active)),
+        Some("is_active: false".to_string()),
+    );
+
+    ic_cdk::println!("Bundle {} deactivated by admin. Reason: {}", bundle_id, reason);
+    Ok(())
 }
 
 #[update]
-pub fn set_platform_treasury(treasury: Principal) -> Result<(), String> {
-    let _admin = require_admin()?;
+pub fn set_platform_treasury(treasury: Principal) -> Result<u64, String> {
+    crate::governance::propose(GovernedAction::SetPlatformTreasury { treasury })
+}
+
+pub(crate) fn nominate_treasury(nominee: Principal, nominated_by: Principal) -> Result<(), String> {
+    set_pending_treasury(Some(new_nomination(nominee, nominated_by, time())));
 
+    ic_cdk::println!("Platform treasury nominee set to: {}", nominee);
+    Ok(())
+}
+
+// The treasury must prove it is a live, controlled principal before fees are routed to it
+#[update]
+pub fn accept_treasury() -> Result<(), String> {
+    let caller = msg_caller();
+    check_acceptance(get_pending_treasury().as_ref(), caller, time())?;
+
+    let previous = get_platform_treasury();
     GLOBAL_STATE.with(|state| {
         let mut state = state.borrow_mut();
         let mut global_state = state.get().clone();
-        global_state.platform_treasury = Some(treasury);
+        global_state.platform_treasury = Some(caller);
+        global_state.pending_treasury = None;
         state.set(global_state)
             .map_err(|_| "Failed to update platform treasury".to_string())
             .map(|_| ())
     })?;
 
-    ic_cdk::println!("Platform treasury set to: {}", treasury);
+    crate::audit::record(caller, "accept_treasury", "platform", None, Some(describe(&previous)), Some(caller.to_text()));
+
+    ic_cdk::println!("Platform treasury set to: {}", caller);
+    Ok(())
+}
+
+#[update]
+pub fn revoke_treasury_nomination() -> Result<(), String> {
+    let admin = require_admin()?;
+    let nomination = get_pending_treasury();
+    set_pending_treasury(None);
+
+    crate::audit::record(
+        admin,
+        "revoke_treasury_nomination",
+        "platform",
+        None,
+        nomination.map(|n| n.nominee.to_text()),
+        None,
+    );
     Ok(())
 }
 
+#[query]
+pub fn get_pending_treasury_nomination() -> Option<Nomination> {
+    get_pending_treasury()
+}
+
 #[query]
 pub fn get_platform_treasury() -> Option<Principal> {
     GLOBAL_STATE.with(|state| {
@@ -166,9 +287,11 @@ pub fn get_platform_treasury() -> Option<Principal> {
 }
 
 #[update]
-pub fn set_default_platform_fee_bps(fee_bps: u64) -> Result<(), String> {
-    let _admin = require_admin()?;
+pub fn set_default_platform_fee_bps(fee_bps: u64) -> Result<u64, String> {
+    crate::governance::propose(GovernedAction::SetDefaultPlatformFeeBps { fee_bps })
+}
 
+pub(crate) fn apply_default_platform_fee_bps(fee_bps: u64) -> Result<(), String> {
     if fee_bps > 10000 {
         return Err("Fee cannot exceed 100% (10000 bps)".to_string());
     }
@@ -186,6 +309,31 @@ pub fn set_default_platform_fee_bps(fee_bps: u64) -> Result<(), String> {
     Ok(())
 }
 
+#[update]
+pub async fn set_ckusdc_ledger(ledger: Principal) -> Result<u64, String> {
+    crate::governance::propose_validated(GovernedAction::SetCkusdcLedger { ledger }).await
+}
+
+// Escrowed funds live on the current ledger, so it can only change once nothing is in flight
+pub(crate) fn apply_ckusdc_ledger(ledger: Principal) -> Result<(), String> {
+    let in_flight = TRANSACTIONS.with(|transactions| {
+        transactions.borrow().iter().filter(|(_, tx)| !tx.status.is_terminal()).count()
+    });
+    if in_flight > 0 {
+        return Err(format!("{} transactions are still in flight on the current ledger", in_flight));
+    }
+
+    set_icrc2_ckusdc_ledger(ledger);
+
+    ic_cdk::println!("ckUSDC ledger set to: {}", ledger);
+    Ok(())
+}
+
+#[query]
+pub fn get_ckusdc_ledger() -> Option<Principal> {
+    get_icrc2_ckusdc_ledger()
+}
+
 #[query]
 pub fn get_default_platform_fee_bps() -> u64 {
     GLOBAL_STATE.with(|state| {
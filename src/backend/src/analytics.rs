@@ -0,0 +1,205 @@
+use ic_cdk::api::time;
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::roles::require_role;
+use crate::nav_history::{point_at, first_point, return_bps};
+
+const DAY_NS: u64 = 86_400_000_000_000;
+const RISK_WINDOW_NS: u64 = 30 * DAY_NS;
+// Crypto trades around the clock, so hourly returns annualize over every hour of the year
+const HOURS_PER_YEAR: f64 = 8_760.0;
+const MAX_RISK_FREE_RATE_BPS: u64 = 10_000;
+
+pub fn validate_analytics_config(config: &AnalyticsConfig) -> Result<(), String> {
+    if config.risk_free_rate_bps > MAX_RISK_FREE_RATE_BPS {
+        return Err(format!("Risk-free rate cannot exceed {} bps", MAX_RISK_FREE_RATE_BPS));
+    }
+    if let Some(benchmark) = config.benchmark_bundle_id {
+        crate::bundle_manager::get_bundle(benchmark)?;
+    }
+    Ok(())
+}
+
+pub fn analytics_config() -> AnalyticsConfig {
+    crate::memory::get_analytics_config().unwrap_or_default()
+}
+
+// Howard Hinnant's civil-from-days, reduced to the year
+fn year_from_days(days: u64) -> u64 {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let year = yoe + era * 400;
+    if mp >= 10 { year + 1 } else { year }
+}
+
+// Days from the epoch to January 1st of `year`
+fn days_to_year_start(year: u64) -> u64 {
+    let y = year - 1;
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + 306;
+    era * 146_097 + doe - 719_468
+}
+
+pub fn year_start_ns(timestamp: u64) -> u64 {
+    days_to_year_start(year_from_days(timestamp / DAY_NS)) * DAY_NS
+}
+
+// Returns between adjacent hour buckets, keyed by the later bucket; across a gap one return would
+// span several hours while being annualized as one
+pub fn hourly_returns(series: &[BundleNAV]) -> Vec<(u64, f64)> {
+    let hour = CandleInterval::Hour.duration_ns();
+    let bucket = |nav: &BundleNAV| CandleInterval::Hour.bucket_start(nav.calculated_at);
+    series.windows(2)
+        .filter(|pair| pair[0].nav_per_token > 0 && bucket(&pair[1]) == bucket(&pair[0]) + hour)
+        .map(|pair| {
+            let (start, end) = (pair[0].nav_per_token as f64, pair[1].nav_per_token as f64);
+            (bucket(&pair[1]), (end - start) / start)
+        })
+        .collect()
+}
+
+fn mean_and_std_dev(values: &[f64]) -> Option<(f64, f64)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some((mean, variance.sqrt()))
+}
+
+pub fn annualized_volatility_bps(returns: &[f64], periods_per_year: f64) -> Option<u64> {
+    let (_, std_dev) = mean_and_std_dev(returns)?;
+    Some((std_dev * periods_per_year.sqrt() * 10_000.0).round() as u64)
+}
+
+pub fn sharpe_ratio(returns: &[f64], periods_per_year: f64, risk_free_rate_bps: u64) -> Option<f64> {
+    let (mean, std_dev) = mean_and_std_dev(returns)?;
+    if std_dev == 0.0 {
+        return None;
+    }
+    let excess = mean * periods_per_year - risk_free_rate_bps as f64 / 10_000.0;
+    Some(excess / (std_dev * periods_per_year.sqrt()))
+}
+
+// Largest peak-to-trough fall, in bps of the peak
+pub fn max_drawdown_bps(navs: &[u64]) -> Option<u64> {
+    let mut peak = *navs.first()?;
+    let mut max_drawdown = 0u64;
+    for &nav in navs {
+        peak = peak.max(nav);
+        if peak > 0 {
+            let drawdown = ((peak - nav) as u128 * 10_000 / peak as u128) as u64;
+            max_drawdown = max_drawdown.max(drawdown);
+        }
+    }
+    Some(max_drawdown)
+}
+
+pub fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let (mean_a, std_a) = mean_and_std_dev(a)?;
+    let (mean_b, std_b) = mean_and_std_dev(b)?;
+    if std_a == 0.0 || std_b == 0.0 {
+        return None;
+    }
+    let covariance = a.iter().zip(b)
+        .map(|(x, y)| (x - mean_a) * (y - mean_b))
+        .sum::<f64>() / (a.len() - 1) as f64;
+    Some(covariance / (std_a * std_b))
+}
+
+// Hourly returns over the same hours in both series, so gaps in either don't misalign them
+pub fn aligned_returns(series: &[BundleNAV], benchmark: &[BundleNAV]) -> (Vec<f64>, Vec<f64>) {
+    let benchmark_by_bucket: std::collections::BTreeMap<u64, f64> = hourly_returns(benchmark).into_iter().collect();
+    hourly_returns(series).into_iter()
+        .filter_map(|(bucket, r)| benchmark_by_bucket.get(&bucket).map(|b| (r, *b)))
+        .unzip()
+}
+
+fn return_since(bundle_id: u64, from: u64, now: u64) -> Option<i64> {
+    let start = point_at(bundle_id, from)?;
+    let end = point_at(bundle_id, now)?;
+    return_bps(start.nav_per_token, end.nav_per_token)
+}
+
+fn recent_series(bundle_id: u64, now: u64) -> Vec<BundleNAV> {
+    crate::nav_history::nav_history(bundle_id, CandleInterval::Hour, now.saturating_sub(RISK_WINDOW_NS), now)
+        .unwrap_or_default()
+}
+
+pub fn bundle_analytics(bundle_id: u64, benchmark_bundle_id: Option<u64>) -> BundleAnalytics {
+    let now = time();
+    let config = analytics_config();
+    let benchmark_bundle_id = benchmark_bundle_id
+        .or(config.benchmark_bundle_id)
+        .filter(|benchmark| *benchmark != bundle_id);
+
+    let series = recent_series(bundle_id, now);
+    let navs: Vec<u64> = series.iter().map(|nav| nav.nav_per_token).collect();
+    let returns: Vec<f64> = hourly_returns(&series).into_iter().map(|(_, r)| r).collect();
+
+    let correlation = benchmark_bundle_id.and_then(|benchmark| {
+        let (a, b) = aligned_returns(&series, &recent_series(benchmark, now));
+        correlation(&a, &b)
+    });
+    let return_inception_bps = first_point(bundle_id).and_then(|start| {
+        let end = point_at(bundle_id, now)?;
+        return_bps(start.nav_per_token, end.nav_per_token)
+    });
+
+    BundleAnalytics {
+        bundle_id,
+        return_24h_bps: return_since(bundle_id, now.saturating_sub(DAY_NS), now),
+        return_7d_bps: return_since(bundle_id, now.saturating_sub(7 * DAY_NS), now),
+        return_30d_bps: return_since(bundle_id, now.saturating_sub(30 * DAY_NS), now),
+        return_ytd_bps: return_since(bundle_id, year_start_ns(now), now),
+        return_inception_bps,
+        volatility_bps: annualized_volatility_bps(&returns, HOURS_PER_YEAR),
+        max_drawdown_bps: max_drawdown_bps(&navs),
+        sharpe_ratio: sharpe_ratio(&returns, HOURS_PER_YEAR, config.risk_free_rate_bps),
+        benchmark_bundle_id,
+        correlation,
+        calculated_at: now,
+    }
+}
+
+#[query]
+pub fn get_bundle_analytics(bundle_id: u64, benchmark_bundle_id: Option<u64>) -> Result<BundleAnalytics, String> {
+    crate::bundle_manager::get_bundle(bundle_id)?;
+    if let Some(benchmark) = benchmark_bundle_id {
+        crate::bundle_manager::get_bundle(benchmark)?;
+    }
+    Ok(bundle_analytics(bundle_id, benchmark_bundle_id))
+}
+
+#[query]
+pub fn get_analytics_config() -> AnalyticsConfig {
+    analytics_config()
+}
+
+#[update]
+pub fn set_analytics_config(config: AnalyticsConfig) -> Result<(), String> {
+    let operator = require_role(Role::Operator)?;
+    validate_analytics_config(&config)?;
+
+    let previous = crate::memory::get_analytics_config();
+    crate::memory::set_analytics_config(config.clone());
+
+    crate::audit::record(
+        operator,
+        "set_analytics_config",
+        "analytics",
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&config)),
+    );
+
+    Ok(())
+}
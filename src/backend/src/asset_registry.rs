@@ -3,7 +3,9 @@ use ic_cdk::api::time;
 use ic_cdk_macros::*;
 use crate::types::*;
 use crate::memory::*;
-use crate::admin::require_admin;
+use crate::roles::require_role;
+
+const MAX_PAYMENT_DECIMALS: u8 = 18;
 
 #[update]
 pub fn add_asset(
@@ -15,12 +17,13 @@ pub fn add_asset(
     decimals: u8,
     metadata: AssetMetadata,
 ) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let manager = require_role(Role::AssetManager)?;
 
     match &token_location {
+        // ICRC-2 assets are payment stablecoins; amounts are normalized through u64 powers of ten
         TokenLocation::ICRC2 { ledger: _ } => {
-            if id != "ckUSDC" {
-                return Err("Only ckUSDC should use ICRC-2".to_string());
+            if decimals > MAX_PAYMENT_DECIMALS {
+                return Err(format!("ICRC-2 assets support at most {} decimals", MAX_PAYMENT_DECIMALS));
             }
         }
         TokenLocation::ICRC151 { ledger: _, token_id } => {
@@ -47,19 +50,22 @@ pub fn add_asset(
         if registry.contains_key(&id) {
             return Err(format!("Asset {} already exists", id));
         }
-        registry.insert(id, asset);
+        let after = crate::audit::describe(&asset);
+        registry.insert(id.clone(), asset);
+        crate::audit::record(manager, "add_asset", id, None, None, Some(after));
         Ok(())
     })
 }
 
 #[update]
 pub fn update_asset(asset_id: AssetId, updates: AssetInfoUpdate) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let manager = require_role(Role::AssetManager)?;
 
     ASSET_REGISTRY.with(|registry| {
         let mut registry = registry.borrow_mut();
         match registry.get(&asset_id) {
             Some(mut asset_info) => {
+                let before = crate::audit::describe(&asset_info);
                 if let Some(name) = updates.name {
                     asset_info.name = name;
                 }
@@ -72,7 +78,9 @@ pub fn update_asset(asset_id: AssetId, updates: AssetInfoUpdate) -> Result<(), S
                 if let Some(metadata) = updates.metadata {
                     asset_info.metadata = metadata;
                 }
-                registry.insert(asset_id, asset_info);
+                let after = crate::audit::describe(&asset_info);
+                registry.insert(asset_id.clone(), asset_info);
+                crate::audit::record(manager, "update_asset", asset_id, None, Some(before), Some(after));
                 Ok(())
             }
             None => Err(format!("Asset {} not found", asset_id))
@@ -127,14 +135,16 @@ pub fn list_assets(filter: Option<AssetFilter>) -> Vec<AssetInfo> {
 
 #[update]
 pub fn deactivate_asset(asset_id: AssetId) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let manager = require_role(Role::AssetManager)?;
 
     ASSET_REGISTRY.with(|registry| {
         let mut registry = registry.borrow_mut();
         match registry.get(&asset_id) {
             Some(mut asset_info) => {
+                let before = format!("is_active: {}", asset_info.is_active);
                 asset_info.is_active = false;
-                registry.insert(asset_id, asset_info);
+                registry.insert(asset_id.clone(), asset_info);
+                crate::audit::record(manager, "deactivate_asset", asset_id, None, Some(before), Some("is_active: false".to_string()));
                 Ok(())
             }
             None => Err(format!("Asset {} not found", asset_id))
@@ -159,11 +169,16 @@ pub fn get_asset_icrc151_location(asset_id: &AssetId) -> Result<(Principal, Vec<
 }
 
 #[update]
-pub fn update_asset_token_location(asset_id: AssetId, new_token_location: TokenLocation) -> Result<(), String> {
-    let _admin = require_admin()?;
+pub fn update_asset_token_location(asset_id: AssetId, new_token_location: TokenLocation) -> Result<u64, String> {
+    get_asset(asset_id.clone())?;
+    crate::governance::propose(GovernedAction::UpdateAssetTokenLocation {
+        asset_id,
+        token_location: new_token_location,
+    })
+}
 
-    // Validate token_location
-    match &new_token_location {
+pub fn validate_token_location(token_location: &TokenLocation) -> Result<(), String> {
+    match token_location {
         TokenLocation::ICRC151 { ledger: _, token_id } => {
             if token_id.len() != 32 {
                 return Err("ICRC-151 token_id must be 32 bytes".to_string());
@@ -174,6 +189,12 @@ pub fn update_asset_token_location(asset_id: AssetId, new_token_location: TokenL
         }
     }
 
+    Ok(())
+}
+
+pub(crate) fn apply_asset_token_location(asset_id: AssetId, new_token_location: TokenLocation) -> Result<(), String> {
+    validate_token_location(&new_token_location)?;
+
     ASSET_REGISTRY.with(|registry| {
         let mut registry = registry.borrow_mut();
         match registry.get(&asset_id) {
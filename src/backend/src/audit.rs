@@ -0,0 +1,65 @@
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_cdk_macros::*;
+use std::fmt::Debug;
+use crate::types::*;
+use crate::memory::*;
+
+const MAX_AUDIT_PAGE: u64 = 100;
+
+pub fn describe<T: Debug>(value: &T) -> String {
+    format!("{:?}", value)
+}
+
+pub fn record(
+    admin: Principal,
+    action_type: &str,
+    target: impl Into<String>,
+    details: Option<String>,
+    before: Option<String>,
+    after: Option<String>,
+) {
+    ADMIN_AUDIT_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        let id = log.last_key_value().map_or(0, |(id, _)| id + 1);
+
+        log.insert(id, AdminAction {
+            id,
+            action_type: action_type.to_string(),
+            target: target.into(),
+            admin,
+            timestamp: time(),
+            details,
+            before,
+            after,
+        });
+    });
+}
+
+pub fn matches_filter(action: &AdminAction, filter: &AdminActionFilter) -> bool {
+    filter.action_type.as_ref().is_none_or(|t| *t == action.action_type)
+        && filter.target.as_ref().is_none_or(|t| *t == action.target)
+        && filter.admin.is_none_or(|a| a == action.admin)
+        && filter.from_time.is_none_or(|from| action.timestamp >= from)
+        && filter.to_time.is_none_or(|to| action.timestamp <= to)
+}
+
+// Newest first; pass the id of the last entry seen to fetch the next page
+#[query]
+pub fn get_admin_actions(before: Option<u64>, limit: u64, filter: Option<AdminActionFilter>) -> Vec<AdminAction> {
+    let limit = limit.min(MAX_AUDIT_PAGE) as usize;
+    let filter = filter.unwrap_or_default();
+
+    ADMIN_AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        let iter: Box<dyn Iterator<Item = (u64, AdminAction)>> = match before {
+            Some(before) => Box::new(log.range(..before).rev()),
+            None => Box::new(log.iter().rev()),
+        };
+
+        iter.map(|(_, action)| action)
+            .filter(|action| matches_filter(action, &filter))
+            .take(limit)
+            .collect()
+    })
+}
@@ -0,0 +1,73 @@
+use candid::Principal;
+use ic_cdk::api::msg_caller;
+use crate::types::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Party {
+    Role(Role),
+    TransactionUser,
+    AssignedResolver,
+}
+
+pub const LOCK_FUNDS_PARTIES: &[Party] = &[Party::Role(Role::Owner), Party::TransactionUser];
+pub const UNLOCK_FUNDS_PARTIES: &[Party] = &[Party::Role(Role::Owner)];
+pub const EXTEND_LOCK_PARTIES: &[Party] = &[Party::Role(Role::Owner), Party::AssignedResolver];
+pub const RECOVERY_PARTIES: &[Party] = &[Party::Role(Role::Operator), Party::TransactionUser];
+
+#[derive(Clone, Debug, Default)]
+pub struct TransactionParties {
+    pub user: Option<Principal>,
+    pub resolver: Option<Principal>,
+}
+
+impl TransactionParties {
+    fn includes(&self, caller: Principal, party: Party) -> bool {
+        match party {
+            Party::Role(role) => crate::roles::has_role(caller, role),
+            Party::TransactionUser => self.user == Some(caller),
+            Party::AssignedResolver => self.resolver == Some(caller),
+        }
+    }
+}
+
+pub fn authorize(caller: Principal, allowed: &[Party], parties: &TransactionParties) -> Result<(), String> {
+    if caller == Principal::anonymous() {
+        return Err("Anonymous callers are not authorized".to_string());
+    }
+
+    if allowed.iter().any(|party| parties.includes(caller, *party)) {
+        return Ok(());
+    }
+
+    Err(format!("Caller {} is not authorized; requires one of {:?}", caller, allowed))
+}
+
+// Users may only recover their own funds; recovering on behalf of someone else needs the Operator role
+pub fn authorize_recovery_for(caller: Principal, user: Principal) -> Result<(), String> {
+    let parties = TransactionParties {
+        user: Some(user),
+        resolver: None,
+    };
+
+    authorize(caller, RECOVERY_PARTIES, &parties)
+}
+
+fn transaction_parties(transaction: &Transaction) -> TransactionParties {
+    TransactionParties {
+        user: Some(transaction.user),
+        resolver: crate::quote_manager::get_assignment(transaction.request_id)
+            .ok()
+            .map(|assignment| assignment.resolver),
+    }
+}
+
+pub fn authorize_transaction_party(caller: Principal, transaction_id: u64, allowed: &[Party]) -> Result<(), String> {
+    let transaction = crate::transaction_manager::get_transaction(transaction_id)?;
+    authorize(caller, allowed, &transaction_parties(&transaction))
+}
+
+pub fn require_transaction_party(transaction_id: u64, allowed: &[Party]) -> Result<Principal, String> {
+    let caller = msg_caller();
+    authorize_transaction_party(caller, transaction_id, allowed)?;
+    Ok(caller)
+}
@@ -6,6 +6,8 @@ use crate::memory::*;
 
 #[update]
 pub async fn create_bundle(request: BundleCreationRequest) -> Result<u64, String> {
+    crate::pause_manager::ensure_not_paused(PausableOperation::BundleCreation, None)?;
+
     let total_percentage: u32 = request.allocations.iter()
         .map(|a| a.percentage as u32)
         .sum();
@@ -43,7 +45,7 @@ pub async fn create_bundle(request: BundleCreationRequest) -> Result<u64, String
         request.icrc151_ledger,
         request.name.clone(),
         request.symbol.clone(),
-        8,
+        crate::nav_token::NAV_TOKEN_DECIMALS,
         Some(10000),
         None,
         request.description.clone(),
@@ -67,6 +69,7 @@ pub async fn create_bundle(request: BundleCreationRequest) -> Result<u64, String
         created_at: time(),
         is_active: false,
         platform_fee_bps: Some(default_fee_bps),
+        valuation_mode: None,
     };
 
     BUNDLE_STORAGE.with(|storage| {
@@ -121,6 +124,12 @@ pub fn list_active_bundles() -> Vec<BundleConfig> {
     })
 }
 
+pub fn list_all_bundles() -> Vec<BundleConfig> {
+    BUNDLE_STORAGE.with(|storage| {
+        storage.borrow().iter().map(|(_, bundle)| bundle).collect()
+    })
+}
+
 pub fn get_bundle_count() -> u64 {
     BUNDLE_STORAGE.with(|storage| {
         storage.borrow().len()
@@ -143,9 +152,32 @@ pub fn get_bundles_using_asset(asset_id: &AssetId) -> Vec<u64> {
 }
 
 #[update]
-pub fn set_bundle_platform_fee(bundle_id: u64, fee_bps: u64) -> Result<(), String> {
-    let _admin = crate::admin::require_admin()?;
+pub fn set_bundle_platform_fee(bundle_id: u64, fee_bps: u64) -> Result<u64, String> {
+    get_bundle(bundle_id)?;
+    crate::governance::propose(GovernedAction::SetBundlePlatformFee { bundle_id, fee_bps })
+}
+
+#[update]
+pub fn set_bundle_valuation_mode(bundle_id: u64, mode: ValuationMode) -> Result<u64, String> {
+    get_bundle(bundle_id)?;
+    crate::governance::propose(GovernedAction::SetBundleValuationMode { bundle_id, mode })
+}
+
+pub(crate) fn apply_bundle_valuation_mode(bundle_id: u64, mode: ValuationMode) -> Result<(), String> {
+    crate::price_history::validate_valuation_mode(&mode)?;
+
+    BUNDLE_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let mut bundle = storage.get(&bundle_id)
+            .ok_or_else(|| format!("Bundle {} not found", bundle_id))?;
+
+        bundle.valuation_mode = Some(mode);
+        storage.insert(bundle_id, bundle);
+        Ok(())
+    })
+}
 
+pub(crate) fn apply_bundle_platform_fee(bundle_id: u64, fee_bps: u64) -> Result<(), String> {
     if fee_bps > 10000 {
         return Err("Fee cannot exceed 100% (10000 bps)".to_string());
     }
@@ -1,6 +1,18 @@
+use candid::Principal;
 use ic_cdk::api::msg_caller;
 use crate::types::*;
-use crate::{icrc151_client, icrc2_client};
+use crate::{icrc151_client, icrc2_client, settlement};
+
+pub const MINT_STEP: &str = "mint";
+pub const COLLATERAL_PULL_PREFIX: &str = "pull:";
+
+pub fn collateral_pull_step(asset_id: &str) -> String {
+    format!("{}{}", COLLATERAL_PULL_PREFIX, asset_id)
+}
+
+pub fn collateral_unwind_step(asset_id: &str) -> String {
+    format!("unwind:{}", asset_id)
+}
 
 pub async fn confirm_asset_deposit(request_id: u64) -> Result<(), String> {
     let caller = msg_caller();
@@ -12,13 +24,156 @@ pub async fn confirm_asset_deposit(request_id: u64) -> Result<(), String> {
         return Err("Only assigned resolver can confirm deposits".to_string());
     }
 
+    let _guard = crate::guard::TransactionGuard::acquire(transaction.id)?;
+
     match transaction.operation {
         OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {},
         _ => return Err("This function is only for buy operations".to_string()),
     }
 
+    crate::pause_manager::ensure_not_paused(
+        PausableOperation::ResolverConfirmations,
+        Some(transaction.bundle_id),
+    )?;
+
     let bundle = crate::bundle_manager::get_bundle(transaction.bundle_id)?;
 
+    match transaction.status {
+        TransactionStatus::WaitingForResolver => {
+            crate::transaction_manager::update_transaction_status(
+                transaction.id,
+                TransactionStatus::InProgress,
+                caller,
+            )?;
+        }
+        TransactionStatus::InProgress | TransactionStatus::AssetsTransferred => {
+            ic_cdk::println!(
+                "Resuming buy settlement for tx {} from {:?}",
+                transaction.id,
+                transaction.status
+            );
+        }
+        ref status => {
+            return Err(format!("Transaction is not awaiting settlement: {:?}", status));
+        }
+    }
+
+    if transaction.status != TransactionStatus::AssetsTransferred {
+        deposit_assets_and_mint(&transaction, &assignment, &bundle).await?;
+
+        crate::transaction_manager::update_transaction_status(
+            transaction.id,
+            TransactionStatus::AssetsTransferred,
+            caller,
+        )?;
+    }
+
+    settle_buy_payments(&transaction, &assignment, &bundle, caller).await
+}
+
+// Pays the platform fee and the resolver out of escrow, then completes the buy
+pub async fn settle_buy_payments(
+    transaction: &Transaction,
+    assignment: &QuoteAssignment,
+    bundle: &BundleConfig,
+    actor: Principal,
+) -> Result<(), String> {
+    let treasury = crate::memory::GLOBAL_STATE.with(|state| {
+        state.borrow().get().platform_treasury
+    });
+    let token = crate::payments::transaction_payment_token(transaction)?;
+    let ledger_fee = icrc2_client::icrc1_fee(token.ledger).await?;
+
+    // The fee leg pays its own ledger fee out of assignment.fees; dust below one ledger fee stays in escrow
+    if assignment.fees > ledger_fee {
+        if let Some(treasury_principal) = treasury {
+            let fee_memo = format!(
+                "Platform fee for buy tx {} ({}bps)",
+                transaction.id,
+                bundle.platform_fee_bps.unwrap_or(50)
+            ).into_bytes();
+
+            let fee_amount = crate::escrow::net_payout(assignment.fees, 0, ledger_fee, 1)?;
+
+            let fee_block = crate::escrow::pay_from_payment_escrow(
+                &token,
+                transaction.id,
+                "fee",
+                treasury_principal,
+                fee_amount,
+                fee_memo,
+            ).await?;
+
+            ic_cdk::println!(
+                "Transferred {} {} platform fee to treasury {} (tx: {})",
+                fee_amount,
+                token.asset_id,
+                treasury_principal,
+                fee_block
+            );
+        } else {
+            ic_cdk::println!(
+                "Warning: Platform fee {} calculated but treasury not configured",
+                assignment.fees
+            );
+        }
+    }
+
+    let resolver_payment = crate::escrow::net_payout(
+        assignment.ckusdc_amount,
+        assignment.fees,
+        ledger_fee,
+        1,
+    )?;
+
+    let payment_memo = format!(
+        "Payment for buy tx {}",
+        transaction.id
+    ).into_bytes();
+
+    let payment_block = crate::escrow::pay_from_payment_escrow(
+        &token,
+        transaction.id,
+        "payment",
+        assignment.resolver,
+        resolver_payment,
+        payment_memo,
+    ).await?;
+
+    ic_cdk::println!(
+        "Paid {} {} to resolver {} (tx: {})",
+        resolver_payment,
+        token.asset_id,
+        assignment.resolver,
+        payment_block
+    );
+
+    crate::transaction_manager::unlock_user_funds(
+        transaction.id,
+        &LockedFundType::payment(&token.asset_id),
+    )?;
+
+    crate::transaction_manager::update_transaction_status(
+        transaction.id,
+        TransactionStatus::Completed,
+        actor,
+    )?;
+
+    if matches!(transaction.operation, OperationType::InitialBuy { .. }) {
+        crate::bundle_manager::activate_bundle(transaction.bundle_id)?;
+    }
+
+    let points = crate::payments::points_for(assignment.ckusdc_amount, token.decimals);
+    crate::memory::add_points(transaction.user, points);
+
+    Ok(())
+}
+
+pub async fn deposit_assets_and_mint(
+    transaction: &Transaction,
+    assignment: &QuoteAssignment,
+    bundle: &BundleConfig,
+) -> Result<(), String> {
     for asset_amount in &assignment.asset_amounts {
         let allocation = bundle.allocations.iter()
             .find(|a| a.asset_id == asset_amount.asset_id)
@@ -38,20 +193,41 @@ pub async fn confirm_asset_deposit(request_id: u64) -> Result<(), String> {
                 allocation.asset_id
             ).into_bytes();
 
-            let pull_result = icrc151_client::transfer_from_icrc151(
+            let pull_call = LedgerCall {
+                kind: LedgerCallKind::Icrc151TransferFrom,
                 ledger,
-                token_id,
-                icrc151_client::Account {
-                    owner: assignment.resolver,
-                    subaccount: None,
-                },
-                icrc151_client::Account {
-                    owner: ic_cdk::api::id(),
-                    subaccount: None,
-                },
-                required_amount,
-                Some(pull_memo),
-            ).await?;
+                token_id: Some(token_id.clone()),
+                from: Some(assignment.resolver),
+                from_subaccount: None,
+                to: Some(ic_cdk::api::canister_self()),
+                to_subaccount: None,
+                amount: required_amount,
+                memo: Some(pull_memo.clone()),
+            };
+
+            let step = collateral_pull_step(&allocation.asset_id);
+
+            let pull_result = settlement::run_ledger_step(transaction.id, &step, pull_call, |created_at_time| {
+                icrc151_client::transfer_from_icrc151(
+                    ledger,
+                    token_id,
+                    icrc151_client::Account {
+                        owner: assignment.resolver,
+                        subaccount: None,
+                    },
+                    icrc151_client::Account {
+                        owner: ic_cdk::api::canister_self(),
+                        subaccount: None,
+                    },
+                    required_amount,
+                    Some(pull_memo),
+                    Some(created_at_time),
+                )
+            }).await?;
+
+            if pull_result.replayed {
+                continue;
+            }
 
             ic_cdk::println!(
                 "Pulled {} ICRC-151 {} from resolver {} via ledger {} (tx: {})",
@@ -59,7 +235,7 @@ pub async fn confirm_asset_deposit(request_id: u64) -> Result<(), String> {
                 allocation.asset_id,
                 assignment.resolver,
                 ledger,
-                pull_result
+                pull_result.block_index
             );
 
             crate::holdings_tracker::update_bundle_holdings(
@@ -78,99 +254,125 @@ pub async fn confirm_asset_deposit(request_id: u64) -> Result<(), String> {
         assignment.nav_tokens
     ).into_bytes();
 
-    let mint_tx_id = icrc151_client::mint_icrc151(
-        bundle_ledger,
-        bundle_token_id,
-        icrc151_client::Account {
-            owner: transaction.user,
-            subaccount: None,
-        },
-        assignment.nav_tokens,
-        Some(mint_memo),
-    ).await?;
+    let mint_call = LedgerCall {
+        kind: LedgerCallKind::Icrc151Mint,
+        ledger: bundle_ledger,
+        token_id: Some(bundle_token_id.clone()),
+        from: None,
+        from_subaccount: None,
+        to: Some(transaction.user),
+        to_subaccount: None,
+        amount: assignment.nav_tokens,
+        memo: Some(mint_memo.clone()),
+    };
+
+    let mint_result = settlement::run_ledger_step(transaction.id, MINT_STEP, mint_call, |_| {
+        icrc151_client::mint_icrc151(
+            bundle_ledger,
+            bundle_token_id,
+            icrc151_client::Account {
+                owner: transaction.user,
+                subaccount: None,
+            },
+            assignment.nav_tokens,
+            Some(mint_memo),
+        )
+    }).await?;
 
     ic_cdk::println!(
         "Minted {} ICRC-151 bundle tokens to user {} via ledger {} (tx: {})",
         assignment.nav_tokens,
         transaction.user,
         bundle_ledger,
-        mint_tx_id
+        mint_result.block_index
     );
 
-    let ckusdc_ledger = candid::Principal::from_text(icrc2_client::CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
-
-    if assignment.fees > 0 {
-        let treasury = crate::memory::GLOBAL_STATE.with(|state| {
-            state.borrow().get().platform_treasury
-        });
-
-        if let Some(treasury_principal) = treasury {
-            let fee_memo = format!(
-                "Platform fee for buy tx {} ({}bps)",
-                transaction.id,
-                bundle.platform_fee_bps.unwrap_or(50)
-            ).into_bytes();
+    Ok(())
+}
 
-            let fee_result = icrc2_client::icrc1_transfer(
-                ckusdc_ledger,
-                treasury_principal,
-                assignment.fees,
-                Some(fee_memo),
-            ).await?;
+// Returns each collateral deposit that landed to the resolver; only safe before any NAV was minted
+pub async fn unwind_collateral(
+    transaction: &Transaction,
+    assignment: &QuoteAssignment,
+    bundle: &BundleConfig,
+) -> Result<Vec<(AssetId, u64)>, String> {
+    let mut returned = Vec::new();
 
-            ic_cdk::println!(
-                "Transferred {} ckUSDC platform fee to treasury {} (tx: {})",
-                assignment.fees,
-                treasury_principal,
-                fee_result
-            );
-        } else {
-            ic_cdk::println!(
-                "Warning: Platform fee {} calculated but treasury not configured",
-                assignment.fees
-            );
+    for asset_amount in &assignment.asset_amounts {
+        let pull = settlement::get_step(transaction.id, &collateral_pull_step(&asset_amount.asset_id));
+        match pull.map(|step| step.state) {
+            Some(SettlementStepState::Succeeded { .. }) => {}
+            Some(SettlementStepState::Pending) => {
+                return Err(format!(
+                    "Collateral pull for {} in transaction {} has an unknown outcome and must be reconciled first",
+                    asset_amount.asset_id,
+                    transaction.id
+                ));
+            }
+            _ => continue,
         }
-    }
 
-    let resolver_payment = assignment.ckusdc_amount - assignment.fees;
+        let allocation = bundle.allocations.iter()
+            .find(|a| a.asset_id == asset_amount.asset_id)
+            .ok_or("Asset not found in bundle allocations".to_string())?;
+        let (ledger, token_id) = match &allocation.token_location {
+            TokenLocation::ICRC151 { ledger, token_id } => (*ledger, token_id.clone()),
+            _ => return Err("Allocation must be ICRC-151".to_string()),
+        };
 
-    let payment_memo = format!(
-        "Payment for buy tx {}",
-        transaction.id
-    ).into_bytes();
+        let memo = format!(
+            "Buy tx {} - return {} collateral",
+            transaction.id,
+            asset_amount.asset_id
+        ).into_bytes();
 
-    let payment_result = icrc2_client::icrc1_transfer(
-        ckusdc_ledger,
-        assignment.resolver,
-        resolver_payment,
-        Some(payment_memo),
-    ).await?;
+        let call = LedgerCall {
+            kind: LedgerCallKind::Icrc151Transfer,
+            ledger,
+            token_id: Some(token_id.clone()),
+            from: Some(ic_cdk::api::canister_self()),
+            from_subaccount: None,
+            to: Some(assignment.resolver),
+            to_subaccount: None,
+            amount: asset_amount.amount,
+            memo: Some(memo.clone()),
+        };
 
-    ic_cdk::println!(
-        "Paid {} ICRC-2 ckUSDC to resolver {} (tx: {})",
-        resolver_payment,
-        assignment.resolver,
-        payment_result
-    );
+        let step = collateral_unwind_step(&asset_amount.asset_id);
+        let result = settlement::run_ledger_step(transaction.id, &step, call, |created_at_time| {
+            icrc151_client::transfer_icrc151(
+                ledger,
+                token_id,
+                None,
+                icrc151_client::Account {
+                    owner: assignment.resolver,
+                    subaccount: None,
+                },
+                asset_amount.amount,
+                Some(memo),
+                Some(created_at_time),
+            )
+        }).await?;
 
-    crate::transaction_manager::unlock_user_funds(
-        transaction.id,
-        &LockedFundType::CkUSDC,
-    )?;
+        if !result.replayed {
+            crate::holdings_tracker::update_bundle_holdings(
+                transaction.bundle_id,
+                &asset_amount.asset_id,
+                -(asset_amount.amount as i64),
+            )?;
 
-    crate::transaction_manager::update_transaction_status(
-        transaction.id,
-        TransactionStatus::Completed,
-    )?;
+            ic_cdk::println!(
+                "Returned {} {} collateral to resolver {} for buy tx {} (tx: {})",
+                asset_amount.amount,
+                asset_amount.asset_id,
+                assignment.resolver,
+                transaction.id,
+                result.block_index
+            );
+        }
 
-    if matches!(transaction.operation, OperationType::InitialBuy { .. }) {
-        crate::bundle_manager::activate_bundle(transaction.bundle_id)?;
+        returned.push((asset_amount.asset_id.clone(), asset_amount.amount));
     }
 
-    let usdc_amount_e6 = assignment.ckusdc_amount;
-    let points = usdc_amount_e6 / 10_000;
-    crate::memory::add_points(transaction.user, points);
-
-    Ok(())
-}
\ No newline at end of file
+    Ok(returned)
+}
@@ -4,8 +4,9 @@ use crate::types::*;
 use crate::memory::*;
 use crate::transaction_manager;
 
-const MAX_RETRY_ATTEMPTS: u8 = 3;
+pub const MAX_RETRY_ATTEMPTS: u8 = 3;
 const RECOVERY_TIMEOUT_NS: u64 = 3_600_000_000_000;
+const MAX_RECOVERY_LOG_PAGE: u64 = 100;
 
 pub async fn detect_and_handle_timeouts() -> Result<u32, String> {
     let current_time = time();
@@ -14,42 +15,104 @@ pub async fn detect_and_handle_timeouts() -> Result<u32, String> {
     let timed_out_transactions = TRANSACTIONS.with(|transactions| {
         transactions.borrow().iter()
             .filter(|(_, tx)| {
-                matches!(tx.status, TransactionStatus::Pending | TransactionStatus::FundsLocked | TransactionStatus::InProgress)
-                && tx.timeout_at < current_time
+                transaction_manager::is_recoverable(tx) && tx.timeout_at < current_time
             })
             .map(|(id, tx)| (id, tx.clone()))
             .collect::<Vec<_>>()
     });
 
+    // Transactions that keep failing are left to an operator instead of logging a failure every pass
+    let (timed_out_transactions, exhausted): (Vec<_>, Vec<_>) = timed_out_transactions.into_iter()
+        .partition(|(tx_id, _)| failed_attempts(*tx_id) < MAX_RETRY_ATTEMPTS);
+    if !exhausted.is_empty() {
+        ic_cdk::println!(
+            "Skipping {} transactions that failed {} automatic recoveries",
+            exhausted.len(),
+            MAX_RETRY_ATTEMPTS
+        );
+    }
+
     for (tx_id, tx) in timed_out_transactions {
         if recover_transaction(tx_id, tx).await.is_ok() {
             recovered += 1;
         }
     }
 
+    retry_pending_nav_returns().await;
+
     ic_cdk::println!("Recovered {} timed-out transactions", recovered);
     Ok(recovered)
 }
 
-async fn recover_transaction(tx_id: u64, tx: Transaction) -> Result<(), String> {
-    ic_cdk::println!("Recovering transaction {}: status={:?}, operation={:?}",
-        tx_id, tx.status, tx.operation);
+// Escrow returns that hit a ledger error during recovery are retried on every pass
+async fn retry_pending_nav_returns() {
+    let pending = TRANSACTIONS.with(|transactions| {
+        transactions.borrow().iter()
+            .filter(|(_, tx)| {
+                matches!(tx.operation, OperationType::Sell { .. })
+                && matches!(tx.status, TransactionStatus::Failed | TransactionStatus::TimedOut)
+            })
+            .map(|(_, tx)| tx)
+            .filter(|tx| crate::escrow::is_nav_escrowed(tx.id) && !crate::escrow::is_nav_returned(tx.id))
+            .collect::<Vec<_>>()
+    });
+
+    for tx in pending {
+        let _guard = match crate::guard::TransactionGuard::acquire(tx.id) {
+            Ok(guard) => guard,
+            Err(_) => continue,
+        };
+
+        if let Err(e) = crate::escrow::return_nav_tokens(&tx).await {
+            ic_cdk::println!("Failed to return escrowed NAV tokens for tx {}: {}", tx.id, e);
+        }
+    }
+}
+
+async fn recover_transaction(tx_id: u64, tx: Transaction) -> Result<RecoveryOutcome, String> {
+    let _guard = crate::guard::TransactionGuard::acquire(tx_id)?;
+
+    let started_at = time();
+    let prior_events = crate::transaction_events::get_transaction_events(tx_id).len();
+
+    let result = run_recovery(tx_id, &tx).await;
 
-    transaction_manager::update_transaction_status(tx_id, TransactionStatus::TimedOut)?;
+    let ledger_calls_succeeded = crate::transaction_events::get_transaction_events(tx_id)
+        .iter()
+        .skip(prior_events)
+        .filter(|event| event.block_index.is_some())
+        .count() as u32;
 
-    let unlocked_funds = unlock_all_transaction_funds_safe(tx_id).await?;
+    record_recovery_event(&tx, started_at, &result, ledger_calls_succeeded);
+
+    result
+}
+
+async fn run_recovery(tx_id: u64, tx: &Transaction) -> Result<RecoveryOutcome, String> {
+    let reached = transaction_manager::furthest_settlement_status(
+        &transaction_manager::get_transaction_transitions(tx_id)
+    );
+
+    ic_cdk::println!("Recovering transaction {}: status={:?}, reached={:?}, operation={:?}",
+        tx_id, tx.status, reached, tx.operation);
+
+    let outcome = match tx.operation {
+        OperationType::InitialBuy { .. } | OperationType::Buy { .. } => recover_buy_transaction(tx, &reached).await?,
+        OperationType::Sell { .. } => recover_sell_transaction(tx).await?,
+    };
+
+    ic_cdk::println!("Recovered transaction {}: {:?}", tx_id, outcome);
+
+    Ok(outcome)
+}
+
+async fn release_transaction_locks(tx: &Transaction) -> Result<(), String> {
+    let unlocked_funds = unlock_all_transaction_funds_safe(tx.id).await?;
 
     for (fund_type, amount) in unlocked_funds {
         ic_cdk::println!("Unlocked {:?}: {} for user {}", fund_type, amount, tx.user);
     }
 
-    match tx.operation {
-        OperationType::InitialBuy { .. } | OperationType::Buy { .. } => recover_buy_transaction(&tx).await?,
-        OperationType::Sell { .. } => recover_sell_transaction(&tx).await?,
-    }
-
-    record_recovery_event(tx_id, &tx)?;
-
     Ok(())
 }
 
@@ -79,50 +142,337 @@ async fn unlock_all_transaction_funds_safe(tx_id: u64) -> Result<Vec<(LockedFund
     Ok(unlocked)
 }
 
-async fn recover_buy_transaction(tx: &Transaction) -> Result<(), String> {
-    if matches!(tx.status, TransactionStatus::InProgress) {
-        let request_id = tx.request_id;
-        if let Ok(assignment) = crate::quote_manager::get_assignment(request_id) {
-            let canister_id = ic_cdk::api::id();
+pub async fn recover_paused_transaction(tx_id: u64) -> Result<(), String> {
+    let tx = transaction_manager::get_transaction(tx_id)?;
+
+    if !transaction_manager::is_recoverable(&tx) {
+        return Err(format!("Transaction {} cannot be recovered from {:?}", tx_id, tx.status));
+    }
+
+    if !crate::pause_manager::is_transaction_blocked(&tx) {
+        return Err(format!("Transaction {} is not blocked by a pause", tx_id));
+    }
+
+    recover_transaction(tx_id, tx).await.map(|_| ())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BuyRecoveryAction {
+    RefundPayment,
+    UnwindCollateral,
+    CompleteSettlement,
+}
 
-            let ckusdc_ledger = Principal::from_text(crate::icrc2_client::CKUSDC_LEDGER_CANISTER)
-                .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+fn step_attempted(state: Option<&SettlementStepState>) -> bool {
+    matches!(state, Some(SettlementStepState::Pending | SettlementStepState::Succeeded { .. }))
+}
 
-            let balance = crate::icrc2_client::icrc1_balance_of(
-                ckusdc_ledger,
-                canister_id
-            ).await?;
+// Once NAV may have been minted the deposited collateral backs it, so the buy can only be finished;
+// before that, deposited collateral goes back to the resolver and the payment back to the user
+pub fn buy_recovery_action(
+    collateral_pulls: &[SettlementStepState],
+    mint: Option<&SettlementStepState>,
+) -> BuyRecoveryAction {
+    if step_attempted(mint) {
+        BuyRecoveryAction::CompleteSettlement
+    } else if collateral_pulls.iter().any(|pull| step_attempted(Some(pull))) {
+        BuyRecoveryAction::UnwindCollateral
+    } else {
+        BuyRecoveryAction::RefundPayment
+    }
+}
 
-            if balance >= assignment.ckusdc_amount {
-                return crate::icrc2_client::send_ckusdc_to_user(
-                    tx.user,
-                    assignment.ckusdc_amount,
-                    Some(format!("Refund for failed tx {}", tx.id).into_bytes())
-                ).await.map(|_| ());
+async fn recover_buy_transaction(tx: &Transaction, reached: &TransactionStatus) -> Result<RecoveryOutcome, String> {
+    let collateral_pulls = collateral_pull_states(tx.id);
+    let mint = crate::settlement::get_step(tx.id, crate::buy_flow::MINT_STEP).map(|s| s.state);
+    let action = buy_recovery_action(&collateral_pulls, mint.as_ref());
+
+    let actor = ic_cdk::api::canister_self();
+
+    // Ledger work runs before the status change, so a failed refund leaves the buy recoverable
+    let outcome = match action {
+        BuyRecoveryAction::RefundPayment => {
+            let mut outcome = RecoveryOutcome::NothingToRecover;
+            if *reached != TransactionStatus::Pending {
+                let amount = crate::escrow::refund_payment(tx, tx.user).await?;
+                if amount > 0 {
+                    outcome = RecoveryOutcome::CkUSDCRefunded { amount };
+                }
             }
+            transaction_manager::update_transaction_status(tx.id, TransactionStatus::TimedOut, actor)?;
+
+            outcome
+        }
+        BuyRecoveryAction::UnwindCollateral => {
+            let assignment = crate::quote_manager::get_assignment(tx.request_id)?;
+            let bundle = crate::bundle_manager::get_bundle(tx.bundle_id)?;
+
+            let assets = crate::buy_flow::unwind_collateral(tx, &assignment, &bundle).await?;
+            let ckusdc = crate::escrow::refund_payment(tx, tx.user).await?;
+            transaction_manager::update_transaction_status(tx.id, TransactionStatus::TimedOut, actor)?;
+
+            RecoveryOutcome::CollateralReturned { ckusdc, assets }
         }
+        BuyRecoveryAction::CompleteSettlement => {
+            let assignment = crate::quote_manager::get_assignment(tx.request_id)?;
+            let bundle = crate::bundle_manager::get_bundle(tx.bundle_id)?;
+
+            if tx.status == TransactionStatus::InProgress {
+                crate::buy_flow::deposit_assets_and_mint(tx, &assignment, &bundle).await?;
+                transaction_manager::update_transaction_status(tx.id, TransactionStatus::AssetsTransferred, actor)?;
+            }
+            crate::buy_flow::settle_buy_payments(tx, &assignment, &bundle, actor).await?;
+
+            RecoveryOutcome::BuyCompleted
+        }
+    };
+
+    release_transaction_locks(tx).await?;
+
+    Ok(outcome)
+}
+
+fn collateral_pull_states(tx_id: u64) -> Vec<SettlementStepState> {
+    crate::settlement::get_settlement_steps(tx_id)
+        .into_iter()
+        .filter(|step| step.step.starts_with(crate::buy_flow::COLLATERAL_PULL_PREFIX))
+        .map(|step| step.state)
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SellRecoveryAction {
+    ReturnNavTokens,
+    RefundResolver,
+    CompleteDissolution,
+}
+
+// Once the user may have been paid, or the escrowed NAV burned, the sale can only be finished;
+// if only the resolver's payment reached escrow it goes back to the resolver with the NAV to the user
+pub fn sell_recovery_action(
+    resolver_pull: Option<&SettlementStepState>,
+    user_proceeds: Option<&SettlementStepState>,
+    burn: Option<&SettlementStepState>,
+) -> SellRecoveryAction {
+    if step_attempted(user_proceeds) || step_attempted(burn) {
+        SellRecoveryAction::CompleteDissolution
+    } else if step_attempted(resolver_pull) {
+        SellRecoveryAction::RefundResolver
+    } else {
+        SellRecoveryAction::ReturnNavTokens
     }
+}
 
-    Ok(())
+async fn recover_sell_transaction(tx: &Transaction) -> Result<RecoveryOutcome, String> {
+    let step_state = |step: &str| crate::settlement::get_step(tx.id, step).map(|s| s.state);
+
+    let action = sell_recovery_action(
+        step_state(crate::escrow::PAYMENT_ESCROW_STEP).as_ref(),
+        step_state(crate::sell_flow::PROCEEDS_STEP).as_ref(),
+        step_state(crate::sell_flow::BURN_STEP).as_ref(),
+    );
+
+    let actor = ic_cdk::api::canister_self();
+
+    // Ledger work runs before the status change, so a failed attempt leaves the sale recoverable
+    let outcome = match action {
+        SellRecoveryAction::ReturnNavTokens => {
+            let amount = return_escrowed_nav(tx).await?;
+            transaction_manager::update_transaction_status(tx.id, TransactionStatus::TimedOut, actor)?;
+
+            if amount > 0 {
+                RecoveryOutcome::NavTokensReturned { amount }
+            } else {
+                RecoveryOutcome::NothingToRecover
+            }
+        }
+        SellRecoveryAction::RefundResolver => {
+            let assignment = crate::quote_manager::get_assignment(tx.request_id)?;
+            let ckusdc = crate::escrow::refund_payment(tx, assignment.resolver).await?;
+            let nav_tokens = return_escrowed_nav(tx).await?;
+            transaction_manager::update_transaction_status(tx.id, TransactionStatus::TimedOut, actor)?;
+
+            RecoveryOutcome::ResolverRefunded { ckusdc, nav_tokens }
+        }
+        SellRecoveryAction::CompleteDissolution => {
+            let assignment = crate::quote_manager::get_assignment(tx.request_id)?;
+            let bundle = crate::bundle_manager::get_bundle(tx.bundle_id)?;
+
+            if tx.status == TransactionStatus::InProgress {
+                crate::sell_flow::settle_resolver_payment(tx, &assignment, &bundle).await?;
+                transaction_manager::update_transaction_status(tx.id, TransactionStatus::AssetsTransferred, actor)?;
+                let token = crate::payments::transaction_payment_token(tx)?;
+                crate::memory::subtract_points(tx.user, crate::payments::points_for(assignment.ckusdc_amount, token.decimals));
+            }
+
+            let assets = crate::sell_flow::complete_dissolution(tx, &assignment).await?;
+            transaction_manager::update_transaction_status(tx.id, TransactionStatus::Completed, actor)?;
+
+            RecoveryOutcome::DissolutionCompleted { assets }
+        }
+    };
+
+    release_transaction_locks(tx).await?;
+
+    Ok(outcome)
 }
 
-async fn recover_sell_transaction(_tx: &Transaction) -> Result<(), String> {
-    Ok(())
+async fn return_escrowed_nav(tx: &Transaction) -> Result<u64, String> {
+    if crate::escrow::is_nav_escrowed(tx.id) && !crate::escrow::is_nav_returned(tx.id) {
+        crate::escrow::return_nav_tokens(tx).await?;
+        return Ok(tx.nav_tokens);
+    }
+
+    Ok(0)
 }
 
-fn record_recovery_event(tx_id: u64, tx: &Transaction) -> Result<(), String> {
-    RECOVERY_LOG.with(|log| {
-        let event = RecoveryEvent {
-            transaction_id: tx_id,
-            user: tx.user,
-            operation: tx.operation.clone(),
-            recovered_at: time(),
-            funds_recovered: true,
-        };
-        log.borrow_mut().push(event);
+pub fn recovery_log_entry(
+    id: u64,
+    tx: &Transaction,
+    started_at: u64,
+    completed_at: u64,
+    result: &Result<RecoveryOutcome, String>,
+    ledger_calls_succeeded: u32,
+) -> RecoveryLogEntry {
+    let (recovery_result, outcome) = match result {
+        Ok(outcome) => (RecoveryResult::Success, Some(outcome.clone())),
+        // Some ledger calls landed before the failure, so funds were moved but the recovery is unfinished
+        Err(reason) if ledger_calls_succeeded > 0 => (RecoveryResult::Partial { reason: reason.clone() }, None),
+        Err(reason) => (RecoveryResult::Failed { reason: reason.clone() }, None),
+    };
+
+    let (ckusdc_moved, nav_tokens_moved, assets_moved) = match &outcome {
+        Some(RecoveryOutcome::CkUSDCRefunded { amount }) => (*amount, 0, Vec::new()),
+        Some(RecoveryOutcome::NavTokensReturned { amount }) => (0, *amount, Vec::new()),
+        Some(RecoveryOutcome::ResolverRefunded { ckusdc, nav_tokens }) => (*ckusdc, *nav_tokens, Vec::new()),
+        Some(RecoveryOutcome::DissolutionCompleted { assets }) => (0, 0, assets.clone()),
+        Some(RecoveryOutcome::CollateralReturned { ckusdc, assets }) => (*ckusdc, 0, assets.clone()),
+        Some(RecoveryOutcome::BuyCompleted | RecoveryOutcome::NothingToRecover) | None => (0, 0, Vec::new()),
+    };
+
+    RecoveryLogEntry {
+        id,
+        transaction_id: tx.id,
+        user: tx.user,
+        operation: tx.operation.clone(),
+        result: recovery_result,
+        outcome,
+        ckusdc_moved,
+        nav_tokens_moved,
+        assets_moved,
+        ledger_calls_succeeded,
+        started_at,
+        completed_at,
+    }
+}
+
+fn record_recovery_event(
+    tx: &Transaction,
+    started_at: u64,
+    result: &Result<RecoveryOutcome, String>,
+    ledger_calls_succeeded: u32,
+) {
+    let previous = last_recovery_entry(tx.id);
+
+    let entry = RECOVERY_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        let id = log.last_key_value().map_or(0, |(id, _)| id + 1);
+        let entry = recovery_log_entry(id, tx, started_at, time(), result, ledger_calls_succeeded);
+        log.insert(id, entry.clone());
+        entry
     });
 
-    Ok(())
+    RECOVERY_LOG_BY_TRANSACTION.with(|index| {
+        index.borrow_mut().insert((entry.transaction_id, entry.id), ());
+    });
+
+    let mut counters = get_recovery_counters();
+    tally_recovery(&mut counters, previous.as_ref(), &entry);
+    set_recovery_counters(counters);
+}
+
+// Newest first
+fn transaction_recovery_entries(tx_id: u64) -> impl Iterator<Item = RecoveryLogEntry> {
+    let ids: Vec<u64> = RECOVERY_LOG_BY_TRANSACTION.with(|index| {
+        index.borrow()
+            .range((tx_id, 0)..=(tx_id, u64::MAX))
+            .rev()
+            .map(|((_, id), _)| id)
+            .collect()
+    });
+
+    ids.into_iter().filter_map(|id| RECOVERY_LOG.with(|log| log.borrow().get(&id)))
+}
+
+fn last_recovery_entry(tx_id: u64) -> Option<RecoveryLogEntry> {
+    transaction_recovery_entries(tx_id).next()
+}
+
+// Unsuccessful attempts since the transaction's last successful one
+pub fn failed_attempts(tx_id: u64) -> u8 {
+    transaction_recovery_entries(tx_id)
+        .take_while(|entry| entry.result != RecoveryResult::Success)
+        .take(MAX_RETRY_ATTEMPTS as usize)
+        .count() as u8
+}
+
+fn result_count<'a>(counters: &'a mut RecoveryCounters, result: &RecoveryResult) -> &'a mut u32 {
+    match result {
+        RecoveryResult::Success => &mut counters.successful,
+        RecoveryResult::Partial { .. } => &mut counters.partial,
+        RecoveryResult::Failed { .. } => &mut counters.failed,
+    }
+}
+
+fn recovery_duration_ns(entry: &RecoveryLogEntry) -> u64 {
+    entry.completed_at.saturating_sub(entry.started_at)
+}
+
+// Replaces the transaction's previous attempt, if any, with its latest one
+pub fn tally_recovery(counters: &mut RecoveryCounters, previous: Option<&RecoveryLogEntry>, entry: &RecoveryLogEntry) {
+    match previous {
+        Some(previous) => {
+            let count = result_count(counters, &previous.result);
+            *count = count.saturating_sub(1);
+            counters.total_duration_ns = counters.total_duration_ns.saturating_sub(recovery_duration_ns(previous));
+        }
+        None => counters.transactions += 1,
+    }
+
+    *result_count(counters, &entry.result) += 1;
+    counters.total_duration_ns += recovery_duration_ns(entry);
+}
+
+// Entries in log order
+pub fn tally_recovery_log(entries: &[RecoveryLogEntry]) -> RecoveryCounters {
+    let mut latest: std::collections::HashMap<u64, &RecoveryLogEntry> = std::collections::HashMap::new();
+    let mut counters = RecoveryCounters::default();
+
+    for entry in entries {
+        let previous = latest.insert(entry.transaction_id, entry);
+        tally_recovery(&mut counters, previous, entry);
+    }
+
+    counters
+}
+
+// Newest first; pass the id of the last entry seen to fetch the next page
+pub fn get_recovery_log(before: Option<u64>, limit: u64) -> Vec<RecoveryLogEntry> {
+    let limit = limit.min(MAX_RECOVERY_LOG_PAGE) as usize;
+
+    RECOVERY_LOG.with(|log| {
+        let log = log.borrow();
+        match before {
+            Some(before) => log.range(..before).rev().take(limit).map(|(_, entry)| entry).collect(),
+            None => log.iter().rev().take(limit).map(|(_, entry)| entry).collect(),
+        }
+    })
+}
+
+pub fn get_transaction_recovery_log(tx_id: u64) -> Vec<RecoveryLogEntry> {
+    let mut entries: Vec<_> = transaction_recovery_entries(tx_id).collect();
+    entries.reverse();
+    entries
 }
 
 pub fn validate_transaction_integrity(tx_id: u64) -> Result<(), String> {
@@ -137,8 +487,8 @@ pub fn validate_transaction_integrity(tx_id: u64) -> Result<(), String> {
 
     match tx.operation {
         OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
-            if !locked_funds.iter().any(|l| matches!(&l.fund_type, LockedFundType::CkUSDC)) {
-                return Err("Missing ckUSDC lock".to_string());
+            if !locked_funds.iter().any(|l| matches!(&l.fund_type, LockedFundType::Payment { .. })) {
+                return Err("Missing payment lock".to_string());
             }
         },
         OperationType::Sell { .. } => {
@@ -158,22 +508,41 @@ pub fn validate_transaction_integrity(tx_id: u64) -> Result<(), String> {
 }
 
 pub async fn perform_emergency_recovery(user: Principal) -> Result<RecoveryReport, String> {
-    let user_transactions = transaction_manager::get_user_transactions(user);
+    let current_time = time();
     let mut recovered_ckusdc = 0u64;
     let mut recovered_nav = 0u64;
-    let recovered_assets = Vec::new();
-
-    for tx in user_transactions.iter() {
-        if matches!(tx.status, TransactionStatus::Pending | TransactionStatus::FundsLocked | TransactionStatus::InProgress) {
-            if let Ok(funds) = unlock_all_transaction_funds_safe(tx.id).await {
-                for (fund_type, amount) in funds {
-                    match fund_type {
-                        LockedFundType::CkUSDC => recovered_ckusdc += amount,
-                        LockedFundType::NAVTokens { .. } => recovered_nav += amount
-                    }
-                }
+    let mut recovered_assets = Vec::new();
+    let mut outcomes = Vec::new();
+
+    let stalled = transaction_manager::get_user_transactions(user)
+        .into_iter()
+        .filter(|tx| transaction_manager::is_recoverable(tx) && tx.timeout_at < current_time);
+
+    for tx in stalled {
+        let tx_id = tx.id;
+        let payment_asset = tx.payment_asset.clone();
+        let outcome = match recover_transaction(tx_id, tx).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                ic_cdk::println!("Emergency recovery of tx {} failed: {}", tx_id, e);
+                continue;
+            }
+        };
+
+        match &outcome {
+            RecoveryOutcome::CkUSDCRefunded { amount } => {
+                recovered_ckusdc += crate::payments::usd_value_e6(&payment_asset, *amount)
             }
+            RecoveryOutcome::NavTokensReturned { amount } => recovered_nav += amount,
+            RecoveryOutcome::ResolverRefunded { nav_tokens, .. } => recovered_nav += nav_tokens,
+            RecoveryOutcome::DissolutionCompleted { assets } => recovered_assets.extend(assets.iter().cloned()),
+            RecoveryOutcome::CollateralReturned { ckusdc, .. } => {
+                recovered_ckusdc += crate::payments::usd_value_e6(&payment_asset, *ckusdc)
+            }
+            RecoveryOutcome::BuyCompleted | RecoveryOutcome::NothingToRecover => {}
         }
+
+        outcomes.push((tx_id, outcome));
     }
 
     Ok(RecoveryReport {
@@ -181,35 +550,44 @@ pub async fn perform_emergency_recovery(user: Principal) -> Result<RecoveryRepor
         recovered_ckusdc,
         recovered_nav_tokens: recovered_nav,
         recovered_assets,
+        outcomes,
         timestamp: time(),
     })
 }
 
+// Reads the running counters; only the last day of the log is scanned
 pub fn get_recovery_statistics() -> RecoveryStatistics {
-    let total_events = RECOVERY_LOG.with(|log| log.borrow().len() as u32);
-
-    let last_24h = time() - 86_400_000_000_000;
-    let recent_events = RECOVERY_LOG.with(|log| {
-        log.borrow().iter()
-            .filter(|e| e.recovered_at > last_24h)
-            .count() as u32
+    let cutoff = time().saturating_sub(86_400_000_000_000);
+
+    let recent_transactions = RECOVERY_LOG.with(|log| {
+        log.borrow().iter().rev()
+            .map(|(_, entry)| entry)
+            .take_while(|entry| entry.completed_at > cutoff)
+            .map(|entry| entry.transaction_id)
+            .collect::<std::collections::BTreeSet<_>>()
+            .len() as u32
     });
 
-    RecoveryStatistics {
-        total_recoveries: total_events,
-        recoveries_last_24h: recent_events,
+    compute_recovery_statistics(&get_recovery_counters(), recent_transactions)
+}
+
+pub fn compute_recovery_statistics(counters: &RecoveryCounters, recoveries_last_24h: u32) -> RecoveryStatistics {
+    let mut stats = RecoveryStatistics {
+        total_recoveries: counters.transactions,
+        recoveries_last_24h,
+        successful_recoveries: counters.successful,
+        partial_recoveries: counters.partial,
+        failed_recoveries: counters.failed,
         average_recovery_time_ms: 0,
-        success_rate: 100.0,
+        success_rate: 0.0,
+    };
+
+    if counters.transactions > 0 {
+        stats.average_recovery_time_ms = counters.total_duration_ns / counters.transactions as u64 / 1_000_000;
+        stats.success_rate = counters.successful as f64 / counters.transactions as f64 * 100.0;
     }
-}
 
-#[derive(Clone, Debug)]
-struct RecoveryEvent {
-    transaction_id: u64,
-    user: Principal,
-    operation: OperationType,
-    recovered_at: u64,
-    funds_recovered: bool,
+    stats
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
@@ -218,6 +596,7 @@ pub struct RecoveryReport {
     pub recovered_ckusdc: u64,
     pub recovered_nav_tokens: u64,
     pub recovered_assets: Vec<(AssetId, u64)>,
+    pub outcomes: Vec<(u64, RecoveryOutcome)>,
     pub timestamp: u64,
 }
 
@@ -225,10 +604,9 @@ pub struct RecoveryReport {
 pub struct RecoveryStatistics {
     pub total_recoveries: u32,
     pub recoveries_last_24h: u32,
+    pub successful_recoveries: u32,
+    pub partial_recoveries: u32,
+    pub failed_recoveries: u32,
     pub average_recovery_time_ms: u64,
     pub success_rate: f64,
 }
-
-thread_local! {
-    static RECOVERY_LOG: std::cell::RefCell<Vec<RecoveryEvent>> = std::cell::RefCell::new(Vec::new());
-}
\ No newline at end of file
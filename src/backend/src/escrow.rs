@@ -0,0 +1,282 @@
+use crate::types::*;
+use candid::Principal;
+use crate::{icrc151_client, icrc2_client, settlement};
+
+const ESCROW_SUBACCOUNT_TAG: u8 = 0x01;
+
+pub const NAV_ESCROW_STEP: &str = "escrow:nav";
+pub const NAV_RETURN_STEP: &str = "escrow:return";
+// Step names are persisted, so the payment escrow keeps its original ckUSDC name
+pub const PAYMENT_ESCROW_STEP: &str = "escrow:ckusdc";
+pub const PAYMENT_REFUND_STEP: &str = "escrow:refund";
+
+pub fn transaction_subaccount(transaction_id: u64) -> [u8; 32] {
+    let mut subaccount = [0u8; 32];
+    subaccount[0] = ESCROW_SUBACCOUNT_TAG;
+    subaccount[24..].copy_from_slice(&transaction_id.to_be_bytes());
+    subaccount
+}
+
+pub fn escrow_account(transaction_id: u64) -> icrc151_client::Account {
+    icrc151_client::Account {
+        owner: ic_cdk::api::canister_self(),
+        subaccount: Some(transaction_subaccount(transaction_id).to_vec()),
+    }
+}
+
+// Every transfer out of the escrow subaccount pays the ledger fee from the escrowed amount,
+// so the final leg receives what is left after the platform fee and one ledger fee per leg
+pub fn net_payout(gross: u64, platform_fee: u64, ledger_fee: u64, legs: u64) -> Result<u64, String> {
+    gross
+        .checked_sub(platform_fee)
+        .and_then(|rest| rest.checked_sub(ledger_fee.saturating_mul(legs)))
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| format!(
+            "Escrowed amount {} does not cover platform fee {} and {} ledger fees of {}",
+            gross, platform_fee, legs, ledger_fee
+        ))
+}
+
+pub fn is_nav_escrowed(transaction_id: u64) -> bool {
+    matches!(
+        settlement::get_step(transaction_id, NAV_ESCROW_STEP).map(|step| step.state),
+        Some(SettlementStepState::Succeeded { .. })
+    )
+}
+
+pub fn is_nav_returned(transaction_id: u64) -> bool {
+    matches!(
+        settlement::get_step(transaction_id, NAV_RETURN_STEP).map(|step| step.state),
+        Some(SettlementStepState::Succeeded { .. })
+    )
+}
+
+pub async fn escrow_nav_tokens(transaction: &Transaction, bundle: &BundleConfig) -> Result<u64, String> {
+    let (ledger, token_id) = bundle.get_token_location()?;
+    let subaccount = transaction_subaccount(transaction.id).to_vec();
+
+    let memo = format!(
+        "Sell tx {} - escrow {} bundle tokens",
+        transaction.id,
+        transaction.nav_tokens
+    ).into_bytes();
+
+    let call = LedgerCall {
+        kind: LedgerCallKind::Icrc151TransferFrom,
+        ledger,
+        token_id: Some(token_id.clone()),
+        from: Some(transaction.user),
+        from_subaccount: None,
+        to: Some(ic_cdk::api::canister_self()),
+        to_subaccount: Some(subaccount),
+        amount: transaction.nav_tokens,
+        memo: Some(memo.clone()),
+    };
+
+    let result = settlement::run_ledger_step(transaction.id, NAV_ESCROW_STEP, call, |created_at_time| {
+        icrc151_client::transfer_from_icrc151(
+            ledger,
+            token_id,
+            icrc151_client::Account {
+                owner: transaction.user,
+                subaccount: None,
+            },
+            escrow_account(transaction.id),
+            transaction.nav_tokens,
+            Some(memo),
+            Some(created_at_time),
+        )
+    }).await?;
+
+    ic_cdk::println!(
+        "Escrowed {} bundle tokens from user {} for sell tx {} (tx: {})",
+        transaction.nav_tokens,
+        transaction.user,
+        transaction.id,
+        result.block_index
+    );
+
+    Ok(result.block_index)
+}
+
+pub async fn return_nav_tokens(transaction: &Transaction) -> Result<u64, String> {
+    let bundle = crate::bundle_manager::get_bundle(transaction.bundle_id)?;
+    let (ledger, token_id) = bundle.get_token_location()?;
+    let subaccount = transaction_subaccount(transaction.id).to_vec();
+
+    let memo = format!(
+        "Sell tx {} - return {} escrowed bundle tokens",
+        transaction.id,
+        transaction.nav_tokens
+    ).into_bytes();
+
+    let call = LedgerCall {
+        kind: LedgerCallKind::Icrc151Transfer,
+        ledger,
+        token_id: Some(token_id.clone()),
+        from: Some(ic_cdk::api::canister_self()),
+        from_subaccount: Some(subaccount.clone()),
+        to: Some(transaction.user),
+        to_subaccount: None,
+        amount: transaction.nav_tokens,
+        memo: Some(memo.clone()),
+    };
+
+    let result = settlement::run_ledger_step(transaction.id, NAV_RETURN_STEP, call, |created_at_time| {
+        icrc151_client::transfer_icrc151(
+            ledger,
+            token_id,
+            Some(subaccount),
+            icrc151_client::Account {
+                owner: transaction.user,
+                subaccount: None,
+            },
+            transaction.nav_tokens,
+            Some(memo),
+            Some(created_at_time),
+        )
+    }).await?;
+
+    ic_cdk::println!(
+        "Returned {} escrowed bundle tokens to user {} for sell tx {} (tx: {})",
+        transaction.nav_tokens,
+        transaction.user,
+        transaction.id,
+        result.block_index
+    );
+
+    Ok(result.block_index)
+}
+
+pub async fn escrow_payment(
+    token: &PaymentToken,
+    transaction_id: u64,
+    step: &str,
+    from: Principal,
+    amount: u64,
+    memo: Vec<u8>,
+) -> Result<u64, String> {
+    let ledger = token.ledger;
+    let subaccount = transaction_subaccount(transaction_id);
+
+    let call = LedgerCall {
+        kind: LedgerCallKind::Icrc2TransferFrom,
+        ledger,
+        token_id: None,
+        from: Some(from),
+        from_subaccount: None,
+        to: Some(ic_cdk::api::canister_self()),
+        to_subaccount: Some(subaccount.to_vec()),
+        amount,
+        memo: Some(memo.clone()),
+    };
+
+    let result = settlement::run_ledger_step(transaction_id, step, call, |created_at_time| {
+        icrc2_client::icrc2_transfer_from(
+            ledger,
+            from,
+            ic_cdk::api::canister_self(),
+            Some(subaccount),
+            amount,
+            Some(memo),
+            Some(created_at_time),
+        )
+    }).await?;
+
+    ic_cdk::println!(
+        "Escrowed {} {} from {} for tx {} (tx: {})",
+        amount,
+        token.asset_id,
+        from,
+        transaction_id,
+        result.block_index
+    );
+
+    Ok(result.block_index)
+}
+
+pub async fn pay_from_payment_escrow(
+    token: &PaymentToken,
+    transaction_id: u64,
+    step: &str,
+    to: Principal,
+    amount: u64,
+    memo: Vec<u8>,
+) -> Result<u64, String> {
+    let ledger = token.ledger;
+    let subaccount = transaction_subaccount(transaction_id);
+
+    let call = LedgerCall {
+        kind: LedgerCallKind::Icrc1Transfer,
+        ledger,
+        token_id: None,
+        from: Some(ic_cdk::api::canister_self()),
+        from_subaccount: Some(subaccount.to_vec()),
+        to: Some(to),
+        to_subaccount: None,
+        amount,
+        memo: Some(memo.clone()),
+    };
+
+    let result = settlement::run_ledger_step(transaction_id, step, call, |created_at_time| {
+        icrc2_client::icrc1_transfer(
+            ledger,
+            Some(subaccount),
+            to,
+            amount,
+            Some(memo),
+            Some(created_at_time),
+        )
+    }).await?;
+
+    Ok(result.block_index)
+}
+
+pub async fn escrowed_payment_balance(token: &PaymentToken, transaction_id: u64) -> Result<u64, String> {
+    icrc2_client::icrc1_balance_of(
+        token.ledger,
+        ic_cdk::api::canister_self(),
+        Some(transaction_subaccount(transaction_id)),
+    ).await
+}
+
+// Returns whatever is left in the transaction's subaccount, never touching other escrows
+pub async fn refund_payment(transaction: &Transaction, to: Principal) -> Result<u64, String> {
+    let transaction_id = transaction.id;
+    if let Some(step) = settlement::get_step(transaction_id, PAYMENT_REFUND_STEP) {
+        if let SettlementStepState::Succeeded { .. } = step.state {
+            return Ok(0);
+        }
+    }
+
+    let token = crate::payments::transaction_payment_token(transaction)?;
+    let balance = escrowed_payment_balance(&token, transaction_id).await?;
+    let ledger_fee = icrc2_client::icrc1_fee(token.ledger).await?;
+
+    if balance <= ledger_fee {
+        return Ok(0);
+    }
+
+    let refund = balance - ledger_fee;
+    let memo = format!("Refund for failed tx {}", transaction_id).into_bytes();
+
+    let block_index = pay_from_payment_escrow(
+        &token,
+        transaction_id,
+        PAYMENT_REFUND_STEP,
+        to,
+        refund,
+        memo,
+    ).await?;
+
+    ic_cdk::println!(
+        "Refunded {} escrowed {} to {} for tx {} (tx: {})",
+        refund,
+        token.asset_id,
+        to,
+        transaction_id,
+        block_index
+    );
+
+    Ok(refund)
+}
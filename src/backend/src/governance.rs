@@ -0,0 +1,414 @@
+use candid::Principal;
+use ic_cdk::api::{msg_caller, time};
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::memory::*;
+
+const MAX_GOVERNANCE_DELAY_NS: u64 = 30 * 86_400_000_000_000;
+const MAX_PROPOSALS_PAGE: u64 = 100;
+
+pub fn required_role(action: &GovernedAction) -> Role {
+    match action {
+        GovernedAction::SetDefaultPlatformFeeBps { .. }
+        | GovernedAction::SetBundlePlatformFee { .. } => Role::FeeManager,
+        GovernedAction::UpdateAssetTokenLocation { .. } => Role::AssetManager,
+        GovernedAction::SetBundleValuationMode { .. } => Role::Operator,
+        GovernedAction::SetCoordinatorPublicKey { .. }
+        | GovernedAction::SetPlatformTreasury { .. }
+        | GovernedAction::SetAdmin { .. }
+        | GovernedAction::SetGovernanceConfig { .. }
+        | GovernedAction::SetCkusdcLedger { .. } => Role::Owner,
+    }
+}
+
+// Format checks that can fail a proposal up front instead of at execution time
+pub fn validate_action(action: &GovernedAction) -> Result<(), String> {
+    match action {
+        GovernedAction::SetCoordinatorPublicKey { public_key_hex } => {
+            crate::quote_manager::decode_coordinator_public_key(public_key_hex).map(|_| ())
+        }
+        GovernedAction::SetDefaultPlatformFeeBps { fee_bps }
+        | GovernedAction::SetBundlePlatformFee { fee_bps, .. } => {
+            if *fee_bps > 10000 {
+                return Err("Fee cannot exceed 100% (10000 bps)".to_string());
+            }
+            Ok(())
+        }
+        GovernedAction::UpdateAssetTokenLocation { token_location, .. } => {
+            crate::asset_registry::validate_token_location(token_location)
+        }
+        GovernedAction::SetBundleValuationMode { mode, .. } => crate::price_history::validate_valuation_mode(mode),
+        GovernedAction::SetPlatformTreasury { treasury: principal }
+        | GovernedAction::SetAdmin { new_admin: principal }
+        | GovernedAction::SetCkusdcLedger { ledger: principal } => {
+            if *principal == Principal::anonymous() {
+                return Err("Anonymous principal is not allowed".to_string());
+            }
+            Ok(())
+        }
+        GovernedAction::SetGovernanceConfig { config } => {
+            if config.required_approvals == 0 {
+                return Err("At least one approval is required".to_string());
+            }
+            if config.delay_ns > MAX_GOVERNANCE_DELAY_NS {
+                return Err("Delay cannot exceed 30 days".to_string());
+            }
+            // Owner-gated actions, including this one, can only ever gather approvals from owners
+            let owners = crate::roles::role_holders(Role::Owner).len() as u32;
+            if config.required_approvals > owners {
+                return Err(format!(
+                    "{} approvals required but only {} principals hold the Owner role",
+                    config.required_approvals,
+                    owners
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+// Checks that need a call to another canister, run before the proposal is stored
+async fn validate_action_remote(action: &GovernedAction) -> Result<(), String> {
+    match action {
+        GovernedAction::SetCkusdcLedger { ledger } => {
+            crate::icrc2_client::validate_stablecoin_ledger(*ledger).await.map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+// A setting that has never been configured has nothing to protect, so its first value applies on the call
+pub fn is_unset(action: &GovernedAction) -> bool {
+    match action {
+        GovernedAction::SetCoordinatorPublicKey { .. } => {
+            GLOBAL_STATE.with(|s| s.borrow().get().coordinator_public_key.is_none())
+        }
+        GovernedAction::SetPlatformTreasury { .. } => crate::admin::get_platform_treasury().is_none(),
+        GovernedAction::SetDefaultPlatformFeeBps { .. } => {
+            GLOBAL_STATE.with(|s| s.borrow().get().default_platform_fee_bps.is_none())
+        }
+        GovernedAction::SetBundlePlatformFee { bundle_id, .. } => crate::bundle_manager::get_bundle(*bundle_id)
+            .map(|bundle| bundle.platform_fee_bps.is_none())
+            .unwrap_or(false),
+        GovernedAction::UpdateAssetTokenLocation { .. } => false,
+        GovernedAction::SetAdmin { .. } => get_admin_principal().is_none(),
+        GovernedAction::SetGovernanceConfig { .. } => {
+            GLOBAL_STATE.with(|s| s.borrow().get().governance_config.is_none())
+        }
+        GovernedAction::SetCkusdcLedger { .. } => get_icrc2_ckusdc_ledger().is_none(),
+        GovernedAction::SetBundleValuationMode { bundle_id, .. } => crate::bundle_manager::get_bundle(*bundle_id)
+            .map(|bundle| bundle.valuation_mode.is_none())
+            .unwrap_or(false),
+    }
+}
+
+pub fn is_executable(proposal: &Proposal, now: u64) -> bool {
+    proposal.status == ProposalStatus::Pending
+        && now >= proposal.executable_at
+        && proposal.approvals.len() as u32 >= proposal.required_approvals
+}
+
+pub fn add_approval(proposal: &mut Proposal, approver: Principal) -> Result<(), String> {
+    if proposal.status != ProposalStatus::Pending {
+        return Err(format!("Proposal {} is not pending: {:?}", proposal.id, proposal.status));
+    }
+
+    if proposal.approvals.contains(&approver) {
+        return Err(format!("Proposal {} already approved by {}", proposal.id, approver));
+    }
+
+    proposal.approvals.push(approver);
+    Ok(())
+}
+
+pub fn action_name(action: &GovernedAction) -> &'static str {
+    match action {
+        GovernedAction::SetCoordinatorPublicKey { .. } => "set_coordinator_public_key",
+        GovernedAction::SetPlatformTreasury { .. } => "set_platform_treasury",
+        GovernedAction::SetDefaultPlatformFeeBps { .. } => "set_default_platform_fee_bps",
+        GovernedAction::SetBundlePlatformFee { .. } => "set_bundle_platform_fee",
+        GovernedAction::UpdateAssetTokenLocation { .. } => "update_asset_token_location",
+        GovernedAction::SetAdmin { .. } => "set_admin",
+        GovernedAction::SetGovernanceConfig { .. } => "set_governance_config",
+        GovernedAction::SetCkusdcLedger { .. } => "set_ckusdc_ledger",
+        GovernedAction::SetBundleValuationMode { .. } => "set_bundle_valuation_mode",
+    }
+}
+
+pub fn action_target(action: &GovernedAction) -> String {
+    match action {
+        GovernedAction::SetBundlePlatformFee { bundle_id, .. }
+        | GovernedAction::SetBundleValuationMode { bundle_id, .. } => format!("bundle:{}", bundle_id),
+        GovernedAction::UpdateAssetTokenLocation { asset_id, .. } => format!("asset:{}", asset_id),
+        _ => "platform".to_string(),
+    }
+}
+
+// Snapshot of the configuration an action touches, for before/after audit entries
+fn current_value(action: &GovernedAction) -> Option<String> {
+    use crate::audit::describe;
+
+    match action {
+        GovernedAction::SetCoordinatorPublicKey { .. } => {
+            GLOBAL_STATE.with(|s| s.borrow().get().coordinator_public_key.clone())
+                .map(|key| key.iter().map(|b| format!("{:02x}", b)).collect())
+        }
+        GovernedAction::SetPlatformTreasury { .. } => Some(format!(
+            "treasury: {:?}, pending: {:?}",
+            crate::admin::get_platform_treasury(),
+            get_pending_treasury().map(|n| n.nominee)
+        )),
+        GovernedAction::SetDefaultPlatformFeeBps { .. } => Some(crate::admin::get_default_platform_fee_bps().to_string()),
+        GovernedAction::SetBundlePlatformFee { bundle_id, .. } => crate::bundle_manager::get_bundle(*bundle_id)
+            .ok()
+            .map(|bundle| describe(&bundle.platform_fee_bps)),
+        GovernedAction::UpdateAssetTokenLocation { asset_id, .. } => crate::asset_registry::get_asset(asset_id.clone())
+            .ok()
+            .map(|asset| describe(&asset.token_location)),
+        GovernedAction::SetAdmin { .. } => Some(format!(
+            "admin: {:?}, pending: {:?}",
+            get_admin_principal(),
+            get_pending_admin().map(|n| n.nominee)
+        )),
+        GovernedAction::SetGovernanceConfig { .. } => Some(describe(&get_governance_config())),
+        GovernedAction::SetCkusdcLedger { .. } => Some(describe(&get_icrc2_ckusdc_ledger())),
+        GovernedAction::SetBundleValuationMode { bundle_id, .. } => crate::bundle_manager::get_bundle(*bundle_id)
+            .ok()
+            .map(|bundle| describe(&bundle.valuation_mode)),
+    }
+}
+
+fn get_stored_proposal(proposal_id: u64) -> Result<Proposal, String> {
+    PROPOSALS.with(|proposals| proposals.borrow().get(&proposal_id))
+        .ok_or_else(|| format!("Proposal {} not found", proposal_id))
+}
+
+fn save_proposal(proposal: Proposal) {
+    PROPOSALS.with(|proposals| {
+        proposals.borrow_mut().insert(proposal.id, proposal);
+    });
+}
+
+pub fn propose(action: GovernedAction) -> Result<u64, String> {
+    let proposer = crate::roles::require_role(required_role(&action))?;
+    validate_action(&action)?;
+
+    let bootstrap = is_unset(&action);
+    let config = if bootstrap {
+        GovernanceConfig { delay_ns: 0, required_approvals: 1 }
+    } else {
+        get_governance_config()
+    };
+    let now = time();
+
+    let id = PROPOSALS.with(|proposals| {
+        proposals.borrow().last_key_value().map_or(0, |(id, _)| id + 1)
+    });
+
+    let proposal = Proposal {
+        id,
+        action: action.clone(),
+        proposer,
+        approvals: vec![proposer],
+        required_approvals: config.required_approvals,
+        created_at: now,
+        executable_at: now + config.delay_ns,
+        status: ProposalStatus::Pending,
+        resolved_at: None,
+        resolved_by: None,
+    };
+    save_proposal(proposal.clone());
+
+    crate::audit::record(
+        proposer,
+        "propose",
+        format!("proposal:{}", id),
+        Some(crate::audit::describe(&action)),
+        None,
+        None,
+    );
+
+    ic_cdk::println!(
+        "Proposal {} by {} scheduled for {}: {:?}",
+        id,
+        proposer,
+        now + config.delay_ns,
+        action
+    );
+
+    if bootstrap {
+        if let ProposalStatus::Failed { error } = execute_proposal(proposal, now) {
+            return Err(format!("Proposal {} failed: {}", id, error));
+        }
+    }
+
+    Ok(id)
+}
+
+pub async fn propose_validated(action: GovernedAction) -> Result<u64, String> {
+    crate::roles::require_role(required_role(&action))?;
+    validate_action_remote(&action).await?;
+    propose(action)
+}
+
+#[update]
+pub async fn propose_admin_action(action: GovernedAction) -> Result<u64, String> {
+    propose_validated(action).await
+}
+
+#[update]
+pub fn approve_proposal(proposal_id: u64) -> Result<Proposal, String> {
+    let mut proposal = get_stored_proposal(proposal_id)?;
+    let approver = crate::roles::require_role(required_role(&proposal.action))?;
+
+    add_approval(&mut proposal, approver)?;
+    save_proposal(proposal.clone());
+
+    crate::audit::record(
+        approver,
+        "approve_proposal",
+        format!("proposal:{}", proposal_id),
+        Some(crate::audit::describe(&proposal.action)),
+        None,
+        None,
+    );
+
+    ic_cdk::println!("Proposal {} approved by {}", proposal_id, approver);
+    Ok(proposal)
+}
+
+#[update]
+pub fn cancel_proposal(proposal_id: u64) -> Result<Proposal, String> {
+    let caller = msg_caller();
+    let mut proposal = get_stored_proposal(proposal_id)?;
+
+    if caller != proposal.proposer && !crate::roles::has_role(caller, Role::Owner) {
+        return Err("Only the proposer or an owner can cancel a proposal".to_string());
+    }
+
+    if proposal.status != ProposalStatus::Pending {
+        return Err(format!("Proposal {} is not pending: {:?}", proposal_id, proposal.status));
+    }
+
+    proposal.status = ProposalStatus::Cancelled;
+    proposal.resolved_at = Some(time());
+    proposal.resolved_by = Some(caller);
+    save_proposal(proposal.clone());
+
+    crate::audit::record(
+        caller,
+        "cancel_proposal",
+        format!("proposal:{}", proposal_id),
+        Some(crate::audit::describe(&proposal.action)),
+        None,
+        None,
+    );
+
+    ic_cdk::println!("Proposal {} cancelled by {}", proposal_id, caller);
+    Ok(proposal)
+}
+
+fn apply_action(proposal: &Proposal) -> Result<(), String> {
+    match proposal.action.clone() {
+        GovernedAction::SetCoordinatorPublicKey { public_key_hex } => {
+            crate::quote_manager::apply_coordinator_public_key(&public_key_hex)
+        }
+        GovernedAction::SetPlatformTreasury { treasury } => crate::admin::nominate_treasury(treasury, proposal.proposer),
+        GovernedAction::SetDefaultPlatformFeeBps { fee_bps } => crate::admin::apply_default_platform_fee_bps(fee_bps),
+        GovernedAction::SetBundlePlatformFee { bundle_id, fee_bps } => {
+            crate::bundle_manager::apply_bundle_platform_fee(bundle_id, fee_bps)
+        }
+        GovernedAction::UpdateAssetTokenLocation { asset_id, token_location } => {
+            crate::asset_registry::apply_asset_token_location(asset_id, token_location)
+        }
+        GovernedAction::SetAdmin { new_admin } => crate::admin::nominate_admin(new_admin, proposal.proposer),
+        GovernedAction::SetGovernanceConfig { config } => {
+            set_governance_config(config);
+            Ok(())
+        }
+        GovernedAction::SetCkusdcLedger { ledger } => crate::admin::apply_ckusdc_ledger(ledger),
+        GovernedAction::SetBundleValuationMode { bundle_id, mode } => {
+            crate::bundle_manager::apply_bundle_valuation_mode(bundle_id, mode)
+        }
+    }
+}
+
+fn execute_proposal(mut proposal: Proposal, now: u64) -> ProposalStatus {
+    let before = current_value(&proposal.action);
+    let result = apply_action(&proposal);
+
+    crate::audit::record(
+        proposal.proposer,
+        action_name(&proposal.action),
+        action_target(&proposal.action),
+        Some(match &result {
+            Ok(()) => format!("Proposal {} executed: {:?}", proposal.id, proposal.action),
+            Err(error) => format!("Proposal {} failed: {}", proposal.id, error),
+        }),
+        before,
+        current_value(&proposal.action),
+    );
+
+    proposal.status = match result {
+        Ok(()) => ProposalStatus::Executed,
+        Err(error) => ProposalStatus::Failed { error },
+    };
+    proposal.resolved_at = Some(now);
+    proposal.resolved_by = Some(ic_cdk::api::canister_self());
+
+    ic_cdk::println!("Proposal {} resolved: {:?}", proposal.id, proposal.status);
+    let status = proposal.status.clone();
+    save_proposal(proposal);
+    status
+}
+
+// Called from the canister #[heartbeat]
+pub fn execute_due_proposals() -> u32 {
+    let now = time();
+
+    let due = PROPOSALS.with(|proposals| {
+        proposals.borrow().iter()
+            .map(|(_, proposal)| proposal)
+            .filter(|proposal| is_executable(proposal, now))
+            .collect::<Vec<_>>()
+    });
+
+    let mut executed = 0;
+
+    for proposal in due {
+        if execute_proposal(proposal, now) == ProposalStatus::Executed {
+            executed += 1;
+        }
+    }
+
+    executed
+}
+
+#[query]
+pub fn get_proposal(proposal_id: u64) -> Result<Proposal, String> {
+    get_stored_proposal(proposal_id)
+}
+
+// Newest first; pass the id of the last proposal seen to fetch the next page
+#[query]
+pub fn list_proposals(before: Option<u64>, limit: u64, pending_only: bool) -> Vec<Proposal> {
+    let limit = limit.min(MAX_PROPOSALS_PAGE) as usize;
+
+    PROPOSALS.with(|proposals| {
+        let proposals = proposals.borrow();
+        let iter: Box<dyn Iterator<Item = (u64, Proposal)>> = match before {
+            Some(before) => Box::new(proposals.range(..before).rev()),
+            None => Box::new(proposals.iter().rev()),
+        };
+
+        iter.map(|(_, proposal)| proposal)
+            .filter(|proposal| !pending_only || proposal.status == ProposalStatus::Pending)
+            .take(limit)
+            .collect()
+    })
+}
+
+#[query]
+pub fn get_governance_settings() -> GovernanceConfig {
+    get_governance_config()
+}
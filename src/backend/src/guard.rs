@@ -0,0 +1,55 @@
+use candid::Principal;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+thread_local! {
+    static TRANSACTIONS_IN_FLIGHT: RefCell<BTreeSet<u64>> = const { RefCell::new(BTreeSet::new()) };
+    static USERS_IN_FLIGHT: RefCell<BTreeSet<Principal>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+// Held across awaits; dropping it (on return, error or a trapped callback's cleanup) releases the slot
+pub struct TransactionGuard {
+    transaction_id: u64,
+}
+
+impl TransactionGuard {
+    pub fn acquire(transaction_id: u64) -> Result<Self, String> {
+        TRANSACTIONS_IN_FLIGHT.with(|in_flight| {
+            if !in_flight.borrow_mut().insert(transaction_id) {
+                return Err(format!("Settlement in progress for transaction {}", transaction_id));
+            }
+            Ok(Self { transaction_id })
+        })
+    }
+}
+
+impl Drop for TransactionGuard {
+    fn drop(&mut self) {
+        TRANSACTIONS_IN_FLIGHT.with(|in_flight| {
+            in_flight.borrow_mut().remove(&self.transaction_id);
+        });
+    }
+}
+
+pub struct UserGuard {
+    user: Principal,
+}
+
+impl UserGuard {
+    pub fn acquire(user: Principal) -> Result<Self, String> {
+        USERS_IN_FLIGHT.with(|in_flight| {
+            if !in_flight.borrow_mut().insert(user) {
+                return Err(format!("Settlement in progress for user {}", user));
+            }
+            Ok(Self { user })
+        })
+    }
+}
+
+impl Drop for UserGuard {
+    fn drop(&mut self) {
+        USERS_IN_FLIGHT.with(|in_flight| {
+            in_flight.borrow_mut().remove(&self.user);
+        });
+    }
+}
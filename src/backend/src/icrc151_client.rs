@@ -26,6 +26,12 @@ pub enum TransferResult {
     Err(TransferError),
 }
 
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TokenBalance {
+    pub token_id: Vec<u8>,
+    pub balance: Nat,
+}
+
 #[derive(CandidType, Serialize, Deserialize)]
 struct Icrc151TransferFromArgs {
     pub token_id: Vec<u8>,
@@ -45,6 +51,7 @@ pub async fn transfer_from_icrc151(
     to: Account,
     amount: u64,
     memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
 ) -> Result<u64, String> {
     let args = Icrc151TransferFromArgs {
         token_id,
@@ -54,7 +61,7 @@ pub async fn transfer_from_icrc151(
         amount: Nat::from(amount),
         fee: None,
         memo,
-        created_at_time: None,
+        created_at_time,
     };
 
     let result: CallResult<(TransferResult,)> = ic_cdk::call(
@@ -65,6 +72,7 @@ pub async fn transfer_from_icrc151(
 
     match result {
         Ok((TransferResult::Ok(tx_id),)) => Ok(tx_id),
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => Ok(duplicate_of),
         Ok((TransferResult::Err(e),)) => Err(format!("Transfer failed: {:?}", e)),
         Err((code, msg)) => Err(format!("Call failed: {:?} - {}", code, msg)),
     }
@@ -84,18 +92,20 @@ struct Icrc151TransferArgs {
 pub async fn transfer_icrc151(
     ledger: Principal,
     token_id: Vec<u8>,
+    from_subaccount: Option<Vec<u8>>,
     to: Account,
     amount: u64,
     memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
 ) -> Result<u64, String> {
     let args = Icrc151TransferArgs {
         token_id,
-        from_subaccount: None,
+        from_subaccount,
         to,
         amount: Nat::from(amount),
         fee: None,
         memo,
-        created_at_time: None,
+        created_at_time,
     };
 
     let result: CallResult<(TransferResult,)> = ic_cdk::call(
@@ -106,6 +116,7 @@ pub async fn transfer_icrc151(
 
     match result {
         Ok((TransferResult::Ok(tx_id),)) => Ok(tx_id),
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => Ok(duplicate_of),
         Ok((TransferResult::Err(e),)) => Err(format!("Transfer failed: {:?}", e)),
         Err((code, msg)) => Err(format!("Call failed: {:?} - {}", code, msg)),
     }
@@ -177,6 +188,16 @@ pub async fn get_balance_icrc151(
     }
 }
 
+// Every token balance `owner` holds on the ledger, in one call
+pub async fn get_balances_for_icrc151(ledger: Principal, owner: Principal) -> Result<Vec<TokenBalance>, String> {
+    ic_cdk::call::Call::bounded_wait(ledger, "get_balances_for")
+        .with_args(&(owner, None::<Vec<u8>>))
+        .await
+        .map_err(|e| format!("Balances query failed: {}", e))?
+        .candid()
+        .map_err(|e| format!("Invalid balances response: {}", e))
+}
+
 pub async fn get_allowance_icrc151(
     ledger: Principal,
     token_id: Vec<u8>,
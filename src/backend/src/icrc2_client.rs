@@ -2,7 +2,16 @@ use candid::{Principal, Nat};
 use ic_cdk::api::call::CallResult;
 use crate::types::*;
 
-pub const CKUSDC_LEDGER_CANISTER: &str = "xevnm-gaaaa-aaaar-qafnq-cai";
+// Seeded into GlobalState for deployments that predate the configurable ledger
+pub const MAINNET_CKUSDC_LEDGER_CANISTER: &str = "xevnm-gaaaa-aaaar-qafnq-cai";
+
+// Quote amounts, fees and NAV math all assume a 6-decimal USD stablecoin
+pub const CKUSDC_DECIMALS: u8 = 6;
+
+pub fn ckusdc_ledger() -> Result<Principal, String> {
+    crate::memory::get_icrc2_ckusdc_ledger()
+        .ok_or_else(|| "ckUSDC ledger is not configured".to_string())
+}
 
 #[derive(candid::CandidType, candid::Deserialize, Clone, Debug)]
 pub struct Account {
@@ -55,6 +64,14 @@ pub struct Allowance {
     pub expires_at: Option<u64>,
 }
 
+#[derive(candid::CandidType, candid::Deserialize, Clone, Debug)]
+pub enum MetadataValue {
+    Nat(Nat),
+    Int(candid::Int),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
 #[derive(candid::CandidType, candid::Deserialize, Clone, Debug)]
 pub enum TransferResult {
     Ok(Nat),
@@ -96,12 +113,14 @@ pub enum ApproveError {
 // ICRC-1 Basic Transfer (when we own the tokens)
 pub async fn icrc1_transfer(
     ledger_canister: Principal,
+    from_subaccount: Option<[u8; 32]>,
     to: Principal,
     amount: u64,
     memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
 ) -> Result<u64, String> {
     let transfer_args = TransferArgs {
-        from_subaccount: None,
+        from_subaccount,
         to: Account {
             owner: to,
             subaccount: None,
@@ -109,7 +128,7 @@ pub async fn icrc1_transfer(
         amount: Nat::from(amount),
         fee: None,
         memo,
-        created_at_time: Some(ic_cdk::api::time()),
+        created_at_time,
     };
 
     let result: CallResult<(TransferResult,)> = ic_cdk::call(
@@ -122,6 +141,10 @@ pub async fn icrc1_transfer(
         Ok((TransferResult::Ok(block_index),)) => {
             block_index.0.try_into().map_err(|_| "Block index too large".to_string())
         },
+        // A retry with the same created_at_time and memo already landed
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => {
+            duplicate_of.0.try_into().map_err(|_| "Block index too large".to_string())
+        },
         Ok((TransferResult::Err(error),)) => Err(format!("Transfer failed: {:?}", error)),
         Err((code, msg)) => Err(format!("Call failed: {:?}: {}", code, msg)),
     }
@@ -132,8 +155,10 @@ pub async fn icrc2_transfer_from(
     ledger_canister: Principal,
     from: Principal,
     to: Principal,
+    to_subaccount: Option<[u8; 32]>,
     amount: u64,
     memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
 ) -> Result<u64, String> {
     let transfer_args = TransferFromArgs {
         spender_subaccount: None,
@@ -143,12 +168,12 @@ pub async fn icrc2_transfer_from(
         },
         to: Account {
             owner: to,
-            subaccount: None,
+            subaccount: to_subaccount,
         },
         amount: Nat::from(amount),
         fee: None,
         memo,
-        created_at_time: Some(ic_cdk::api::time()),
+        created_at_time,
     };
 
     let result: CallResult<(TransferResult,)> = ic_cdk::call(
@@ -161,6 +186,9 @@ pub async fn icrc2_transfer_from(
         Ok((TransferResult::Ok(block_index),)) => {
             block_index.0.try_into().map_err(|_| "Block index too large".to_string())
         },
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => {
+            duplicate_of.0.try_into().map_err(|_| "Block index too large".to_string())
+        },
         Ok((TransferResult::Err(error),)) => Err(format!("Transfer from failed: {:?}", error)),
         Err((code, msg)) => Err(format!("Call failed: {:?}: {}", code, msg)),
     }
@@ -236,10 +264,11 @@ pub async fn icrc2_allowance(
 pub async fn icrc1_balance_of(
     ledger_canister: Principal,
     account: Principal,
+    subaccount: Option<[u8; 32]>,
 ) -> Result<u64, String> {
     let account_arg = Account {
         owner: account,
-        subaccount: None,
+        subaccount,
     };
 
     let result: CallResult<(Nat,)> = ic_cdk::call(
@@ -256,52 +285,97 @@ pub async fn icrc1_balance_of(
     }
 }
 
-// High-level functions for ckUSDC
+// Get the transfer fee using ICRC-1
+pub async fn icrc1_fee(ledger_canister: Principal) -> Result<u64, String> {
+    let result: CallResult<(Nat,)> = ic_cdk::call(
+        ledger_canister,
+        "icrc1_fee",
+        (),
+    ).await;
 
-pub async fn pull_ckusdc_from_user(
-    from: Principal,
-    amount: u64,
-    memo: Option<Vec<u8>>,
-) -> Result<u64, String> {
-    let ledger = Principal::from_text(CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+    match result {
+        Ok((fee,)) => {
+            fee.0.try_into().map_err(|_| "Fee too large".to_string())
+        },
+        Err((code, msg)) => Err(format!("Fee query failed: {:?}: {}", code, msg)),
+    }
+}
 
-    let canister_id = ic_cdk::api::id();
+pub async fn icrc1_metadata(ledger_canister: Principal) -> Result<Vec<(String, MetadataValue)>, String> {
+    ic_cdk::call::Call::bounded_wait(ledger_canister, "icrc1_metadata")
+        .await
+        .map_err(|e| format!("Metadata query failed: {}", e))?
+        .candid()
+        .map_err(|e| format!("Invalid metadata response: {}", e))
+}
 
-    // Check allowance first
-    let allowance = icrc2_allowance(ledger, from, canister_id).await?;
-    if allowance.allowance < amount {
-        return Err(format!("Insufficient allowance: {} < {}", allowance.allowance, amount));
+pub async fn icrc1_decimals(ledger_canister: Principal) -> Result<u8, String> {
+    ic_cdk::call::Call::bounded_wait(ledger_canister, "icrc1_decimals")
+        .await
+        .map_err(|e| format!("Decimals query failed: {}", e))?
+        .candid()
+        .map_err(|e| format!("Invalid decimals response: {}", e))
+}
+
+pub fn stablecoin_ledger_info(
+    ledger: Principal,
+    metadata: &[(String, MetadataValue)],
+    decimals: u8,
+) -> Result<StablecoinLedgerInfo, String> {
+    let text = |key: &str| metadata.iter().find_map(|(k, v)| match v {
+        MetadataValue::Text(text) if k == key => Some(text.clone()),
+        _ => None,
+    });
+    let nat = |key: &str| metadata.iter().find_map(|(k, v)| match v {
+        MetadataValue::Nat(n) if k == key => Some(n.clone()),
+        _ => None,
+    });
+
+    let symbol = text("icrc1:symbol").ok_or("Ledger metadata has no icrc1:symbol")?;
+    let name = text("icrc1:name").unwrap_or_else(|| symbol.clone());
+    let fee = nat("icrc1:fee")
+        .ok_or("Ledger metadata has no icrc1:fee")?
+        .0
+        .try_into()
+        .map_err(|_| "Ledger fee too large".to_string())?;
+
+    if let Some(listed) = nat("icrc1:decimals") {
+        if listed != decimals {
+            return Err(format!("icrc1:decimals metadata ({}) disagrees with icrc1_decimals ({})", listed, decimals));
+        }
     }
 
-    // Pull tokens from user to canister
-    icrc2_transfer_from(ledger, from, canister_id, amount, memo).await
+    if decimals != CKUSDC_DECIMALS {
+        return Err(format!("Stablecoin ledger must use {} decimals, found {}", CKUSDC_DECIMALS, decimals));
+    }
+
+    Ok(StablecoinLedgerInfo { ledger, name, symbol, decimals, fee })
 }
 
-pub async fn send_ckusdc_to_user(
-    to: Principal,
-    amount: u64,
-    memo: Option<Vec<u8>>,
-) -> Result<u64, String> {
-    let ledger = Principal::from_text(CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+// Queries the ledger and rejects anything that doesn't look like a 6-decimal ICRC-1 stablecoin
+pub async fn validate_stablecoin_ledger(ledger: Principal) -> Result<StablecoinLedgerInfo, String> {
+    if ledger == Principal::anonymous() || ledger == Principal::management_canister() {
+        return Err(format!("{} is not a ledger canister", ledger));
+    }
 
-    // Direct transfer from canister to user
-    icrc1_transfer(ledger, to, amount, memo).await
+    let metadata = icrc1_metadata(ledger).await?;
+    let decimals = icrc1_decimals(ledger).await?;
+    stablecoin_ledger_info(ledger, &metadata, decimals)
 }
 
+// High-level functions for ckUSDC
+
 pub async fn get_ckusdc_balance(account: Principal) -> Result<u64, String> {
-    let ledger = Principal::from_text(CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+    let ledger = ckusdc_ledger()?;
 
-    icrc1_balance_of(ledger, account).await
+    icrc1_balance_of(ledger, account, None).await
 }
 
 pub async fn get_asset_balance(asset_id: &AssetId, account: Principal) -> Result<u64, String> {
     let asset_info = crate::asset_registry::get_asset(asset_id.clone())?;
     let ledger = asset_info.get_icrc2_ledger()?;
 
-    icrc1_balance_of(ledger, account).await
+    icrc1_balance_of(ledger, account, None).await
 }
 
 // High-level functions for any asset
@@ -322,7 +396,7 @@ pub async fn pull_asset_from_user(
             asset_id, allowance.allowance, amount));
     }
 
-    icrc2_transfer_from(ledger, from, canister_id, amount, memo).await
+    icrc2_transfer_from(ledger, from, canister_id, None, amount, memo, Some(ic_cdk::api::time())).await
 }
 
 pub async fn send_asset_to_user(
@@ -334,7 +408,7 @@ pub async fn send_asset_to_user(
     let asset_info = crate::asset_registry::get_asset(asset_id.clone())?;
     let ledger = asset_info.get_icrc2_ledger()?;
 
-    icrc1_transfer(ledger, to, amount, memo).await
+    icrc1_transfer(ledger, None, to, amount, memo, Some(ic_cdk::api::time())).await
 }
 
 pub async fn check_user_allowance(
@@ -20,6 +20,20 @@ mod resolver_manager;
 mod sell_flow;
 mod buy_flow;
 mod error_recovery;
+mod pause_manager;
+mod transaction_events;
+mod settlement;
+mod guard;
+mod escrow;
+mod authorization;
+mod roles;
+mod governance;
+mod audit;
+mod migrations;
+mod payments;
+mod price_history;
+mod nav_history;
+mod analytics;
 mod tests;
 
 use types::*;
@@ -29,6 +43,7 @@ use memory::*;
 fn init() {
     let caller = msg_caller();
     set_admin_principal(caller);
+    migrations::stamp_latest_versions();
     ic_cdk::println!("XFusion backend canister initialized with admin: {}", caller);
 }
 
@@ -39,18 +54,17 @@ fn pre_upgrade() {
 
 #[post_upgrade]
 fn post_upgrade() {
-    GLOBAL_STATE.with(|cell| {
-        let state = cell.borrow().get().clone();
-
-        match state.version {
-            1 => {
-                ic_cdk::println!("GlobalState V1 - no migration required");
-            }
-            _ => {
-                ic_cdk::println!("Unknown GlobalState version: {}", state.version);
+    match migrations::run_migrations(false) {
+        Ok(report) => {
+            for step in &report.steps {
+                ic_cdk::println!(
+                    "Migrated {} v{} -> v{} ({} records): {}",
+                    step.region, step.from_version, step.to_version, step.records, step.description
+                );
             }
         }
-    });
+        Err(e) => ic_cdk::trap(format!("Stable-state migration failed: {}", e)),
+    }
 
     ic_cdk::println!("Upgrade completed successfully");
 }
@@ -75,13 +89,20 @@ async fn heartbeat() {
     });
 
     if should_update {
+        governance::execute_due_proposals();
+
         let bundles = bundle_manager::list_active_bundles();
         for bundle in bundles {
-            let _ = nav_calculator::calculate_bundle_nav(bundle.id).await;
+            if let Ok(nav) = nav_calculator::calculate_bundle_nav(bundle.id).await {
+                nav_history::record(&nav);
+            }
+            memory::cache_analytics(analytics::bundle_analytics(bundle.id, None));
             if let Ok(count) = nav_token::get_bundle_holder_count(bundle.id).await {
                 memory::cache_holder_count(bundle.id, count as u64);
             }
         }
+
+        let _ = error_recovery::detect_and_handle_timeouts().await;
     }
 }
 
@@ -95,6 +116,11 @@ async fn get_bundles_list() -> Vec<BundleListItem> {
     query_api::get_bundles_list().await
 }
 
+#[update]
+async fn get_bundles_list_sorted(sort_by: BundleSortKey, descending: bool) -> Vec<BundleListItem> {
+    query_api::get_bundles_list_sorted(sort_by, descending).await
+}
+
 #[update]
 async fn calculate_bundle_nav(bundle_id: u64) -> Result<BundleNAV, String> {
     nav_calculator::calculate_bundle_nav(bundle_id).await
@@ -107,10 +133,16 @@ async fn get_asset_price(asset_id: AssetId) -> Result<AssetPrice, String> {
 
 #[update]
 async fn get_portfolio_value(user: Option<Principal>) -> Result<u64, String> {
-    let user_principal = user.unwrap_or_else(|| msg_caller());
+    let user_principal = user.unwrap_or_else(msg_caller);
     nav_calculator::get_portfolio_value(user_principal).await
 }
 
+#[update]
+async fn get_portfolio_valuation(user: Option<Principal>) -> Result<PortfolioValuation, String> {
+    let user_principal = user.unwrap_or_else(msg_caller);
+    nav_calculator::get_portfolio_valuation(user_principal).await
+}
+
 #[update]
 async fn get_nav_precision_report(bundle_id: u64) -> Result<NAVPrecisionReport, String> {
     nav_calculator::calculate_nav_with_full_precision_report(bundle_id).await
@@ -127,7 +159,7 @@ fn get_assignment(assignment_id: u64) -> Result<QuoteAssignment, String> {
 }
 
 #[update]
-fn set_coordinator_public_key(public_key_hex: String) -> Result<(), String> {
+fn set_coordinator_public_key(public_key_hex: String) -> Result<u64, String> {
     quote_manager::set_coordinator_public_key(public_key_hex)
 }
 
@@ -167,6 +199,37 @@ fn get_transaction(transaction_id: u64) -> Result<Transaction, String> {
     transaction_manager::get_transaction(transaction_id)
 }
 
+#[query]
+fn get_transaction_events(transaction_id: u64) -> Vec<TransactionEvent> {
+    transaction_events::get_transaction_events(transaction_id)
+}
+
+#[query]
+fn get_settlement_steps(transaction_id: u64) -> Vec<SettlementStep> {
+    settlement::get_settlement_steps(transaction_id)
+}
+
+#[update]
+fn resolve_settlement_step(transaction_id: u64, step: String, block_index: Option<u64>) -> Result<(), String> {
+    let operator = roles::require_role(Role::Operator)?;
+    settlement::resolve_pending_step(transaction_id, &step, block_index)?;
+
+    audit::record(
+        operator,
+        "resolve_settlement_step",
+        format!("transaction:{}", transaction_id),
+        Some(format!("step: {}, block_index: {:?}", step, block_index)),
+        None,
+        None,
+    );
+    Ok(())
+}
+
+#[query]
+fn get_transaction_transitions(transaction_id: u64) -> Vec<TransactionTransition> {
+    transaction_manager::get_transaction_transitions(transaction_id)
+}
+
 #[query]
 fn get_user_transactions(user: Principal) -> Vec<Transaction> {
     transaction_manager::get_user_transactions(user)
@@ -179,8 +242,11 @@ fn get_user_locked_funds(user: Principal) -> Vec<LockedFunds> {
 
 #[update]
 async fn cleanup_expired_transactions() -> Result<u32, String> {
-    let _admin = admin::require_admin()?;
-    Ok(transaction_manager::cleanup_expired_transactions().await)
+    let operator = roles::require_role(Role::Operator)?;
+    let cleaned = transaction_manager::cleanup_expired_transactions().await;
+
+    audit::record(operator, "cleanup_expired_transactions", "transactions", Some(format!("Cleaned {}", cleaned)), None, None);
+    Ok(cleaned)
 }
 
 #[update]
@@ -255,12 +321,24 @@ fn get_user_total_locked_amount(user: Principal, fund_type: LockedFundType) -> u
 
 #[update]
 async fn lock_user_funds_with_validation(transaction_id: u64, fund_type: LockedFundType, amount: u64) -> Result<(), String> {
+    authorization::require_transaction_party(transaction_id, authorization::LOCK_FUNDS_PARTIES)?;
     transaction_manager::lock_user_funds_with_validation(transaction_id, fund_type, amount).await
 }
 
 #[update]
 fn unlock_all_transaction_funds(transaction_id: u64) -> Result<Vec<(LockedFundType, u64)>, String> {
-    transaction_manager::unlock_all_transaction_funds(transaction_id)
+    let admin = authorization::require_transaction_party(transaction_id, authorization::UNLOCK_FUNDS_PARTIES)?;
+    let unlocked = transaction_manager::unlock_all_transaction_funds(transaction_id)?;
+
+    audit::record(
+        admin,
+        "unlock_all_transaction_funds",
+        format!("transaction:{}", transaction_id),
+        Some(audit::describe(&unlocked)),
+        None,
+        None,
+    );
+    Ok(unlocked)
 }
 
 #[query]
@@ -275,20 +353,23 @@ fn get_lock_expiration_time(transaction_id: u64, fund_type: LockedFundType) -> R
 
 #[update]
 fn extend_lock_expiration(transaction_id: u64, fund_type: LockedFundType, new_expiration: u64) -> Result<(), String> {
+    authorization::require_transaction_party(transaction_id, authorization::EXTEND_LOCK_PARTIES)?;
     transaction_manager::extend_lock_expiration(transaction_id, &fund_type, new_expiration)
 }
 
 #[update]
 fn cleanup_expired_locks() -> Result<u32, String> {
-    let _admin = admin::require_admin()?;
-    Ok(transaction_manager::cleanup_expired_locks())
+    let operator = roles::require_role(Role::Operator)?;
+    let cleaned = transaction_manager::cleanup_expired_locks();
+
+    audit::record(operator, "cleanup_expired_locks", "locks", Some(format!("Cleaned {}", cleaned)), None, None);
+    Ok(cleaned)
 }
 
 #[query]
 async fn check_ckusdc_allowance(user: Principal) -> Result<u64, String> {
     let canister_id = ic_cdk::api::id();
-    let ledger = Principal::from_text(icrc2_client::CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+    let ledger = icrc2_client::ckusdc_ledger()?;
 
     let allowance = icrc2_client::icrc2_allowance(ledger, user, canister_id).await?;
     allowance.allowance.0.try_into().map_err(|_| "Allowance too large".to_string())
@@ -301,13 +382,27 @@ async fn check_asset_allowance(asset_id: AssetId, user: Principal) -> Result<u64
 
 #[update]
 async fn detect_and_recover_timeouts() -> Result<u32, String> {
-    let _admin = admin::require_admin()?;
-    error_recovery::detect_and_handle_timeouts().await
+    let operator = roles::require_role(Role::Operator)?;
+    let recovered = error_recovery::detect_and_handle_timeouts().await?;
+
+    audit::record(operator, "detect_and_recover_timeouts", "transactions", Some(format!("Recovered {}", recovered)), None, None);
+    Ok(recovered)
+}
+
+#[update]
+async fn recover_paused_transaction(transaction_id: u64) -> Result<(), String> {
+    let operator = roles::require_role(Role::Operator)?;
+    error_recovery::recover_paused_transaction(transaction_id).await?;
+
+    audit::record(operator, "recover_paused_transaction", format!("transaction:{}", transaction_id), None, None, None);
+    Ok(())
 }
 
 #[update]
 async fn emergency_recovery(user: Option<Principal>) -> Result<error_recovery::RecoveryReport, String> {
-    let user_principal = user.unwrap_or_else(|| msg_caller());
+    let caller = msg_caller();
+    let user_principal = user.unwrap_or(caller);
+    authorization::authorize_recovery_for(caller, user_principal)?;
     error_recovery::perform_emergency_recovery(user_principal).await
 }
 
@@ -321,6 +416,16 @@ fn get_recovery_statistics() -> error_recovery::RecoveryStatistics {
     error_recovery::get_recovery_statistics()
 }
 
+#[query]
+fn get_recovery_log(before: Option<u64>, limit: u64) -> Vec<RecoveryLogEntry> {
+    error_recovery::get_recovery_log(before, limit)
+}
+
+#[query]
+fn get_transaction_recovery_log(transaction_id: u64) -> Vec<RecoveryLogEntry> {
+    error_recovery::get_transaction_recovery_log(transaction_id)
+}
+
 #[query]
 fn get_user_points(user: Option<Principal>) -> u64 {
     let user_principal = user.unwrap_or_else(|| msg_caller());
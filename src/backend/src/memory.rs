@@ -21,6 +21,21 @@ pub const POINTS_STORAGE_MEMORY_ID: MemoryId = MemoryId::new(12);
 pub const WEEKLY_POINTS_STORAGE_MEMORY_ID: MemoryId = MemoryId::new(13);
 pub const USED_NONCES_MEMORY_ID: MemoryId = MemoryId::new(14);
 pub const NAV_CACHE_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const TRANSACTION_TRANSITIONS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const TRANSACTION_EVENTS_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const SETTLEMENT_STEPS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const RECOVERY_LOG_MEMORY_ID: MemoryId = MemoryId::new(19);
+pub const ROLE_ASSIGNMENTS_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub const PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub const ADMIN_AUDIT_LOG_MEMORY_ID: MemoryId = MemoryId::new(22);
+pub const SCHEMA_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(23);
+pub const ASSET_PRICE_POLICIES_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub const REFERENCE_PRICES_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub const PRICE_HALTS_MEMORY_ID: MemoryId = MemoryId::new(26);
+pub const PRICE_OBSERVATIONS_MEMORY_ID: MemoryId = MemoryId::new(27);
+pub const PRICE_CANDLES_MEMORY_ID: MemoryId = MemoryId::new(28);
+pub const NAV_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(29);
+pub const RECOVERY_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(30);
 
 thread_local! {
     pub static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
@@ -63,6 +78,98 @@ thread_local! {
         )
     );
 
+    pub static TRANSACTION_TRANSITIONS: RefCell<StableBTreeMap<(u64, u32), TransactionTransition, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(TRANSACTION_TRANSITIONS_MEMORY_ID))
+        )
+    );
+
+    pub static TRANSACTION_EVENTS: RefCell<StableBTreeMap<(u64, u32), TransactionEvent, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(TRANSACTION_EVENTS_MEMORY_ID))
+        )
+    );
+
+    pub static SETTLEMENT_STEPS: RefCell<StableBTreeMap<String, SettlementStep, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SETTLEMENT_STEPS_MEMORY_ID))
+        )
+    );
+
+    pub static RECOVERY_LOG: RefCell<StableBTreeMap<u64, RecoveryLogEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECOVERY_LOG_MEMORY_ID))
+        )
+    );
+
+    pub static ROLE_ASSIGNMENTS: RefCell<StableBTreeMap<Principal, RoleAssignment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ROLE_ASSIGNMENTS_MEMORY_ID))
+        )
+    );
+
+    pub static PROPOSALS: RefCell<StableBTreeMap<u64, Proposal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PROPOSALS_MEMORY_ID))
+        )
+    );
+
+    pub static ADMIN_AUDIT_LOG: RefCell<StableBTreeMap<u64, AdminAction, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ADMIN_AUDIT_LOG_MEMORY_ID))
+        )
+    );
+
+    pub static SCHEMA_VERSIONS: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SCHEMA_VERSIONS_MEMORY_ID))
+        )
+    );
+
+    pub static ASSET_PRICE_POLICIES: RefCell<StableBTreeMap<AssetId, AssetPricePolicy, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ASSET_PRICE_POLICIES_MEMORY_ID))
+        )
+    );
+
+    pub static REFERENCE_PRICES: RefCell<StableBTreeMap<AssetId, ReferencePrice, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(REFERENCE_PRICES_MEMORY_ID))
+        )
+    );
+
+    pub static PRICE_HALTS: RefCell<StableBTreeMap<AssetId, PriceHalt, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRICE_HALTS_MEMORY_ID))
+        )
+    );
+
+    // Price in USD (8 decimals) of each accepted observation
+    pub static PRICE_OBSERVATIONS: RefCell<StableBTreeMap<PriceObservationKey, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRICE_OBSERVATIONS_MEMORY_ID))
+        )
+    );
+
+    pub static PRICE_CANDLES: RefCell<StableBTreeMap<PriceCandleKey, PriceCandle, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRICE_CANDLES_MEMORY_ID))
+        )
+    );
+
+    pub static NAV_HISTORY: RefCell<StableBTreeMap<NavPointKey, BundleNAV, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(NAV_HISTORY_MEMORY_ID))
+        )
+    );
+
+    // (transaction_id, entry_id) for each recovery log entry
+    pub static RECOVERY_LOG_BY_TRANSACTION: RefCell<StableBTreeMap<(u64, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECOVERY_LOG_INDEX_MEMORY_ID))
+        )
+    );
+
     pub static LOCKED_FUNDS: RefCell<StableBTreeMap<String, LockedFunds, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(LOCKED_FUNDS_MEMORY_ID))
@@ -97,6 +204,11 @@ thread_local! {
         std::collections::HashMap::new()
     );
 
+    // Refilled by the heartbeat, so listing bundles never recomputes analytics
+    pub static ANALYTICS_CACHE: RefCell<std::collections::HashMap<u64, BundleAnalytics>> = RefCell::new(
+        std::collections::HashMap::new()
+    );
+
     pub static GLOBAL_STATE: RefCell<StableCell<GlobalState, Memory>> = RefCell::new(
         StableCell::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(GLOBAL_STATE_MEMORY_ID)),
@@ -159,6 +271,30 @@ pub fn set_oracle_config(config: OracleConfig) {
     })
 }
 
+pub fn get_price_aggregation_config() -> Option<PriceAggregationConfig> {
+    GLOBAL_STATE.with(|state| state.borrow().get().price_aggregation.clone())
+}
+
+pub fn set_price_aggregation_config(config: PriceAggregationConfig) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.price_aggregation = Some(config);
+        state.borrow_mut().set(s).expect("Failed to set price aggregation config");
+    })
+}
+
+pub fn get_analytics_config() -> Option<AnalyticsConfig> {
+    GLOBAL_STATE.with(|state| state.borrow().get().analytics_config.clone())
+}
+
+pub fn set_analytics_config(config: AnalyticsConfig) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.analytics_config = Some(config);
+        state.borrow_mut().set(s).expect("Failed to set analytics config");
+    })
+}
+
 pub fn get_icrc151_ledger() -> Option<Principal> {
     GLOBAL_STATE.with(|state| state.borrow().get().icrc151_ledger)
 }
@@ -195,6 +331,66 @@ pub fn set_quote_service_principal(principal: Principal) {
     })
 }
 
+pub fn get_pause_state() -> PauseState {
+    GLOBAL_STATE.with(|state| state.borrow().get().pause_state.clone().unwrap_or_default())
+}
+
+pub fn set_pause_state(pause_state: PauseState) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.pause_state = Some(pause_state);
+        state.borrow_mut().set(s).expect("Failed to set pause state");
+    })
+}
+
+pub fn get_pending_admin() -> Option<Nomination> {
+    GLOBAL_STATE.with(|state| state.borrow().get().pending_admin.clone())
+}
+
+pub fn set_pending_admin(nomination: Option<Nomination>) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.pending_admin = nomination;
+        state.borrow_mut().set(s).expect("Failed to set pending admin");
+    })
+}
+
+pub fn get_pending_treasury() -> Option<Nomination> {
+    GLOBAL_STATE.with(|state| state.borrow().get().pending_treasury.clone())
+}
+
+pub fn set_pending_treasury(nomination: Option<Nomination>) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.pending_treasury = nomination;
+        state.borrow_mut().set(s).expect("Failed to set pending treasury");
+    })
+}
+
+pub fn get_governance_config() -> GovernanceConfig {
+    GLOBAL_STATE.with(|state| state.borrow().get().governance_config.clone().unwrap_or_default())
+}
+
+pub fn get_recovery_counters() -> RecoveryCounters {
+    GLOBAL_STATE.with(|state| state.borrow().get().recovery_counters.clone().unwrap_or_default())
+}
+
+pub fn set_recovery_counters(counters: RecoveryCounters) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.recovery_counters = Some(counters);
+        state.borrow_mut().set(s).expect("Failed to set recovery counters");
+    })
+}
+
+pub fn set_governance_config(config: GovernanceConfig) {
+    GLOBAL_STATE.with(|state| {
+        let mut s = state.borrow().get().clone();
+        s.governance_config = Some(config);
+        state.borrow_mut().set(s).expect("Failed to set governance config");
+    })
+}
+
 pub fn get_current_week() -> u64 {
     let now = ic_cdk::api::time();
     let seconds = now / 1_000_000_000;
@@ -326,6 +522,14 @@ pub fn get_cached_holder_count(bundle_id: u64) -> Option<u64> {
     })
 }
 
+pub fn get_cached_analytics(bundle_id: u64) -> Option<BundleAnalytics> {
+    ANALYTICS_CACHE.with(|cache| cache.borrow().get(&bundle_id).cloned())
+}
+
+pub fn cache_analytics(analytics: BundleAnalytics) {
+    ANALYTICS_CACHE.with(|cache| cache.borrow_mut().insert(analytics.bundle_id, analytics));
+}
+
 pub fn cache_holder_count(bundle_id: u64, holder_count: u64) {
     let now = ic_cdk::api::time() / 1_000_000_000;
     NAV_CACHE.with(|cache| {
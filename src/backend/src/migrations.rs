@@ -0,0 +1,416 @@
+use candid::{decode_one, CandidType, Principal};
+use ic_cdk_macros::*;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{Memory as StableMemory, StableBTreeMap, StableCell, Storable};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use crate::types::*;
+use crate::memory::*;
+use crate::resolver_manager::ResolverInfo;
+
+// A stored value read as plain bytes, so an old schema can't trap in `from_bytes`.
+// Bounds mirror `T` so the map layout matches the typed map over the same memory.
+pub struct RawRecord<T> {
+    pub bytes: Vec<u8>,
+    _record: PhantomData<T>,
+}
+
+impl<T> RawRecord<T> {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes, _record: PhantomData }
+    }
+}
+
+impl<T: Storable> Storable for RawRecord<T> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.bytes)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self::new(bytes.into_owned())
+    }
+
+    const BOUND: Bound = T::BOUND;
+}
+
+pub struct Region {
+    pub name: &'static str,
+    pub verify: fn() -> Result<u64, String>,
+}
+
+// Moves one region from `from_version` to `from_version + 1`
+pub struct Migration {
+    pub region: &'static str,
+    pub from_version: u32,
+    pub description: &'static str,
+    pub run: fn(dry_run: bool) -> Result<u64, String>,
+}
+
+pub const GLOBAL_STATE_REGION: &str = "global_state";
+pub const LOCKED_FUNDS_REGION: &str = "locked_funds";
+pub const RECOVERY_LOG_INDEX_REGION: &str = "recovery_log_index";
+
+pub const REGIONS: &[Region] = &[
+    Region { name: GLOBAL_STATE_REGION, verify: || verify_cell::<GlobalState>(GLOBAL_STATE_MEMORY_ID) },
+    Region { name: "assets", verify: || verify_map::<AssetId, AssetInfo>(ASSET_REGISTRY_MEMORY_ID) },
+    Region { name: "bundles", verify: || verify_map::<u64, BundleConfig>(BUNDLE_STORAGE_MEMORY_ID) },
+    Region { name: "prices", verify: || verify_map::<AssetId, AssetPrice>(PRICE_STORAGE_MEMORY_ID) },
+    Region { name: "bundle_holdings", verify: || verify_map::<String, BundleHolding>(BUNDLE_HOLDINGS_MEMORY_ID) },
+    Region { name: "quote_assignments", verify: || verify_map::<u64, QuoteAssignment>(QUOTE_ASSIGNMENT_MEMORY_ID) },
+    Region { name: "transactions", verify: || verify_map::<u64, Transaction>(TRANSACTION_STORAGE_MEMORY_ID) },
+    Region {
+        name: "transaction_transitions",
+        verify: || verify_map::<(u64, u32), TransactionTransition>(TRANSACTION_TRANSITIONS_MEMORY_ID),
+    },
+    Region {
+        name: "transaction_events",
+        verify: || verify_map::<(u64, u32), TransactionEvent>(TRANSACTION_EVENTS_MEMORY_ID),
+    },
+    Region { name: "settlement_steps", verify: || verify_map::<String, SettlementStep>(SETTLEMENT_STEPS_MEMORY_ID) },
+    Region { name: LOCKED_FUNDS_REGION, verify: || verify_map::<String, LockedFunds>(LOCKED_FUNDS_MEMORY_ID) },
+    Region { name: "resolvers", verify: || verify_map::<Principal, ResolverInfo>(RESOLVER_REGISTRY_MEMORY_ID) },
+    Region { name: "recovery_log", verify: || verify_map::<u64, RecoveryLogEntry>(RECOVERY_LOG_MEMORY_ID) },
+    Region { name: "role_assignments", verify: || verify_map::<Principal, RoleAssignment>(ROLE_ASSIGNMENTS_MEMORY_ID) },
+    Region { name: "proposals", verify: || verify_map::<u64, Proposal>(PROPOSALS_MEMORY_ID) },
+    Region { name: "admin_audit_log", verify: || verify_map::<u64, AdminAction>(ADMIN_AUDIT_LOG_MEMORY_ID) },
+    Region {
+        name: "asset_price_policies",
+        verify: || verify_map::<AssetId, AssetPricePolicy>(ASSET_PRICE_POLICIES_MEMORY_ID),
+    },
+    Region { name: "reference_prices", verify: || verify_map::<AssetId, ReferencePrice>(REFERENCE_PRICES_MEMORY_ID) },
+    Region { name: "price_halts", verify: || verify_map::<AssetId, PriceHalt>(PRICE_HALTS_MEMORY_ID) },
+    Region { name: "price_observations", verify: || verify_map::<PriceObservationKey, u64>(PRICE_OBSERVATIONS_MEMORY_ID) },
+    Region { name: "price_candles", verify: || verify_map::<PriceCandleKey, PriceCandle>(PRICE_CANDLES_MEMORY_ID) },
+    Region { name: "nav_history", verify: || verify_map::<NavPointKey, BundleNAV>(NAV_HISTORY_MEMORY_ID) },
+    Region {
+        name: RECOVERY_LOG_INDEX_REGION,
+        verify: || verify_map::<(u64, u64), ()>(RECOVERY_LOG_INDEX_MEMORY_ID),
+    },
+];
+
+// Append new steps at the end; each region's steps must chain from version 1 upwards
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        region: GLOBAL_STATE_REGION,
+        from_version: 1,
+        description: "Stamp GlobalState.version and store it without the 1024-byte bound",
+        run: migrate_global_state_v2,
+    },
+    Migration {
+        region: GLOBAL_STATE_REGION,
+        from_version: 2,
+        description: "Seed the configurable ckUSDC ledger with the mainnet ledger",
+        run: migrate_global_state_v3,
+    },
+    Migration {
+        region: LOCKED_FUNDS_REGION,
+        from_version: 1,
+        description: "Replace the CkUSDC fund type with Payment { asset_id }",
+        run: migrate_locked_funds_v2,
+    },
+    Migration {
+        region: RECOVERY_LOG_INDEX_REGION,
+        from_version: 1,
+        description: "Index the recovery log by transaction and tally per-transaction recovery statistics",
+        run: migrate_recovery_log_index_v2,
+    },
+];
+
+pub fn latest_version(migrations: &[Migration], region: &str) -> u32 {
+    migrations.iter()
+        .filter(|m| m.region == region)
+        .map(|m| m.from_version + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+// Checks the step list and returns the steps still to run, in order
+pub fn plan_migrations<'a, F>(migrations: &'a [Migration], regions: &[&str], current: F) -> Result<Vec<&'a Migration>, String>
+where
+    F: Fn(&str) -> u32,
+{
+    let mut expected: HashMap<&str, u32> = HashMap::new();
+
+    for migration in migrations {
+        if !regions.contains(&migration.region) {
+            return Err(format!("Migration targets unknown region {}", migration.region));
+        }
+
+        let next = expected.entry(migration.region).or_insert(1);
+        if migration.from_version != *next {
+            return Err(format!(
+                "Migration for {} from v{} is out of order; expected v{}",
+                migration.region, migration.from_version, next
+            ));
+        }
+        *next += 1;
+    }
+
+    for region in regions {
+        let stored = current(region);
+        let latest = latest_version(migrations, region);
+        if stored > latest {
+            return Err(format!(
+                "Region {} is at v{} but this build only knows up to v{}; refusing to downgrade",
+                region, stored, latest
+            ));
+        }
+    }
+
+    Ok(migrations.iter()
+        .filter(|m| m.from_version >= current(m.region))
+        .collect())
+}
+
+// Decodes every record with the old schema before writing anything, so a bad record aborts the step
+pub fn migrate_map<K, Old, New, M>(
+    memory: M,
+    convert: fn(Old) -> Result<New, String>,
+    dry_run: bool,
+) -> Result<u64, String>
+where
+    K: Storable + Ord + Clone + Debug,
+    Old: CandidType + DeserializeOwned,
+    New: Storable,
+    M: StableMemory,
+{
+    let mut map: StableBTreeMap<K, RawRecord<New>, M> = StableBTreeMap::init(memory);
+
+    let mut converted = Vec::new();
+    for (key, record) in map.iter() {
+        let old: Old = decode_one(&record.bytes)
+            .map_err(|e| format!("Failed to decode record {:?}: {}", key, e))?;
+        let new = convert(old)
+            .map_err(|e| format!("Failed to migrate record {:?}: {}", key, e))?;
+        converted.push((key, new.to_bytes().into_owned()));
+    }
+
+    let records = converted.len() as u64;
+    if !dry_run {
+        for (key, bytes) in converted {
+            map.insert(key, RawRecord::new(bytes));
+        }
+    }
+
+    Ok(records)
+}
+
+pub fn migrate_cell<Old, New, M>(
+    memory: M,
+    convert: fn(Old) -> Result<New, String>,
+    dry_run: bool,
+) -> Result<u64, String>
+where
+    Old: CandidType + DeserializeOwned,
+    New: Storable,
+    M: StableMemory,
+{
+    if memory.size() == 0 {
+        return Ok(0);
+    }
+
+    let mut cell = StableCell::init(memory, RawRecord::<New>::new(Vec::new()))
+        .map_err(|e| format!("Failed to open cell: {:?}", e))?;
+
+    let old: Old = decode_one(&cell.get().bytes)
+        .map_err(|e| format!("Failed to decode cell: {}", e))?;
+    let new = convert(old)?;
+
+    if !dry_run {
+        cell.set(RawRecord::new(new.to_bytes().into_owned()))
+            .map_err(|e| format!("Failed to write cell: {:?}", e))?;
+    }
+
+    Ok(1)
+}
+
+fn region_memory(memory_id: MemoryId) -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(memory_id))
+}
+
+fn verify_map<K, T>(memory_id: MemoryId) -> Result<u64, String>
+where
+    K: Storable + Ord + Clone + Debug,
+    T: CandidType + DeserializeOwned + Storable,
+{
+    migrate_map::<K, T, T, _>(region_memory(memory_id), Ok, true)
+}
+
+fn verify_cell<T>(memory_id: MemoryId) -> Result<u64, String>
+where
+    T: CandidType + DeserializeOwned + Storable,
+{
+    migrate_cell::<T, T, _>(region_memory(memory_id), Ok, true)
+}
+
+pub fn global_state_v2(mut state: GlobalState) -> GlobalState {
+    state.version = 2;
+    state
+}
+
+fn migrate_global_state_v2(dry_run: bool) -> Result<u64, String> {
+    migrate_cell::<GlobalState, GlobalState, _>(
+        region_memory(GLOBAL_STATE_MEMORY_ID),
+        |state| Ok(global_state_v2(state)),
+        dry_run,
+    )
+}
+
+pub fn global_state_v3(mut state: GlobalState) -> GlobalState {
+    if state.icrc2_ckusdc_ledger.is_none() {
+        state.icrc2_ckusdc_ledger = Principal::from_text(crate::icrc2_client::MAINNET_CKUSDC_LEDGER_CANISTER).ok();
+    }
+    state.version = 3;
+    state
+}
+
+fn migrate_global_state_v3(dry_run: bool) -> Result<u64, String> {
+    migrate_cell::<GlobalState, GlobalState, _>(
+        region_memory(GLOBAL_STATE_MEMORY_ID),
+        |state| Ok(global_state_v3(state)),
+        dry_run,
+    )
+}
+
+// LockedFunds as stored before payment assets were configurable
+#[derive(CandidType, Deserialize)]
+pub enum LegacyLockedFundType {
+    CkUSDC,
+    NAVTokens { bundle_id: u64 },
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct LegacyLockedFunds {
+    pub user: Principal,
+    pub transaction_id: u64,
+    pub fund_type: LegacyLockedFundType,
+    pub amount: u64,
+    pub locked_at: u64,
+    pub expires_at: u64,
+}
+
+pub fn locked_funds_v2(locked: LegacyLockedFunds) -> LockedFunds {
+    let fund_type = match locked.fund_type {
+        LegacyLockedFundType::CkUSDC => LockedFundType::payment(crate::payments::DEFAULT_PAYMENT_ASSET),
+        LegacyLockedFundType::NAVTokens { bundle_id } => LockedFundType::NAVTokens { bundle_id },
+    };
+
+    LockedFunds {
+        user: locked.user,
+        transaction_id: locked.transaction_id,
+        fund_type,
+        amount: locked.amount,
+        locked_at: locked.locked_at,
+        expires_at: locked.expires_at,
+    }
+}
+
+fn migrate_locked_funds_v2(dry_run: bool) -> Result<u64, String> {
+    migrate_map::<String, LegacyLockedFunds, LockedFunds, _>(
+        region_memory(LOCKED_FUNDS_MEMORY_ID),
+        |locked| Ok(locked_funds_v2(locked)),
+        dry_run,
+    )
+}
+
+fn migrate_recovery_log_index_v2(dry_run: bool) -> Result<u64, String> {
+    let entries: Vec<RecoveryLogEntry> = RECOVERY_LOG.with(|log| {
+        log.borrow().iter().map(|(_, entry)| entry).collect()
+    });
+
+    if !dry_run {
+        RECOVERY_LOG_BY_TRANSACTION.with(|index| {
+            let mut index = index.borrow_mut();
+            for entry in &entries {
+                index.insert((entry.transaction_id, entry.id), ());
+            }
+        });
+        set_recovery_counters(crate::error_recovery::tally_recovery_log(&entries));
+    }
+
+    Ok(entries.len() as u64)
+}
+
+fn stored_version(region: &str) -> u32 {
+    SCHEMA_VERSIONS.with(|versions| versions.borrow().get(&region.to_string())).unwrap_or(1)
+}
+
+fn set_stored_version(region: &str, version: u32) {
+    SCHEMA_VERSIONS.with(|versions| {
+        versions.borrow_mut().insert(region.to_string(), version);
+    });
+}
+
+fn region_names() -> Vec<&'static str> {
+    REGIONS.iter().map(|region| region.name).collect()
+}
+
+// Fresh installs start at the latest schema
+pub fn stamp_latest_versions() {
+    for region in REGIONS {
+        set_stored_version(region.name, latest_version(MIGRATIONS, region.name));
+    }
+}
+
+// Runs from post_upgrade before any typed map is opened; an error must trap to roll the upgrade back
+pub fn run_migrations(dry_run: bool) -> Result<MigrationReport, String> {
+    let pending = plan_migrations(MIGRATIONS, &region_names(), stored_version)?;
+
+    let mut steps = Vec::new();
+    for migration in &pending {
+        let to_version = migration.from_version + 1;
+        let records = (migration.run)(dry_run).map_err(|e| {
+            format!("Migration {} v{} -> v{} failed: {}", migration.region, migration.from_version, to_version, e)
+        })?;
+
+        if !dry_run {
+            set_stored_version(migration.region, to_version);
+        }
+
+        steps.push(MigrationStep {
+            region: migration.region.to_string(),
+            from_version: migration.from_version,
+            to_version,
+            description: migration.description.to_string(),
+            records,
+        });
+    }
+
+    // Regions with pending steps were already decoded with their old schema above
+    let checks = if dry_run {
+        REGIONS.iter()
+            .filter(|region| !pending.iter().any(|m| m.region == region.name))
+            .map(|region| match (region.verify)() {
+                Ok(records) => RegionCheck { region: region.name.to_string(), records, error: None },
+                Err(error) => RegionCheck { region: region.name.to_string(), records: 0, error: Some(error) },
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(MigrationReport { dry_run, steps, checks })
+}
+
+#[query]
+pub fn get_schema_versions() -> Vec<SchemaVersion> {
+    REGIONS.iter()
+        .map(|region| SchemaVersion {
+            region: region.name.to_string(),
+            version: stored_version(region.name),
+            latest_version: latest_version(MIGRATIONS, region.name),
+        })
+        .collect()
+}
+
+// Runs every pending step and decodes every stored record without writing anything
+#[query]
+pub fn dry_run_migrations() -> Result<MigrationReport, String> {
+    crate::roles::require_role(Role::Operator)?;
+    run_migrations(true)
+}
@@ -6,6 +6,12 @@ use crate::nav_token::get_total_tokens_for_bundle;
 
 pub async fn calculate_bundle_nav(bundle_id: u64) -> Result<BundleNAV, String> {
     let bundle = get_bundle(bundle_id)?;
+    calculate_bundle_nav_with_mode(bundle_id, bundle.valuation_mode.unwrap_or(ValuationMode::Spot)).await
+}
+
+// Spot prices are still fetched under TWAP so the circuit breakers and price history stay current
+pub async fn calculate_bundle_nav_with_mode(bundle_id: u64, valuation_mode: ValuationMode) -> Result<BundleNAV, String> {
+    let bundle = get_bundle(bundle_id)?;
 
     if !bundle.is_active {
         return Err("Bundle is not active".to_string());
@@ -21,11 +27,13 @@ pub async fn calculate_bundle_nav(bundle_id: u64) -> Result<BundleNAV, String> {
             total_tokens: 0,
             asset_values: Vec::new(),
             calculated_at: time(),
+            fallback_assets: Vec::new(),
         });
     }
 
     let mut asset_values = Vec::new();
     let mut total_value_usd = 0u64;
+    let mut fallback_assets = Vec::new();
 
     let holdings = crate::holdings_tracker::get_all_bundle_holdings(bundle_id);
 
@@ -42,9 +50,19 @@ pub async fn calculate_bundle_nav(bundle_id: u64) -> Result<BundleNAV, String> {
             .ok_or(format!("Price not found for asset {}", holding.asset_id))?;
         let asset_info = crate::asset_registry::get_asset(holding.asset_id.clone())?;
 
+        let price_usd = match &valuation_mode {
+            ValuationMode::Spot => {
+                if crate::oracle::is_substitute_price(asset_price) {
+                    fallback_assets.push(holding.asset_id.clone());
+                }
+                asset_price.price_usd
+            }
+            ValuationMode::Twap { window_ns } => crate::price_history::twap_price(&holding.asset_id, *window_ns)?,
+        };
+
         let holding_value_usd = calculate_holding_value_usd(
             holding.amount,
-            asset_price.price_usd,
+            price_usd,
             asset_info.decimals,
         )?;
 
@@ -78,6 +96,7 @@ pub async fn calculate_bundle_nav(bundle_id: u64) -> Result<BundleNAV, String> {
         total_tokens: total_nav_tokens,
         asset_values,
         calculated_at: time(),
+        fallback_assets,
     })
 }
 
@@ -344,25 +363,116 @@ pub async fn calculate_multiple_bundle_navs(bundle_ids: Vec<u64>) -> Vec<(u64, R
     results
 }
 
-pub async fn get_portfolio_value(user_principal: candid::Principal) -> Result<u64, String> {
-    let nav_tokens = crate::nav_token::get_user_nav_tokens(user_principal);
-    let mut total_value = 0u64;
+// Oracle prices, and so NAVs and portfolio values, are 8-decimal USD
+const USD_VALUE_DECIMALS: u8 = 8;
 
-    for nav_token in nav_tokens {
-        match calculate_bundle_nav(nav_token.bundle_id).await {
-            Ok(bundle_nav) => {
-                let user_value = nav_token.amount.checked_mul(bundle_nav.nav_per_token)
-                    .ok_or("User portfolio value overflow")?;
-                total_value = total_value.checked_add(user_value)
-                    .ok_or("Total portfolio value overflow")?;
+pub fn nav_token_value_usd(amount: u64, nav_per_token: u64) -> Result<u64, String> {
+    let value = amount as u128 * nav_per_token as u128 / 10u128.pow(crate::nav_token::NAV_TOKEN_DECIMALS as u32);
+    u64::try_from(value).map_err(|_| "Position value overflow".to_string())
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CostBasis {
+    pub nav_tokens: u64,
+    pub cost_usd: u64,
+}
+
+// Average cost per bundle from completed buys and sells; a sell releases cost in proportion to the tokens sold
+pub fn cost_basis_by_bundle(transactions: &[Transaction]) -> std::collections::BTreeMap<u64, CostBasis> {
+    let mut completed: Vec<&Transaction> = transactions.iter()
+        .filter(|t| t.status == TransactionStatus::Completed)
+        .collect();
+    completed.sort_by_key(|t| (t.completed_at.unwrap_or(t.updated_at), t.id));
+
+    let mut basis: std::collections::BTreeMap<u64, CostBasis> = std::collections::BTreeMap::new();
+    for transaction in completed {
+        let position = basis.entry(transaction.bundle_id).or_default();
+        match transaction.operation {
+            OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
+                let paid_e6 = crate::payments::usd_value_e6(&transaction.payment_asset, transaction.ckusdc_amount);
+                let paid = crate::payments::normalize_amount(paid_e6, crate::payments::USD_DECIMALS, USD_VALUE_DECIMALS);
+                position.nav_tokens = position.nav_tokens.saturating_add(transaction.nav_tokens);
+                position.cost_usd = position.cost_usd.saturating_add(paid);
             }
-            Err(_) => {
-                continue;
+            OperationType::Sell { .. } => {
+                let sold = transaction.nav_tokens.min(position.nav_tokens);
+                if sold > 0 {
+                    let released = position.cost_usd as u128 * sold as u128 / position.nav_tokens as u128;
+                    position.cost_usd -= released as u64;
+                    position.nav_tokens -= sold;
+                }
             }
         }
     }
+    basis
+}
 
-    Ok(total_value)
+// Cost basis is only known when the tracked buys cover the whole balance; tokens received by transfer have none
+pub fn value_position(
+    bundle: &BundleConfig,
+    balance: u64,
+    nav_per_token: Option<u64>,
+    basis: Option<&CostBasis>,
+) -> Result<PortfolioPosition, String> {
+    let value_usd = nav_per_token.map(|nav| nav_token_value_usd(balance, nav)).transpose()?;
+    let cost_basis_usd = basis
+        .filter(|b| b.nav_tokens >= balance && b.nav_tokens > 0)
+        .map(|b| (b.cost_usd as u128 * balance as u128 / b.nav_tokens as u128) as u64);
+    let unrealized_pnl_usd = match (value_usd, cost_basis_usd) {
+        (Some(value), Some(cost)) => Some(value as i64 - cost as i64),
+        _ => None,
+    };
+
+    Ok(PortfolioPosition {
+        bundle_id: bundle.id,
+        bundle_name: bundle.name.clone(),
+        symbol: bundle.symbol.clone(),
+        balance,
+        nav_per_token,
+        value_usd,
+        cost_basis_usd,
+        unrealized_pnl_usd,
+    })
+}
+
+async fn current_nav_per_token(bundle_id: u64) -> Option<u64> {
+    match crate::memory::get_cached_nav(bundle_id) {
+        Some((nav, _)) => Some(nav),
+        None => calculate_bundle_nav(bundle_id).await.ok().map(|nav| nav.nav_per_token),
+    }
+}
+
+pub async fn get_portfolio_valuation(user_principal: candid::Principal) -> Result<PortfolioValuation, String> {
+    let nav_tokens = crate::nav_token::fetch_user_nav_tokens(user_principal).await?;
+    let basis = cost_basis_by_bundle(&crate::transaction_manager::get_user_transactions(user_principal));
+
+    let mut positions = Vec::new();
+    for nav_token in nav_tokens {
+        let bundle = get_bundle(nav_token.bundle_id)?;
+        let nav_per_token = current_nav_per_token(bundle.id).await;
+        positions.push(value_position(&bundle, nav_token.amount, nav_per_token, basis.get(&bundle.id))?);
+    }
+
+    let total_value_usd = positions.iter()
+        .filter_map(|p| p.value_usd)
+        .try_fold(0u64, |total, value| total.checked_add(value))
+        .ok_or("Total portfolio value overflow")?;
+    let total_cost_basis_usd = positions.iter()
+        .try_fold(0u64, |total, p| p.cost_basis_usd.map(|cost| total.saturating_add(cost)));
+    let fully_valued = positions.iter().all(|p| p.value_usd.is_some());
+
+    Ok(PortfolioValuation {
+        user: user_principal,
+        positions,
+        total_value_usd,
+        total_cost_basis_usd,
+        fully_valued,
+        valued_at: time(),
+    })
+}
+
+pub async fn get_portfolio_value(user_principal: candid::Principal) -> Result<u64, String> {
+    Ok(get_portfolio_valuation(user_principal).await?.total_value_usd)
 }
 
 pub fn validate_bundle_allocations(allocations: &[AssetAllocation]) -> Result<(), String> {
@@ -0,0 +1,127 @@
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::memory::*;
+
+const MAX_NAV_POINTS_PAGE: usize = 1_000;
+
+// 1m points for a day, 1h for 90 days, 1d for five years
+pub fn retention_ns(interval: CandleInterval) -> u64 {
+    match interval {
+        CandleInterval::Minute => 1_440 * interval.duration_ns(),
+        CandleInterval::Hour => 2_160 * interval.duration_ns(),
+        CandleInterval::Day => 1_825 * interval.duration_ns(),
+    }
+}
+
+fn point_key(bundle_id: u64, interval: CandleInterval, open_time: u64) -> NavPointKey {
+    NavPointKey { bundle_id, interval, open_time }
+}
+
+// Each bucket keeps the latest NAV computed within it
+pub fn record(nav: &BundleNAV) {
+    // Nothing to value yet, and a zero NAV would break return calculations
+    if nav.total_tokens == 0 {
+        return;
+    }
+    // A substitute price would leave a point that no oracle ever reported
+    if !nav.fallback_assets.is_empty() {
+        return;
+    }
+
+    NAV_HISTORY.with(|h| {
+        let mut history = h.borrow_mut();
+        for interval in CandleInterval::ALL {
+            let open_time = interval.bucket_start(nav.calculated_at);
+            history.insert(point_key(nav.bundle_id, interval, open_time), nav.clone());
+
+            let cutoff = open_time.saturating_sub(retention_ns(interval));
+            let expired: Vec<NavPointKey> = history
+                .range(point_key(nav.bundle_id, interval, 0)..point_key(nav.bundle_id, interval, cutoff))
+                .map(|(key, _)| key)
+                .collect();
+            for key in expired {
+                history.remove(&key);
+            }
+        }
+    });
+}
+
+// Points whose bucket opens in [from, to], oldest first; page by passing the last bucket start + 1 as `from`
+pub fn nav_history(bundle_id: u64, interval: CandleInterval, from: u64, to: u64) -> Result<Vec<BundleNAV>, String> {
+    if from > to {
+        return Err("from must not be after to".to_string());
+    }
+    Ok(NAV_HISTORY.with(|h| {
+        h.borrow()
+            .range(point_key(bundle_id, interval, from)..=point_key(bundle_id, interval, to))
+            .take(MAX_NAV_POINTS_PAGE)
+            .map(|(_, nav)| nav)
+            .collect()
+    }))
+}
+
+// The latest point at or before `timestamp`, taking the finest resolution that still has one
+pub fn point_at(bundle_id: u64, timestamp: u64) -> Option<BundleNAV> {
+    NAV_HISTORY.with(|h| {
+        let history = h.borrow();
+        CandleInterval::ALL.iter()
+            .filter_map(|&interval| {
+                history
+                    .range(point_key(bundle_id, interval, 0)..=point_key(bundle_id, interval, interval.bucket_start(timestamp)))
+                    .rev()
+                    .map(|(_, nav)| nav)
+                    .find(|nav| nav.calculated_at <= timestamp)
+            })
+            .max_by_key(|nav| nav.calculated_at)
+    })
+}
+
+pub fn first_point(bundle_id: u64) -> Option<BundleNAV> {
+    NAV_HISTORY.with(|h| {
+        let history = h.borrow();
+        CandleInterval::ALL.iter()
+            .filter_map(|&interval| {
+                history
+                    .range(point_key(bundle_id, interval, 0)..=point_key(bundle_id, interval, u64::MAX))
+                    .next()
+                    .map(|(_, nav)| nav)
+            })
+            .min_by_key(|nav| nav.calculated_at)
+    })
+}
+
+pub fn return_bps(start_nav: u64, end_nav: u64) -> Option<i64> {
+    if start_nav == 0 {
+        return None;
+    }
+    let change = end_nav as i128 - start_nav as i128;
+    i64::try_from(change * 10_000 / start_nav as i128).ok()
+}
+
+// A `from` before the bundle's first point measures from that first point
+pub fn bundle_return(bundle_id: u64, from: u64, to: u64) -> Result<BundleReturn, String> {
+    if from > to {
+        return Err("from must not be after to".to_string());
+    }
+    let end = point_at(bundle_id, to)
+        .ok_or_else(|| format!("No NAV history for bundle {} at or before {}", bundle_id, to))?;
+    let start = match point_at(bundle_id, from) {
+        Some(start) => start,
+        None => first_point(bundle_id)
+            .ok_or_else(|| format!("No NAV history for bundle {}", bundle_id))?,
+    };
+    let return_bps = return_bps(start.nav_per_token, end.nav_per_token)
+        .ok_or("Starting NAV is zero")?;
+
+    Ok(BundleReturn { bundle_id, start, end, return_bps })
+}
+
+#[query]
+pub fn get_nav_history(bundle_id: u64, interval: CandleInterval, from: u64, to: u64) -> Result<Vec<BundleNAV>, String> {
+    nav_history(bundle_id, interval, from, to)
+}
+
+#[query]
+pub fn get_bundle_return(bundle_id: u64, from: u64, to: u64) -> Result<BundleReturn, String> {
+    bundle_return(bundle_id, from, to)
+}
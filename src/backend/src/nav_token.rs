@@ -1,6 +1,8 @@
 use candid::Principal;
 use crate::icrc151_client;
 
+pub const NAV_TOKEN_DECIMALS: u8 = 8;
+
 pub async fn get_user_nav_token_balance(user: Principal, bundle_id: u64) -> Result<u64, String> {
     let bundle = crate::bundle_manager::get_bundle(bundle_id)?;
     let (ledger, token_id) = bundle.get_token_location()?;
@@ -66,8 +68,32 @@ pub async fn get_bundle_holder_count(bundle_id: u64) -> Result<u32, String> {
     count.try_into().map_err(|_| "Holder count too large".to_string())
 }
 
-pub fn get_user_nav_tokens(_user: Principal) -> Vec<crate::types::XFusionNAVToken> {
-    vec![]
+// One get_balances_for call per ledger rather than one balance call per bundle
+pub async fn fetch_user_nav_tokens(user: Principal) -> Result<Vec<crate::types::XFusionNAVToken>, String> {
+    let mut bundles_by_ledger: std::collections::BTreeMap<Principal, Vec<(Vec<u8>, u64)>> = std::collections::BTreeMap::new();
+    for bundle in crate::bundle_manager::list_all_bundles() {
+        if let Ok((ledger, token_id)) = bundle.get_token_location() {
+            bundles_by_ledger.entry(ledger).or_default().push((token_id, bundle.id));
+        }
+    }
+
+    let now = ic_cdk::api::time();
+    let mut nav_tokens = Vec::new();
+    for (ledger, bundles) in bundles_by_ledger {
+        let balances = icrc151_client::get_balances_for_icrc151(ledger, user).await?;
+        for balance in balances {
+            let Some((_, bundle_id)) = bundles.iter().find(|(token_id, _)| *token_id == balance.token_id) else {
+                continue;
+            };
+            let amount: u64 = balance.balance.0.try_into().map_err(|_| "Balance too large".to_string())?;
+            if amount > 0 {
+                nav_tokens.push(crate::types::XFusionNAVToken { bundle_id: *bundle_id, owner: user, amount, last_updated: now });
+            }
+        }
+    }
+
+    nav_tokens.sort_by_key(|token| token.bundle_id);
+    Ok(nav_tokens)
 }
 
 fn nav_token_key(user: Principal, bundle_id: u64) -> String {
@@ -3,8 +3,9 @@ use ic_cdk_macros::*;
 use candid::{CandidType, Deserialize, Principal};
 use crate::types::*;
 use crate::memory::*;
-use crate::admin::require_admin;
+use crate::roles::require_role;
 
+// Used until an OracleConfig is set
 const XFUSION_ORACLE_CANISTER: &str = "zutfo-jqaaa-aaaao-a4puq-cai";
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
@@ -17,12 +18,206 @@ pub struct OraclePrice {
 
 const DEFAULT_CACHE_DURATION_NS: u64 = 60_000_000_000;
 const DEFAULT_MAX_STALENESS_NS: u64 = 300_000_000_000;
+const STABLECOIN_FALLBACK_PRICE: u64 = 1_00000000;
+pub const STABLECOIN_FALLBACK_SOURCE: &str = "stablecoin_fallback";
+
+pub fn validate_oracle_config(config: &OracleConfig) -> Result<(), String> {
+    if config.oracle_canister == Principal::anonymous() {
+        return Err("Oracle canister cannot be the anonymous principal".to_string());
+    }
+    if config.cache_duration_ns == 0 {
+        return Err("Cache duration must be positive".to_string());
+    }
+    if config.max_staleness_ns < config.cache_duration_ns {
+        return Err("Max staleness cannot be shorter than the cache duration".to_string());
+    }
+    validate_price_move_limit(config.max_price_move_bps)
+}
+
+fn validate_price_move_limit(max_price_move_bps: Option<u64>) -> Result<(), String> {
+    if max_price_move_bps == Some(0) {
+        return Err("Max price move must be positive".to_string());
+    }
+    Ok(())
+}
+
+pub fn validate_asset_price_policy(policy: &AssetPricePolicy) -> Result<(), String> {
+    if policy.cache_duration_ns == Some(0) || policy.max_staleness_ns == Some(0) {
+        return Err("Price policy durations must be positive".to_string());
+    }
+    if let (Some(cache), Some(staleness)) = (policy.cache_duration_ns, policy.max_staleness_ns) {
+        if staleness < cache {
+            return Err("Max staleness cannot be shorter than the cache duration".to_string());
+        }
+    }
+    validate_price_move_limit(policy.max_price_move_bps)
+}
+
+pub fn effective_policy(config: Option<&OracleConfig>, overrides: Option<&AssetPricePolicy>) -> PricePolicy {
+    let (cache_duration_ns, max_staleness_ns, fallback_enabled, max_price_move_bps) = match config {
+        Some(config) => (config.cache_duration_ns, config.max_staleness_ns, config.fallback_enabled, config.max_price_move_bps),
+        None => (DEFAULT_CACHE_DURATION_NS, DEFAULT_MAX_STALENESS_NS, true, None),
+    };
+
+    PricePolicy {
+        cache_duration_ns: overrides.and_then(|o| o.cache_duration_ns).unwrap_or(cache_duration_ns),
+        max_staleness_ns: overrides.and_then(|o| o.max_staleness_ns).unwrap_or(max_staleness_ns),
+        fallback_enabled,
+        max_price_move_bps: overrides.and_then(|o| o.max_price_move_bps).or(max_price_move_bps),
+        stablecoin_fallback: overrides.and_then(|o| o.stablecoin_fallback).unwrap_or(false),
+    }
+}
+
+pub fn price_move_bps(previous: u64, observed: u64) -> u64 {
+    if previous == 0 {
+        return u64::MAX;
+    }
+    (previous.abs_diff(observed) as u128 * 10_000 / previous as u128).min(u64::MAX as u128) as u64
+}
+
+// Returns the halt a new observation triggers, if any
+pub fn check_price_move(previous: Option<&AssetPrice>, observed: u64, policy: &PricePolicy) -> Option<PriceHaltReason> {
+    let previous_usd = previous?.price_usd;
+    let max_move = policy.max_price_move_bps?;
+    let move_bps = price_move_bps(previous_usd, observed);
+
+    (move_bps > max_move).then_some(PriceHaltReason::PriceMove { previous_usd, observed_usd: observed, move_bps })
+}
+
+pub fn stablecoin_fallback_price(ticker: &str, policy: &PricePolicy) -> Option<u64> {
+    if !policy.stablecoin_fallback {
+        return None;
+    }
+    match ticker {
+        "USDC" | "ckUSDC" => Some(STABLECOIN_FALLBACK_PRICE),
+        _ => None,
+    }
+}
+
+const DEFAULT_MAX_DEVIATION_BPS: u64 = 200;
+
+pub struct AggregatedPrice {
+    pub price_usd: u64,
+    pub source: String,
+    pub confidence: u8,
+}
+
+pub fn validate_price_aggregation_config(config: &PriceAggregationConfig) -> Result<(), String> {
+    if config.additional_oracles.contains(&Principal::anonymous()) {
+        return Err("Oracle canister cannot be the anonymous principal".to_string());
+    }
+    let mut oracles = config.additional_oracles.clone();
+    oracles.sort();
+    oracles.dedup();
+    if oracles.len() != config.additional_oracles.len() {
+        return Err("Oracle canisters must be unique".to_string());
+    }
+    // The primary oracle and a reference price can add two more sources
+    if config.min_sources == 0 || config.min_sources as usize > config.additional_oracles.len() + 2 {
+        return Err("Minimum source count must be between 1 and the number of available sources".to_string());
+    }
+    if config.max_deviation_bps == 0 || config.max_deviation_bps > 10_000 {
+        return Err("Max deviation must be between 1 and 10000 bps".to_string());
+    }
+    Ok(())
+}
+
+pub fn median(values: &[u64]) -> Option<u64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(sorted[mid]),
+        _ => Some(((sorted[mid - 1] as u128 + sorted[mid] as u128) / 2) as u64),
+    }
+}
+
+pub fn within_deviation(price: u64, center: u64, max_deviation_bps: u64) -> bool {
+    price.abs_diff(center) as u128 * 10_000 <= center as u128 * max_deviation_bps as u128
+}
+
+// Samples further than max_deviation_bps from the median are dropped; confidence is the share of queried sources that agree
+pub fn aggregate_prices(
+    samples: &[PriceSample],
+    queried: usize,
+    min_sources: u32,
+    max_deviation_bps: u64,
+) -> Result<AggregatedPrice, String> {
+    let prices: Vec<u64> = samples.iter().map(|sample| sample.price_usd).collect();
+    let center = median(&prices).ok_or("No price source returned a fresh price")?;
+
+    let agreeing: Vec<u64> = prices.into_iter()
+        .filter(|price| within_deviation(*price, center, max_deviation_bps))
+        .collect();
+    let queried = queried.max(samples.len());
+
+    if (agreeing.len() as u32) < min_sources {
+        return Err(format!(
+            "Only {} of {} price sources agree within {} bps; {} required",
+            agreeing.len(), queried, max_deviation_bps, min_sources
+        ));
+    }
+
+    Ok(AggregatedPrice {
+        price_usd: median(&agreeing).unwrap_or(center),
+        source: format!("median:{}/{}", agreeing.len(), queried),
+        confidence: (agreeing.len() * 100 / queried) as u8,
+    })
+}
+
+fn aggregation_config() -> PriceAggregationConfig {
+    crate::memory::get_price_aggregation_config().unwrap_or(PriceAggregationConfig {
+        additional_oracles: Vec::new(),
+        min_sources: 1,
+        max_deviation_bps: DEFAULT_MAX_DEVIATION_BPS,
+    })
+}
+
+fn oracle_sources() -> Result<Vec<Principal>, String> {
+    let mut oracles = vec![oracle_canister()?];
+    for oracle in aggregation_config().additional_oracles {
+        if !oracles.contains(&oracle) {
+            oracles.push(oracle);
+        }
+    }
+    Ok(oracles)
+}
+
+pub fn oracle_canister() -> Result<Principal, String> {
+    match crate::memory::get_oracle_config() {
+        Some(config) => Ok(config.oracle_canister),
+        None => Principal::from_text(XFUSION_ORACLE_CANISTER)
+            .map_err(|e| format!("Invalid oracle canister ID: {}", e)),
+    }
+}
+
+fn price_age_ns(timestamp: u64, now: u64) -> u64 {
+    now.saturating_sub(timestamp)
+}
+
+fn price_policy(asset_id: &AssetId) -> PricePolicy {
+    let overrides = ASSET_PRICE_POLICIES.with(|p| p.borrow().get(asset_id));
+    effective_policy(crate::memory::get_oracle_config().as_ref(), overrides.as_ref())
+}
 
 #[update]
 pub fn set_oracle_config(config: OracleConfig) -> Result<(), String> {
-    let _admin = require_admin()?;
+    let operator = require_role(Role::Operator)?;
+    validate_oracle_config(&config)?;
 
-    crate::memory::set_oracle_config(config);
+    let previous = crate::memory::get_oracle_config();
+    crate::memory::set_oracle_config(config.clone());
+
+    crate::audit::record(
+        operator,
+        "set_oracle_config",
+        "oracle",
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&config)),
+    );
 
     Ok(())
 }
@@ -32,6 +227,184 @@ pub fn get_oracle_config() -> Option<OracleConfig> {
     crate::memory::get_oracle_config()
 }
 
+#[update]
+pub fn set_price_aggregation_config(config: PriceAggregationConfig) -> Result<(), String> {
+    let operator = require_role(Role::Operator)?;
+    validate_price_aggregation_config(&config)?;
+
+    let previous = crate::memory::get_price_aggregation_config();
+    crate::memory::set_price_aggregation_config(config.clone());
+
+    crate::audit::record(
+        operator,
+        "set_price_aggregation_config",
+        "oracle",
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&config)),
+    );
+
+    Ok(())
+}
+
+#[query]
+pub fn get_price_aggregation_config() -> PriceAggregationConfig {
+    aggregation_config()
+}
+
+// A reference price counts as one more source and expires with the asset's max staleness
+#[update]
+pub fn set_reference_price(asset_id: AssetId, price_usd: Option<u64>) -> Result<(), String> {
+    let operator = require_role(Role::Operator)?;
+    crate::asset_registry::get_asset(asset_id.clone())?;
+    if price_usd == Some(0) {
+        return Err("Reference price must be positive".to_string());
+    }
+
+    let previous = REFERENCE_PRICES.with(|r| {
+        let mut references = r.borrow_mut();
+        match price_usd {
+            Some(price_usd) => references.insert(asset_id.clone(), ReferencePrice {
+                asset_id: asset_id.clone(),
+                price_usd,
+                timestamp: time(),
+                set_by: operator,
+            }),
+            None => references.remove(&asset_id),
+        }
+    });
+
+    crate::audit::record(
+        operator,
+        "set_reference_price",
+        format!("asset:{}", asset_id),
+        None,
+        previous.map(|p| p.price_usd.to_string()),
+        price_usd.map(|p| p.to_string()),
+    );
+
+    Ok(())
+}
+
+#[query]
+pub fn get_reference_price(asset_id: AssetId) -> Option<ReferencePrice> {
+    REFERENCE_PRICES.with(|r| r.borrow().get(&asset_id))
+}
+
+#[query]
+pub fn list_price_halts() -> Vec<PriceHalt> {
+    PRICE_HALTS.with(|h| h.borrow().iter().map(|(_, halt)| halt).collect())
+}
+
+// Lets trading resume on a halted asset; a price-move halt also accepts the observed price as the new baseline
+#[update]
+pub fn override_price_halt(asset_id: AssetId, reason: String) -> Result<(), String> {
+    let operator = require_role(Role::Operator)?;
+
+    let mut halt = PRICE_HALTS.with(|h| h.borrow().get(&asset_id))
+        .ok_or_else(|| format!("Asset {} is not price-halted", asset_id))?;
+    if halt.overridden_by.is_some() {
+        return Err(format!("Price halt on {} is already overridden", asset_id));
+    }
+
+    if let PriceHaltReason::PriceMove { observed_usd, .. } = halt.reason {
+        PRICE_STORAGE.with(|p| p.borrow_mut().insert(asset_id.clone(), AssetPrice {
+            asset_id: asset_id.clone(),
+            price_usd: observed_usd,
+            timestamp: halt.halted_at,
+            source: "operator_override".to_string(),
+            confidence: 0,
+        }));
+    }
+
+    halt.overridden_by = Some(operator);
+    PRICE_HALTS.with(|h| h.borrow_mut().insert(asset_id.clone(), halt.clone()));
+
+    crate::audit::record(
+        operator,
+        "override_price_halt",
+        format!("asset:{}", asset_id),
+        Some(reason),
+        Some(crate::audit::describe(&halt.reason)),
+        None,
+    );
+
+    Ok(())
+}
+
+pub fn trading_block(halt: Option<&PriceHalt>, cached: Option<&AssetPrice>, policy: &PricePolicy, now: u64) -> Option<String> {
+    if let Some(halt) = halt.filter(|halt| halt.overridden_by.is_none()) {
+        return Some(format!("price-halted since {} ({:?})", halt.halted_at, halt.reason));
+    }
+
+    // An override never lets a bundle trade on a missing or stale price
+    match cached {
+        Some(price) if price_age_ns(price.timestamp, now) <= policy.max_staleness_ns => None,
+        Some(price) => Some(format!("price is {} seconds old", price_age_ns(price.timestamp, now) / 1_000_000_000)),
+        None => Some("no price observed".to_string()),
+    }
+}
+
+// Refreshes the prices first, so a recovered feed lifts its halt before the check
+pub async fn ensure_assets_tradeable(asset_ids: &[AssetId]) -> Result<(), String> {
+    let _ = get_multiple_prices(asset_ids).await;
+
+    let now = time();
+    let blocked: Vec<String> = asset_ids.iter()
+        .filter_map(|asset_id| {
+            let halt = PRICE_HALTS.with(|h| h.borrow().get(asset_id));
+            let cached = PRICE_STORAGE.with(|p| p.borrow().get(asset_id));
+            trading_block(halt.as_ref(), cached.as_ref(), &price_policy(asset_id), now)
+                .map(|reason| format!("{}: {}", asset_id, reason))
+        })
+        .collect();
+
+    if blocked.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Trading is halted for this bundle: {}", blocked.join("; ")))
+    }
+}
+
+// Passing None removes the override so the asset follows OracleConfig again
+#[update]
+pub fn set_asset_price_policy(asset_id: AssetId, policy: Option<AssetPricePolicy>) -> Result<(), String> {
+    let operator = require_role(Role::Operator)?;
+    crate::asset_registry::get_asset(asset_id.clone())?;
+    if let Some(policy) = &policy {
+        validate_asset_price_policy(policy)?;
+    }
+
+    let previous = ASSET_PRICE_POLICIES.with(|p| {
+        let mut policies = p.borrow_mut();
+        match &policy {
+            Some(policy) => policies.insert(asset_id.clone(), policy.clone()),
+            None => policies.remove(&asset_id),
+        }
+    });
+
+    crate::audit::record(
+        operator,
+        "set_asset_price_policy",
+        format!("asset:{}", asset_id),
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&policy)),
+    );
+
+    Ok(())
+}
+
+#[query]
+pub fn list_asset_price_policies() -> Vec<(AssetId, AssetPricePolicy)> {
+    ASSET_PRICE_POLICIES.with(|p| p.borrow().iter().collect())
+}
+
+#[query]
+pub fn get_price_policy(asset_id: AssetId) -> PricePolicy {
+    price_policy(&asset_id)
+}
+
 // This is now exported from lib.rs
 pub async fn get_asset_price(asset_id: AssetId) -> Result<AssetPrice, String> {
     get_latest_price(&asset_id).await
@@ -46,25 +419,16 @@ pub async fn get_latest_price(asset_id: &AssetId) -> Result<AssetPrice, String>
     let oracle_ticker = asset_info.oracle_ticker
         .ok_or_else(|| format!("No oracle ticker configured for asset {}", asset_id))?;
 
-    let price = fetch_price_from_oracle(&oracle_ticker).await?;
-
-    let asset_price = AssetPrice {
-        asset_id: asset_id.clone(),
-        price_usd: price,
-        timestamp: time(),
-        source: "xfusion_oracle".to_string(),
-        confidence: 95,
-    };
-
-    PRICE_STORAGE.with(|p| p.borrow_mut().insert(asset_id.clone(), asset_price.clone()));
+    let aggregated = fetch_aggregated_prices(&[(asset_id.clone(), oracle_ticker.clone())]).await
+        .and_then(|mut results| results.pop().unwrap_or_else(|| Err("No price returned".to_string())));
 
-    Ok(asset_price)
+    record_observation(asset_id, aggregated)
+        .or_else(|e| fallback_price(asset_id, &oracle_ticker, &price_policy(asset_id)).ok_or(e))
 }
 
 pub async fn get_multiple_prices(asset_ids: &[AssetId]) -> Result<Vec<AssetPrice>, String> {
     let mut prices = Vec::new();
-    let mut tickers_to_fetch = Vec::new();
-    let mut assets_to_update = Vec::new();
+    let mut assets_to_fetch = Vec::new();
 
     for asset_id in asset_ids {
         if let Some(cached_price) = get_cached_price_if_valid(asset_id) {
@@ -73,97 +437,164 @@ pub async fn get_multiple_prices(asset_ids: &[AssetId]) -> Result<Vec<AssetPrice
             let asset_info = crate::asset_registry::get_asset(asset_id.clone())?;
             let oracle_ticker = asset_info.oracle_ticker
                 .ok_or_else(|| format!("No oracle ticker configured for asset {}", asset_id))?;
-            tickers_to_fetch.push(oracle_ticker);
-            assets_to_update.push(asset_id.clone());
+            assets_to_fetch.push((asset_id.clone(), oracle_ticker));
         }
     }
 
-    if !tickers_to_fetch.is_empty() {
-        let current_time = time();
-
-        // Call the real oracle for multiple prices at once (more efficient)
-        let oracle_principal = Principal::from_text("zutfo-jqaaa-aaaao-a4puq-cai")
-            .map_err(|e| format!("Invalid oracle canister ID: {}", e))?;
+    if !assets_to_fetch.is_empty() {
+        let results = fetch_aggregated_prices(&assets_to_fetch).await?;
 
-        #[derive(CandidType, Deserialize)]
-        struct Price {
-            pub value: u64,
-            pub confidence: Option<u64>,
-            pub timestamp: u64,
-            pub source: String,
+        for ((asset_id, ticker), result) in assets_to_fetch.iter().zip(results) {
+            match record_observation(asset_id, result) {
+                Ok(asset_price) => prices.push(asset_price),
+                Err(e) => match fallback_price(asset_id, ticker, &price_policy(asset_id)) {
+                    Some(fallback) => prices.push(fallback),
+                    None => ic_cdk::println!("No price available for {}: {}", ticker, e),
+                },
+            }
         }
+    }
 
-        // Call oracle's get_prices method for batch fetching
-        let (fetched_prices,): (Vec<Option<Price>>,) = ic_cdk::call(
-            oracle_principal,
-            "get_prices",
-            (tickers_to_fetch.clone(),)
-        )
-        .await
-        .map_err(|e| format!("Failed to call oracle for multiple prices: {:?}", e))?;
+    Ok(prices)
+}
 
-        // Process fetched prices
-        for (i, price_opt) in fetched_prices.into_iter().enumerate() {
-            let price_usd = if let Some(price) = price_opt {
-                price.value
-            } else {
-                // Fallback for stablecoins if oracle doesn't have the price
-                match tickers_to_fetch[i].as_str() {
-                    "USDC" | "ckUSDC" => 1_00000000,  // $1 for stablecoins
-                    _ => {
-                        ic_cdk::println!("No price available for {}", tickers_to_fetch[i]);
-                        continue;
-                    }
-                }
-            };
+// An override only covers the condition the operator reviewed, so a new reason re-arms the halt
+pub fn next_halt(existing: Option<PriceHalt>, asset_id: &AssetId, reason: PriceHaltReason, now: u64) -> PriceHalt {
+    match existing {
+        Some(existing) => PriceHalt {
+            overridden_by: existing.overridden_by.filter(|_| existing.reason == reason),
+            reason,
+            ..existing
+        },
+        None => PriceHalt { asset_id: asset_id.clone(), reason, halted_at: now, overridden_by: None },
+    }
+}
 
-            let asset_price = AssetPrice {
-                asset_id: assets_to_update[i].clone(),
-                price_usd,
-                timestamp: current_time,
-                source: "xfusion_oracle".to_string(),
-                confidence: 95,
-            };
+fn halt_asset(asset_id: &AssetId, reason: PriceHaltReason) {
+    PRICE_HALTS.with(|h| {
+        let mut halts = h.borrow_mut();
+        let existing = halts.get(asset_id);
+        if existing.as_ref().is_none_or(|halt| halt.reason != reason) {
+            ic_cdk::println!("Price halt on {}: {:?}", asset_id, reason);
+        }
 
-            PRICE_STORAGE.with(|p| p.borrow_mut().insert(assets_to_update[i].clone(), asset_price.clone()));
-            prices.push(asset_price);
+        halts.insert(asset_id.clone(), next_halt(existing, asset_id, reason, time()));
+    });
+}
+
+// Caches an accepted price and clears any halt; a move past the asset's limit halts it instead
+fn record_observation(asset_id: &AssetId, observation: Result<AssetPrice, String>) -> Result<AssetPrice, String> {
+    let asset_price = match observation {
+        Ok(asset_price) => asset_price,
+        Err(detail) => {
+            halt_asset(asset_id, PriceHaltReason::NoValidPrice { detail: detail.clone() });
+            return Err(detail);
         }
+    };
+
+    let previous = PRICE_STORAGE.with(|p| p.borrow().get(asset_id));
+    if let Some(reason) = check_price_move(previous.as_ref(), asset_price.price_usd, &price_policy(asset_id)) {
+        let detail = format!("{}: price moved beyond its limit ({:?})", asset_id, reason);
+        halt_asset(asset_id, reason);
+        return Err(detail);
     }
 
-    Ok(prices)
+    PRICE_STORAGE.with(|p| p.borrow_mut().insert(asset_id.clone(), asset_price.clone()));
+    crate::price_history::record(&asset_price);
+    if PRICE_HALTS.with(|h| h.borrow_mut().remove(asset_id)).is_some() {
+        ic_cdk::println!("Price halt on {} cleared", asset_id);
+    }
+
+    Ok(asset_price)
 }
 
-async fn fetch_price_from_oracle(ticker: &str) -> Result<u64, String> {
-    // Call the real XFusion oracle canister
-    let oracle_principal = Principal::from_text("zutfo-jqaaa-aaaao-a4puq-cai")
-        .map_err(|e| format!("Invalid oracle canister ID: {}", e))?;
+pub fn is_substitute_price(price: &AssetPrice) -> bool {
+    price.source == STABLECOIN_FALLBACK_SOURCE
+}
 
-    // Define the Price structure matching oracle's structure
-    #[derive(CandidType, Deserialize)]
-    struct Price {
-        pub value: u64,
-        pub confidence: Option<u64>,
-        pub timestamp: u64,
-        pub source: String,
+// Not cached, so the oracle is asked again on the next read
+fn fallback_price(asset_id: &AssetId, ticker: &str, policy: &PricePolicy) -> Option<AssetPrice> {
+    if !policy.fallback_enabled {
+        return None;
     }
 
-    // Call oracle's get_price method
-    let (price_opt,): (Option<Price>,) = ic_cdk::call(oracle_principal, "get_price", (ticker,))
-        .await
-        .map_err(|e| format!("Failed to call oracle: {:?}", e))?;
+    let now = time();
+    let cached = PRICE_STORAGE.with(|p| p.borrow().get(asset_id))
+        .filter(|price| price_age_ns(price.timestamp, now) <= policy.max_staleness_ns);
+    if cached.is_some() {
+        return cached;
+    }
 
-    let price = price_opt.ok_or(format!("No price available for {}", ticker))?;
+    let price_usd = stablecoin_fallback_price(ticker, policy)?;
+    ic_cdk::println!("Using the $1 stablecoin fallback for {}; trading stays halted", asset_id);
 
-    // Validate price freshness (within 10 minutes)
-    let current_time = time();
-    let max_age = 10 * 60 * 1_000_000_000; // 10 minutes in nanoseconds
+    Some(AssetPrice {
+        asset_id: asset_id.clone(),
+        price_usd,
+        timestamp: now,
+        source: STABLECOIN_FALLBACK_SOURCE.to_string(),
+        confidence: 50,
+    })
+}
+
+async fn fetch_oracle_prices(oracle: Principal, tickers: &[String]) -> Result<Vec<Option<OraclePrice>>, String> {
+    ic_cdk::call::Call::bounded_wait(oracle, "get_prices")
+        .with_arg(tickers)
+        .await
+        .map_err(|e| format!("Failed to call oracle {}: {}", oracle, e))?
+        .candid()
+        .map_err(|e| format!("Invalid response from oracle {}: {}", oracle, e))
+}
 
-    if current_time > price.timestamp && current_time - price.timestamp > max_age {
-        // Price is stale, but we'll use it with a warning
-        ic_cdk::println!("Warning: {} price is {} seconds old", ticker, (current_time - price.timestamp) / 1_000_000_000);
+// Queries every configured oracle once for all tickers, then aggregates each asset separately
+async fn fetch_aggregated_prices(assets: &[(AssetId, String)]) -> Result<Vec<Result<AssetPrice, String>>, String> {
+    let oracles = oracle_sources()?;
+    let config = aggregation_config();
+    let tickers: Vec<String> = assets.iter().map(|(_, ticker)| ticker.clone()).collect();
+    let mut samples: Vec<Vec<PriceSample>> = vec![Vec::new(); assets.len()];
+
+    for oracle in &oracles {
+        match fetch_oracle_prices(*oracle, &tickers).await {
+            Ok(prices) => {
+                for (asset_samples, price) in samples.iter_mut().zip(prices) {
+                    if let Some(price) = price {
+                        asset_samples.push(PriceSample {
+                            source: oracle.to_text(),
+                            price_usd: price.value,
+                            timestamp: price.timestamp,
+                        });
+                    }
+                }
+            }
+            Err(e) => ic_cdk::println!("{}", e),
+        }
     }
 
-    Ok(price.value)
+    let now = time();
+    Ok(assets.iter().zip(samples).map(|((asset_id, _), mut asset_samples)| {
+        let reference = REFERENCE_PRICES.with(|r| r.borrow().get(asset_id));
+        let queried = oracles.len() + usize::from(reference.is_some());
+        if let Some(reference) = reference {
+            asset_samples.push(PriceSample {
+                source: "reference".to_string(),
+                price_usd: reference.price_usd,
+                timestamp: reference.timestamp,
+            });
+        }
+
+        let policy = price_policy(asset_id);
+        asset_samples.retain(|sample| price_age_ns(sample.timestamp, now) <= policy.max_staleness_ns);
+
+        aggregate_prices(&asset_samples, queried, config.min_sources, config.max_deviation_bps)
+            .map(|aggregated| AssetPrice {
+                asset_id: asset_id.clone(),
+                price_usd: aggregated.price_usd,
+                timestamp: now,
+                source: aggregated.source,
+                confidence: aggregated.confidence,
+            })
+            .map_err(|e| format!("{}: {}", asset_id, e))
+    }).collect())
 }
 
 #[query]
@@ -172,29 +603,20 @@ pub fn get_cached_price(asset_id: AssetId) -> Option<AssetPrice> {
 }
 
 pub fn get_cached_price_if_valid(asset_id: &AssetId) -> Option<AssetPrice> {
-    PRICE_STORAGE.with(|p| {
-        let storage = p.borrow();
-        if let Some(cached_price) = storage.get(asset_id) {
-            let age = time() - cached_price.timestamp;
-            if age < DEFAULT_CACHE_DURATION_NS {
-                Some(cached_price)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    })
+    let policy = price_policy(asset_id);
+    let now = time();
+
+    PRICE_STORAGE.with(|p| p.borrow().get(asset_id))
+        .filter(|price| price_age_ns(price.timestamp, now) < policy.cache_duration_ns)
 }
 
 pub fn is_price_stale(asset_id: &AssetId, max_staleness: Option<u64>) -> bool {
-    let max_staleness = max_staleness.unwrap_or(DEFAULT_MAX_STALENESS_NS);
+    let max_staleness = max_staleness.unwrap_or_else(|| price_policy(asset_id).max_staleness_ns);
 
     PRICE_STORAGE.with(|p| {
         let storage = p.borrow();
         if let Some(cached_price) = storage.get(asset_id) {
-            let age = time() - cached_price.timestamp;
-            age > max_staleness
+            price_age_ns(cached_price.timestamp, time()) > max_staleness
         } else {
             true
         }
@@ -202,19 +624,14 @@ pub fn is_price_stale(asset_id: &AssetId, max_staleness: Option<u64>) -> bool {
 }
 
 pub fn refresh_expired_prices() -> Vec<AssetId> {
-    let mut expired_assets = Vec::new();
+    let now = time();
 
     PRICE_STORAGE.with(|p| {
-        let storage = p.borrow();
-        for (asset_id, price) in storage.iter() {
-            let age = time() - price.timestamp;
-            if age > DEFAULT_CACHE_DURATION_NS {
-                expired_assets.push(asset_id);
-            }
-        }
-    });
-
-    expired_assets
+        p.borrow().iter()
+            .filter(|(asset_id, price)| price_age_ns(price.timestamp, now) > price_policy(asset_id).cache_duration_ns)
+            .map(|(asset_id, _)| asset_id)
+            .collect()
+    })
 }
 
 #[query]
@@ -229,10 +646,10 @@ pub fn get_cache_statistics() -> CacheStatistics {
         let storage = p.borrow();
         total_entries = storage.len() as u32;
 
-        for (_, price) in storage.iter() {
-            let age = current_time - price.timestamp;
+        for (asset_id, price) in storage.iter() {
+            let age = price_age_ns(price.timestamp, current_time);
 
-            if age < DEFAULT_CACHE_DURATION_NS {
+            if age < price_policy(&asset_id).cache_duration_ns {
                 valid_entries += 1;
             } else {
                 expired_entries += 1;
@@ -244,6 +661,8 @@ pub fn get_cache_statistics() -> CacheStatistics {
         }
     });
 
+    let policy = effective_policy(crate::memory::get_oracle_config().as_ref(), None);
+
     CacheStatistics {
         total_entries,
         valid_entries,
@@ -254,7 +673,9 @@ pub fn get_cache_statistics() -> CacheStatistics {
             0.0
         },
         oldest_entry_age_seconds: oldest_entry_age / 1_000_000_000,
-        cache_duration_seconds: DEFAULT_CACHE_DURATION_NS / 1_000_000_000,
+        cache_duration_seconds: policy.cache_duration_ns / 1_000_000_000,
+        max_staleness_seconds: policy.max_staleness_ns / 1_000_000_000,
+        oracle_canister: oracle_canister().unwrap_or_else(|_| Principal::anonymous()),
     }
 }
 
@@ -271,8 +692,7 @@ pub fn list_valid_cached_prices() -> Vec<AssetPrice> {
     PRICE_STORAGE.with(|p| {
         p.borrow().iter()
             .filter_map(|(_, price)| {
-                let age = current_time - price.timestamp;
-                if age < DEFAULT_CACHE_DURATION_NS {
+                if price_age_ns(price.timestamp, current_time) < price_policy(&price.asset_id).cache_duration_ns {
                     Some(price)
                 } else {
                     None
@@ -284,7 +704,7 @@ pub fn list_valid_cached_prices() -> Vec<AssetPrice> {
 
 #[update]
 pub fn clear_price_cache() -> Result<u32, String> {
-    let _admin = require_admin()?;
+    let operator = require_role(Role::Operator)?;
 
     let mut count = 0;
     PRICE_STORAGE.with(|p| {
@@ -297,6 +717,8 @@ pub fn clear_price_cache() -> Result<u32, String> {
         }
     });
 
+    crate::audit::record(operator, "clear_price_cache", "oracle", Some(format!("Cleared {} cached prices", count)), None, None);
+
     ic_cdk::println!("Cleared {} cached prices", count);
     Ok(count)
 }
@@ -0,0 +1,118 @@
+use ic_cdk::api::time;
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::roles::require_role;
+
+pub fn operation_for(operation: &OperationType) -> PausableOperation {
+    match operation {
+        OperationType::InitialBuy { .. } => PausableOperation::InitialFunding,
+        OperationType::Buy { .. } => PausableOperation::NewBuys,
+        OperationType::Sell { .. } => PausableOperation::NewSells,
+    }
+}
+
+pub fn check_not_paused(
+    state: &PauseState,
+    operation: PausableOperation,
+    bundle_id: Option<u64>,
+) -> Result<(), PauseError> {
+    if state.is_paused(&operation) {
+        return Err(PauseError::OperationPaused { operation });
+    }
+
+    if let Some(bundle_id) = bundle_id {
+        if state.is_bundle_paused(bundle_id) {
+            return Err(PauseError::BundlePaused { bundle_id });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn ensure_not_paused(operation: PausableOperation, bundle_id: Option<u64>) -> Result<(), PauseError> {
+    check_not_paused(&crate::memory::get_pause_state(), operation, bundle_id)
+}
+
+pub fn is_transaction_blocked(transaction: &Transaction) -> bool {
+    let state = crate::memory::get_pause_state();
+    state.is_paused(&PausableOperation::ResolverConfirmations)
+        || state.is_bundle_paused(transaction.bundle_id)
+}
+
+fn update_pause_state<F>(action_type: &str, target: String, f: F) -> Result<PauseState, String>
+where
+    F: FnOnce(&mut PauseState),
+{
+    let pauser = require_role(Role::Pauser)?;
+
+    let previous = crate::memory::get_pause_state();
+    let mut state = previous.clone();
+    f(&mut state);
+    state.updated_at = time();
+    state.updated_by = Some(pauser);
+    crate::memory::set_pause_state(state.clone());
+
+    crate::audit::record(
+        pauser,
+        action_type,
+        target,
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&state)),
+    );
+
+    Ok(state)
+}
+
+pub fn set_all_operations_paused(paused: bool) -> Result<PauseState, String> {
+    let action_type = if paused { "pause_all_operations" } else { "unpause_all_operations" };
+
+    update_pause_state(action_type, "platform".to_string(), |state| {
+        state.new_buys = paused;
+        state.new_sells = paused;
+        state.initial_funding = paused;
+        state.resolver_confirmations = paused;
+        state.bundle_creation = paused;
+    })
+}
+
+#[update]
+pub fn set_operation_paused(operation: PausableOperation, paused: bool) -> Result<PauseState, String> {
+    let state = update_pause_state(
+        "set_operation_paused",
+        format!("operation:{:?}", operation),
+        |state| state.set_paused(&operation, paused),
+    )?;
+
+    ic_cdk::println!("Operation {:?} paused: {}", operation, paused);
+    Ok(state)
+}
+
+#[update]
+pub fn pause_bundle(bundle_id: u64) -> Result<PauseState, String> {
+    crate::bundle_manager::get_bundle(bundle_id)?;
+
+    let state = update_pause_state("pause_bundle", format!("bundle:{}", bundle_id), |state| {
+        if !state.paused_bundles.contains(&bundle_id) {
+            state.paused_bundles.push(bundle_id);
+        }
+    })?;
+
+    ic_cdk::println!("Bundle {} paused", bundle_id);
+    Ok(state)
+}
+
+#[update]
+pub fn unpause_bundle(bundle_id: u64) -> Result<PauseState, String> {
+    let state = update_pause_state("unpause_bundle", format!("bundle:{}", bundle_id), |state| {
+        state.paused_bundles.retain(|id| *id != bundle_id);
+    })?;
+
+    ic_cdk::println!("Bundle {} unpaused", bundle_id);
+    Ok(state)
+}
+
+#[query]
+pub fn get_pause_state() -> PauseState {
+    crate::memory::get_pause_state()
+}
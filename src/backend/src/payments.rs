@@ -0,0 +1,90 @@
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::memory::*;
+
+pub const DEFAULT_PAYMENT_ASSET: &str = "ckUSDC";
+
+// Points and volume statistics are kept in 6-decimal USD regardless of the payment asset
+pub const USD_DECIMALS: u8 = 6;
+const USD_E6_PER_POINT: u64 = 10_000;
+
+pub fn payment_asset(asset_id: &Option<AssetId>) -> AssetId {
+    asset_id.clone().unwrap_or_else(|| DEFAULT_PAYMENT_ASSET.to_string())
+}
+
+pub fn payment_token_from_asset(asset: &AssetInfo) -> Result<PaymentToken, String> {
+    if !asset.is_active {
+        return Err(format!("Payment asset {} is not active", asset.id));
+    }
+
+    match &asset.token_location {
+        TokenLocation::ICRC2 { ledger } => Ok(PaymentToken {
+            asset_id: asset.id.clone(),
+            ledger: *ledger,
+            decimals: asset.decimals,
+        }),
+        _ => Err(format!("Asset {} is not an ICRC-2 payment token", asset.id)),
+    }
+}
+
+// ckUSDC always uses the configured ledger; every other payment token comes from the asset registry
+pub fn payment_token(asset_id: &str) -> Result<PaymentToken, String> {
+    if asset_id == DEFAULT_PAYMENT_ASSET {
+        return Ok(PaymentToken {
+            asset_id: asset_id.to_string(),
+            ledger: crate::icrc2_client::ckusdc_ledger()?,
+            decimals: crate::icrc2_client::CKUSDC_DECIMALS,
+        });
+    }
+
+    let asset = ASSET_REGISTRY.with(|registry| registry.borrow().get(&asset_id.to_string()))
+        .ok_or_else(|| format!("Unknown payment asset {}", asset_id))?;
+    payment_token_from_asset(&asset)
+}
+
+pub fn transaction_payment_token(transaction: &Transaction) -> Result<PaymentToken, String> {
+    payment_token(&payment_asset(&transaction.payment_asset))
+}
+
+pub fn normalize_amount(amount: u64, from_decimals: u8, to_decimals: u8) -> u64 {
+    if from_decimals >= to_decimals {
+        amount / 10u64.saturating_pow((from_decimals - to_decimals) as u32)
+    } else {
+        amount.saturating_mul(10u64.saturating_pow((to_decimals - from_decimals) as u32))
+    }
+}
+
+pub fn points_for(amount: u64, decimals: u8) -> u64 {
+    normalize_amount(amount, decimals, USD_DECIMALS) / USD_E6_PER_POINT
+}
+
+// Deactivated assets keep their registered decimals so history stays at the right scale;
+// only ckUSDC and assets removed from the registry use ckUSDC decimals
+pub fn payment_decimals(asset_id: &str) -> u8 {
+    if asset_id == DEFAULT_PAYMENT_ASSET {
+        return crate::icrc2_client::CKUSDC_DECIMALS;
+    }
+
+    ASSET_REGISTRY.with(|registry| registry.borrow().get(&asset_id.to_string()))
+        .map(|asset| asset.decimals)
+        .unwrap_or(crate::icrc2_client::CKUSDC_DECIMALS)
+}
+
+pub fn usd_value_e6(asset_id: &Option<AssetId>, amount: u64) -> u64 {
+    normalize_amount(amount, payment_decimals(&payment_asset(asset_id)), USD_DECIMALS)
+}
+
+#[query]
+pub fn list_payment_tokens() -> Vec<PaymentToken> {
+    let mut tokens: Vec<PaymentToken> = payment_token(DEFAULT_PAYMENT_ASSET).into_iter().collect();
+
+    ASSET_REGISTRY.with(|registry| {
+        tokens.extend(
+            registry.borrow().iter()
+                .filter(|(id, _)| id != DEFAULT_PAYMENT_ASSET)
+                .filter_map(|(_, asset)| payment_token_from_asset(&asset).ok())
+        );
+    });
+
+    tokens
+}
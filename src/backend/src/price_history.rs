@@ -0,0 +1,224 @@
+use ic_cdk::api::time;
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::memory::*;
+
+pub const MIN_WINDOW_NS: u64 = 60_000_000_000;
+pub const MAX_WINDOW_NS: u64 = 7 * 86_400_000_000_000;
+// Observations are kept for the longest window; an EMA reads back this many time constants
+pub const OBSERVATION_RETENTION_NS: u64 = MAX_WINDOW_NS;
+const EMA_LOOKBACK_WINDOWS: u64 = 5;
+const MAX_OBSERVATIONS_PAGE: u64 = 500;
+const MAX_CANDLES_PAGE: usize = 1_000;
+
+pub fn validate_window(window_ns: u64) -> Result<(), String> {
+    if !(MIN_WINDOW_NS..=MAX_WINDOW_NS).contains(&window_ns) {
+        return Err(format!("Window must be between {} and {} ns", MIN_WINDOW_NS, MAX_WINDOW_NS));
+    }
+    Ok(())
+}
+
+pub fn validate_valuation_mode(mode: &ValuationMode) -> Result<(), String> {
+    match mode {
+        ValuationMode::Spot => Ok(()),
+        ValuationMode::Twap { window_ns } => validate_window(*window_ns),
+    }
+}
+
+fn observation_key(asset_id: &AssetId, timestamp: u64) -> PriceObservationKey {
+    PriceObservationKey { asset_id: asset_id.clone(), timestamp }
+}
+
+// Drops observations older than the retention, except the newest of them, which covers the start of the longest window
+fn store_observation(asset_id: &AssetId, price_usd: u64, timestamp: u64) {
+    let cutoff = timestamp.saturating_sub(OBSERVATION_RETENTION_NS);
+
+    PRICE_OBSERVATIONS.with(|o| {
+        let mut observations = o.borrow_mut();
+        observations.insert(observation_key(asset_id, timestamp), price_usd);
+
+        let mut expired: Vec<PriceObservationKey> = observations
+            .range(observation_key(asset_id, 0)..observation_key(asset_id, cutoff))
+            .map(|(key, _)| key)
+            .collect();
+        expired.pop();
+        for key in expired {
+            observations.remove(&key);
+        }
+    });
+}
+
+// Called for every price the oracle accepts
+pub fn record(price: &AssetPrice) {
+    store_observation(&price.asset_id, price.price_usd, price.timestamp);
+    for interval in CandleInterval::ALL {
+        record_candle(&price.asset_id, interval, price.price_usd, price.timestamp);
+    }
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [CandleInterval::Minute, CandleInterval::Hour, CandleInterval::Day];
+
+    pub fn duration_ns(self) -> u64 {
+        match self {
+            CandleInterval::Minute => 60_000_000_000,
+            CandleInterval::Hour => 3_600_000_000_000,
+            CandleInterval::Day => 86_400_000_000_000,
+        }
+    }
+
+    // 1m candles for a day, 1h for 30 days, 1d for two years
+    pub fn retention_ns(self) -> u64 {
+        match self {
+            CandleInterval::Minute => 1_440 * self.duration_ns(),
+            CandleInterval::Hour => 720 * self.duration_ns(),
+            CandleInterval::Day => 730 * self.duration_ns(),
+        }
+    }
+
+    pub fn bucket_start(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.duration_ns()
+    }
+}
+
+pub fn apply_sample(candle: Option<PriceCandle>, open_time: u64, price_usd: u64) -> PriceCandle {
+    match candle {
+        Some(mut candle) => {
+            candle.high = candle.high.max(price_usd);
+            candle.low = candle.low.min(price_usd);
+            candle.close = price_usd;
+            candle.samples += 1;
+            candle
+        }
+        None => PriceCandle { open_time, open: price_usd, high: price_usd, low: price_usd, close: price_usd, samples: 1 },
+    }
+}
+
+fn candle_key(asset_id: &AssetId, interval: CandleInterval, open_time: u64) -> PriceCandleKey {
+    PriceCandleKey { asset_id: asset_id.clone(), interval, open_time }
+}
+
+pub fn record_candle(asset_id: &AssetId, interval: CandleInterval, price_usd: u64, timestamp: u64) {
+    let open_time = interval.bucket_start(timestamp);
+    let key = candle_key(asset_id, interval, open_time);
+    let cutoff = open_time.saturating_sub(interval.retention_ns());
+
+    PRICE_CANDLES.with(|c| {
+        let mut candles = c.borrow_mut();
+        let candle = apply_sample(candles.get(&key), open_time, price_usd);
+        candles.insert(key, candle);
+
+        let expired: Vec<PriceCandleKey> = candles
+            .range(candle_key(asset_id, interval, 0)..candle_key(asset_id, interval, cutoff))
+            .map(|(key, _)| key)
+            .collect();
+        for key in expired {
+            candles.remove(&key);
+        }
+    });
+}
+
+// Candles opening in [from, to], oldest first; page by passing the last open_time + 1 as `from`
+pub fn price_history(asset_id: &AssetId, interval: CandleInterval, from: u64, to: u64) -> Result<Vec<PriceCandle>, String> {
+    if from > to {
+        return Err("from must not be after to".to_string());
+    }
+    Ok(PRICE_CANDLES.with(|c| {
+        c.borrow()
+            .range(candle_key(asset_id, interval, from)..=candle_key(asset_id, interval, to))
+            .take(MAX_CANDLES_PAGE)
+            .map(|(_, candle)| candle)
+            .collect()
+    }))
+}
+
+// Each observation holds until the next one; the last observation before the window covers its start
+pub fn twap(observations: &[PriceObservation], window_ns: u64, now: u64) -> Option<u64> {
+    let start = now.saturating_sub(window_ns);
+    let first = observations.iter().rposition(|o| o.timestamp <= start).unwrap_or(0);
+    let relevant = &observations[first..];
+
+    let mut weighted = 0u128;
+    let mut total = 0u128;
+    for (i, observation) in relevant.iter().enumerate() {
+        let from = observation.timestamp.max(start);
+        let to = relevant.get(i + 1).map_or(now, |next| next.timestamp).max(from);
+        let duration = (to - from) as u128;
+
+        weighted += observation.price_usd as u128 * duration;
+        total += duration;
+    }
+
+    if total == 0 {
+        return relevant.last().map(|o| o.price_usd);
+    }
+    Some((weighted / total) as u64)
+}
+
+// Time-decayed EMA with `window_ns` as the time constant, so uneven sampling doesn't skew it
+pub fn ema(observations: &[PriceObservation], window_ns: u64) -> Option<u64> {
+    let (first, rest) = observations.split_first()?;
+
+    let mut value = first.price_usd as f64;
+    let mut last_timestamp = first.timestamp;
+    for observation in rest {
+        let elapsed = observation.timestamp.saturating_sub(last_timestamp) as f64;
+        let alpha = 1.0 - (-elapsed / window_ns as f64).exp();
+        value += alpha * (observation.price_usd as f64 - value);
+        last_timestamp = observation.timestamp;
+    }
+
+    Some(value.round() as u64)
+}
+
+// Observations from `start` on, oldest first, led by the last one before `start` since it covers the start
+pub fn observations_since(asset_id: &AssetId, start: u64) -> Vec<PriceObservation> {
+    PRICE_OBSERVATIONS.with(|o| {
+        let observations = o.borrow();
+        let before = observations.range(observation_key(asset_id, 0)..observation_key(asset_id, start)).next_back();
+        before.into_iter()
+            .chain(observations.range(observation_key(asset_id, start)..=observation_key(asset_id, u64::MAX)))
+            .map(|(key, price_usd)| PriceObservation { price_usd, timestamp: key.timestamp })
+            .collect()
+    })
+}
+
+pub fn twap_price(asset_id: &AssetId, window_ns: u64) -> Result<u64, String> {
+    validate_window(window_ns)?;
+    let now = time();
+    twap(&observations_since(asset_id, now.saturating_sub(window_ns)), window_ns, now)
+        .ok_or_else(|| format!("No price observations for {}", asset_id))
+}
+
+#[query]
+pub fn get_twap(asset_id: AssetId, window_ns: u64) -> Result<u64, String> {
+    twap_price(&asset_id, window_ns)
+}
+
+#[query]
+pub fn get_ema(asset_id: AssetId, window_ns: u64) -> Result<u64, String> {
+    validate_window(window_ns)?;
+    let start = time().saturating_sub(window_ns.saturating_mul(EMA_LOOKBACK_WINDOWS));
+    ema(&observations_since(&asset_id, start), window_ns)
+        .ok_or_else(|| format!("No price observations for {}", asset_id))
+}
+
+// The most recent `limit` observations, oldest first
+#[query]
+pub fn get_price_observations(asset_id: AssetId, limit: u64) -> Vec<PriceObservation> {
+    let mut observations: Vec<PriceObservation> = PRICE_OBSERVATIONS.with(|o| {
+        o.borrow()
+            .range(observation_key(&asset_id, 0)..=observation_key(&asset_id, u64::MAX))
+            .rev()
+            .take(limit.min(MAX_OBSERVATIONS_PAGE) as usize)
+            .map(|(key, price_usd)| PriceObservation { price_usd, timestamp: key.timestamp })
+            .collect()
+    });
+    observations.reverse();
+    observations
+}
+
+#[query]
+pub fn get_price_history(asset_id: AssetId, interval: CandleInterval, from: u64, to: u64) -> Result<Vec<PriceCandle>, String> {
+    price_history(&asset_id, interval, from, to)
+}
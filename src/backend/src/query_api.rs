@@ -84,15 +84,52 @@ pub async fn get_bundles_list() -> Vec<BundleListItem> {
             created_at: bundle.created_at,
             is_active: bundle.is_active,
             token_location: bundle.token_location,
+            // Empty until the next heartbeat after an upgrade or a new bundle
+            analytics: crate::memory::get_cached_analytics(bundle.id)
+                .unwrap_or_else(|| BundleAnalytics { bundle_id: bundle.id, ..Default::default() }),
         });
     }
 
     result
 }
 
-#[query]
-pub fn get_user_portfolio(user: Principal) -> UserPortfolio {
-    let nav_tokens = nav_token::get_user_nav_tokens(user);
+fn sort_value(item: &BundleListItem, key: BundleSortKey) -> Option<f64> {
+    let analytics = &item.analytics;
+    match key {
+        BundleSortKey::NavPerToken => Some(item.nav_per_token as f64),
+        BundleSortKey::TotalNav => Some(item.total_nav_usd as f64),
+        BundleSortKey::Holders => Some(item.holders as f64),
+        BundleSortKey::Return24h => analytics.return_24h_bps.map(|v| v as f64),
+        BundleSortKey::Return7d => analytics.return_7d_bps.map(|v| v as f64),
+        BundleSortKey::Return30d => analytics.return_30d_bps.map(|v| v as f64),
+        BundleSortKey::ReturnYtd => analytics.return_ytd_bps.map(|v| v as f64),
+        BundleSortKey::ReturnInception => analytics.return_inception_bps.map(|v| v as f64),
+        BundleSortKey::Volatility => analytics.volatility_bps.map(|v| v as f64),
+        BundleSortKey::MaxDrawdown => analytics.max_drawdown_bps.map(|v| v as f64),
+        BundleSortKey::Sharpe => analytics.sharpe_ratio,
+    }
+}
+
+// Bundles without enough history for the metric sort last in either direction
+pub fn sort_bundle_list(items: &mut [BundleListItem], sort_by: BundleSortKey, descending: bool) {
+    items.sort_by(|a, b| match (sort_value(a, sort_by), sort_value(b, sort_by)) {
+        (Some(x), Some(y)) if descending => y.total_cmp(&x),
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+pub async fn get_bundles_list_sorted(sort_by: BundleSortKey, descending: bool) -> Vec<BundleListItem> {
+    let mut items = get_bundles_list().await;
+    sort_bundle_list(&mut items, sort_by, descending);
+    items
+}
+
+#[update]
+pub async fn get_user_portfolio(user: Principal) -> Result<UserPortfolio, String> {
+    let nav_tokens = nav_token::fetch_user_nav_tokens(user).await?;
     let created_bundles = bundle_manager::get_user_bundles(user);
 
     let holdings: Vec<NAVTokenHolding> = nav_tokens.into_iter().map(|token| {
@@ -111,6 +148,7 @@ pub fn get_user_portfolio(user: Principal) -> UserPortfolio {
                 created_at: 0,
                 is_active: false,
                 platform_fee_bps: Some(50),
+                valuation_mode: None,
             });
 
         NAVTokenHolding {
@@ -124,13 +162,13 @@ pub fn get_user_portfolio(user: Principal) -> UserPortfolio {
     let total_bundles_created = created_bundles.len() as u32;
     let total_nav_tokens_held = holdings.iter().map(|h| h.amount).sum();
 
-    UserPortfolio {
+    Ok(UserPortfolio {
         user,
         nav_token_holdings: holdings,
         created_bundles,
         total_bundles_created,
         total_nav_tokens_held,
-    }
+    })
 }
 
 #[query]
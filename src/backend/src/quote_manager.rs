@@ -10,15 +10,23 @@ pub fn get_assignment(assignment_id: u64) -> Result<QuoteAssignment, String> {
     })
 }
 
-pub fn set_coordinator_public_key(public_key_hex: String) -> Result<(), String> {
-    let _admin = crate::admin::require_admin()?;
+pub fn set_coordinator_public_key(public_key_hex: String) -> Result<u64, String> {
+    crate::governance::propose(GovernedAction::SetCoordinatorPublicKey { public_key_hex })
+}
 
-    let public_key_bytes = hex_to_bytes(&public_key_hex)?;
+pub fn decode_coordinator_public_key(public_key_hex: &str) -> Result<Vec<u8>, String> {
+    let public_key_bytes = hex_to_bytes(public_key_hex)?;
 
     if public_key_bytes.len() != 32 {
         return Err("Ed25519 public key must be 32 bytes".to_string());
     }
 
+    Ok(public_key_bytes)
+}
+
+pub(crate) fn apply_coordinator_public_key(public_key_hex: &str) -> Result<(), String> {
+    let public_key_bytes = decode_coordinator_public_key(public_key_hex)?;
+
     GLOBAL_STATE.with(|state| {
         let mut state = state.borrow_mut();
         let mut global_state = state.get().clone();
@@ -80,7 +88,7 @@ fn serialize_quote_for_signing(quote: &QuoteObject) -> Vec<u8> {
             .join(",")
     );
 
-    let serialized = format!(
+    let mut serialized = format!(
         "{}|{}|{}|{}|{}|{}|{}|{}|{}",
         quote.bundle_id,
         operation_json,
@@ -93,7 +101,11 @@ fn serialize_quote_for_signing(quote: &QuoteObject) -> Vec<u8> {
         quote.nonce
     );
 
-    ic_cdk::println!("[BACKEND SERIALIZE] {}", serialized);
+    // Only signed when set, so ckUSDC quotes from older coordinators still verify
+    if let Some(asset) = &quote.payment_asset {
+        serialized.push_str(&format!("|{}", asset));
+    }
+
     serialized.into_bytes()
 }
 
@@ -145,11 +157,7 @@ fn validate_coordinator_signature(quote: &QuoteObject) -> Result<(), String> {
 
 pub async fn execute_quote(quote: QuoteObject) -> Result<u64, String> {
     let user = msg_caller();
-
-    ic_cdk::println!("=== EXECUTE QUOTE DEBUG ===");
-    ic_cdk::println!("msg_caller (user): {}", user);
-    ic_cdk::println!("quote.bundle_id: {}", quote.bundle_id);
-    ic_cdk::println!("quote.ckusdc_amount: {}", quote.ckusdc_amount);
+    let _guard = crate::guard::UserGuard::acquire(user)?;
 
     validate_coordinator_signature(&quote)?;
 
@@ -162,7 +170,10 @@ pub async fn execute_quote(quote: QuoteObject) -> Result<u64, String> {
         ));
     }
 
-    consume_nonce(quote.nonce, current_time)?;
+    crate::pause_manager::ensure_not_paused(
+        crate::pause_manager::operation_for(&quote.operation),
+        Some(quote.bundle_id),
+    )?;
 
     let bundle = crate::bundle_manager::get_bundle(quote.bundle_id)?;
 
@@ -170,6 +181,15 @@ pub async fn execute_quote(quote: QuoteObject) -> Result<u64, String> {
         return Err("Bundle is not active. Only initial funding is allowed.".to_string());
     }
 
+    let bundle_assets: Vec<AssetId> = bundle.allocations.iter().map(|a| a.asset_id.clone()).collect();
+    crate::oracle::ensure_assets_tradeable(&bundle_assets).await?;
+
+    // Burned only once the quote can execute, so a halt or inactive bundle doesn't waste the quote
+    consume_nonce(quote.nonce, time())?;
+
+    let payment_token = crate::payments::payment_token(&crate::payments::payment_asset(&quote.payment_asset))?;
+    let payment_lock = LockedFundType::payment(&payment_token.asset_id);
+
     let platform_fee = (quote.ckusdc_amount as u128 * bundle.platform_fee_bps.unwrap_or(50) as u128 / 10000) as u64;
 
     let transaction_id = crate::transaction_manager::create_transaction_from_quote(&quote, user)?;
@@ -178,46 +198,53 @@ pub async fn execute_quote(quote: QuoteObject) -> Result<u64, String> {
         OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
             crate::transaction_manager::lock_user_funds_with_validation(
                 transaction_id,
-                LockedFundType::CkUSDC,
+                payment_lock.clone(),
                 quote.ckusdc_amount,
             ).await?;
         }
         OperationType::Sell { .. } => {
+            let nav_lock = LockedFundType::NAVTokens { bundle_id: quote.bundle_id };
+
             crate::transaction_manager::lock_user_funds_with_validation(
                 transaction_id,
-                LockedFundType::NAVTokens { bundle_id: quote.bundle_id },
+                nav_lock.clone(),
                 quote.nav_tokens,
             ).await?;
+
+            let transaction = crate::transaction_manager::get_transaction(transaction_id)?;
+
+            if let Err(e) = crate::escrow::escrow_nav_tokens(&transaction, &bundle).await {
+                let _ = crate::transaction_manager::unlock_user_funds(transaction_id, &nav_lock);
+                crate::transaction_manager::update_transaction_status(transaction_id, TransactionStatus::Failed, user)?;
+                return Err(e);
+            }
         }
     }
 
     if matches!(quote.operation, OperationType::InitialBuy { .. } | OperationType::Buy { .. }) {
-        let ckusdc_ledger = candid::Principal::from_text(crate::icrc2_client::CKUSDC_LEDGER_CANISTER)
-            .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+        let pull_memo = format!("Lock {} for tx {}", payment_token.asset_id, transaction_id).into_bytes();
 
-        ic_cdk::println!("=== ATTEMPTING CKUSDC PULL ===");
-        ic_cdk::println!("From user: {}", user);
-        ic_cdk::println!("To backend: {}", ic_cdk::api::id());
-        ic_cdk::println!("Amount: {}", quote.ckusdc_amount);
-
-        let pull_memo = format!("Lock ckUSDC for tx {}", transaction_id).into_bytes();
-
-        let pull_result = crate::icrc2_client::icrc2_transfer_from(
-            ckusdc_ledger,
+        let pull_result = crate::escrow::escrow_payment(
+            &payment_token,
+            transaction_id,
+            crate::escrow::PAYMENT_ESCROW_STEP,
             user,
-            ic_cdk::api::id(),
             quote.ckusdc_amount,
-            Some(pull_memo),
+            pull_memo,
         ).await;
 
-        ic_cdk::println!("Pull result: {:?}", pull_result);
-        pull_result?;
+        if let Err(e) = pull_result {
+            let _ = crate::transaction_manager::unlock_user_funds(transaction_id, &payment_lock);
+            crate::transaction_manager::update_transaction_status(transaction_id, TransactionStatus::Failed, user)?;
+            return Err(e);
+        }
     }
 
     let assignment = QuoteAssignment {
         request_id: transaction_id,
         resolver: quote.resolver,
         nav_tokens: quote.nav_tokens,
+        payment_asset: Some(payment_token.asset_id.clone()),
         ckusdc_amount: quote.ckusdc_amount,
         asset_amounts: quote.asset_amounts.clone(),
         estimated_nav: 0,
@@ -230,6 +257,12 @@ pub async fn execute_quote(quote: QuoteObject) -> Result<u64, String> {
         assignments.borrow_mut().insert(transaction_id, assignment);
     });
 
+    crate::transaction_manager::update_transaction_status(
+        transaction_id,
+        TransactionStatus::WaitingForResolver,
+        user,
+    )?;
+
     Ok(transaction_id)
 }
 
@@ -5,7 +5,7 @@ use ic_stable_structures::Storable;
 use std::borrow::Cow;
 
 use crate::memory::*;
-use crate::admin::is_admin;
+use crate::types::Role;
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct ResolverInfo {
@@ -42,8 +42,8 @@ pub fn register_resolver(
 ) -> Result<(), String> {
     let caller = msg_caller();
 
-    if !is_admin(caller) {
-        return Err("Unauthorized: Only admin can register resolvers".to_string());
+    if !crate::roles::has_role(caller, Role::ResolverManager) {
+        return Err("Unauthorized: ResolverManager role required to register resolvers".to_string());
     }
 
     if fee_rate > 1000 {
@@ -69,8 +69,12 @@ pub fn register_resolver(
             last_active: time(),
         };
 
+        let details = crate::audit::describe(&resolver_info);
         registry.insert(caller, resolver_info);
-        Ok(())
+        Ok(details)
+    })
+    .map(|details| {
+        crate::audit::record(caller, "register_resolver", format!("resolver:{}", caller), None, None, Some(details));
     })
 }
 
@@ -81,8 +85,8 @@ pub fn register_resolver_admin(
 ) -> Result<(), String> {
     let caller = msg_caller();
 
-    if !is_admin(caller) {
-        return Err("Unauthorized: Only admin can register resolvers".to_string());
+    if !crate::roles::has_role(caller, Role::ResolverManager) {
+        return Err("Unauthorized: ResolverManager role required to register resolvers".to_string());
     }
 
     if fee_rate > 1000 {
@@ -108,29 +112,51 @@ pub fn register_resolver_admin(
             last_active: time(),
         };
 
+        let details = crate::audit::describe(&resolver_info);
         registry.insert(resolver_principal, resolver_info);
-        Ok(())
+        Ok(details)
+    })
+    .map(|details| {
+        crate::audit::record(
+            caller,
+            "register_resolver",
+            format!("resolver:{}", resolver_principal),
+            None,
+            None,
+            Some(details),
+        );
     })
 }
 
 pub fn update_resolver_status(resolver: Principal, is_active: bool) -> Result<(), String> {
     let caller = msg_caller();
 
-    if !is_admin(caller) && caller != resolver {
+    if !crate::roles::has_role(caller, Role::ResolverManager) && caller != resolver {
         return Err("Unauthorized".to_string());
     }
 
-    RESOLVER_REGISTRY.with(|registry| {
+    let was_active = RESOLVER_REGISTRY.with(|registry| {
         let mut registry = registry.borrow_mut();
 
         let mut resolver_info = registry.get(&resolver)
             .ok_or("Resolver not found")?;
 
+        let was_active = resolver_info.is_active;
         resolver_info.is_active = is_active;
         resolver_info.last_active = time();
         registry.insert(resolver, resolver_info);
-        Ok(())
-    })
+        Ok::<bool, String>(was_active)
+    })?;
+
+    crate::audit::record(
+        caller,
+        "update_resolver_status",
+        format!("resolver:{}", resolver),
+        None,
+        Some(format!("is_active: {}", was_active)),
+        Some(format!("is_active: {}", is_active)),
+    );
+    Ok(())
 }
 
 pub fn update_resolver_performance(
@@ -0,0 +1,119 @@
+use candid::Principal;
+use ic_cdk::api::{msg_caller, time};
+use ic_cdk_macros::*;
+use crate::types::*;
+use crate::memory::*;
+
+// Owner holds every role; the configured admin principal is always an owner
+pub fn role_satisfies(held: &[Role], required: Role) -> bool {
+    held.contains(&Role::Owner) || held.contains(&required)
+}
+
+pub fn get_principal_roles(principal: Principal) -> Vec<Role> {
+    let mut roles = ROLE_ASSIGNMENTS.with(|assignments| {
+        assignments.borrow().get(&principal).map(|a| a.roles).unwrap_or_default()
+    });
+
+    if get_admin_principal() == Some(principal) && !roles.contains(&Role::Owner) {
+        roles.insert(0, Role::Owner);
+    }
+
+    roles
+}
+
+pub fn has_role(principal: Principal, role: Role) -> bool {
+    principal != Principal::anonymous() && role_satisfies(&get_principal_roles(principal), role)
+}
+
+// Every principal that passes a check for the role, including owners and the admin principal
+pub fn role_holders(role: Role) -> Vec<Principal> {
+    let mut holders: Vec<Principal> = ROLE_ASSIGNMENTS.with(|assignments| {
+        assignments.borrow().iter().map(|(principal, _)| principal).collect()
+    });
+    holders.extend(get_admin_principal());
+    holders.sort();
+    holders.dedup();
+    holders.retain(|principal| has_role(*principal, role));
+    holders
+}
+
+pub fn require_role(role: Role) -> Result<Principal, String> {
+    let caller = msg_caller();
+    if !has_role(caller, role) {
+        return Err(format!("{:?} role required", role));
+    }
+    Ok(caller)
+}
+
+fn update_roles<F>(action_type: &str, principal: Principal, f: F) -> Result<Vec<Role>, String>
+where
+    F: FnOnce(&mut Vec<Role>),
+{
+    let owner = require_role(Role::Owner)?;
+
+    if principal == Principal::anonymous() {
+        return Err("Cannot assign roles to the anonymous principal".to_string());
+    }
+
+    let (previous, roles) = ROLE_ASSIGNMENTS.with(|assignments| {
+        let mut assignments = assignments.borrow_mut();
+
+        let previous = assignments.get(&principal).map(|a| a.roles).unwrap_or_default();
+        let mut roles = previous.clone();
+        f(&mut roles);
+        roles.sort();
+        roles.dedup();
+
+        if roles.is_empty() {
+            assignments.remove(&principal);
+        } else {
+            assignments.insert(principal, RoleAssignment {
+                principal,
+                roles: roles.clone(),
+                updated_at: time(),
+                updated_by: owner,
+            });
+        }
+
+        (previous, roles)
+    });
+
+    crate::audit::record(
+        owner,
+        action_type,
+        format!("principal:{}", principal),
+        None,
+        Some(crate::audit::describe(&previous)),
+        Some(crate::audit::describe(&roles)),
+    );
+
+    Ok(roles)
+}
+
+#[update]
+pub fn grant_role(principal: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let roles = update_roles("grant_role", principal, |roles| roles.push(role))?;
+
+    ic_cdk::println!("Granted {:?} to {}", role, principal);
+    Ok(roles)
+}
+
+#[update]
+pub fn revoke_role(principal: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let roles = update_roles("revoke_role", principal, |roles| roles.retain(|r| *r != role))?;
+
+    ic_cdk::println!("Revoked {:?} from {}", role, principal);
+    Ok(roles)
+}
+
+#[query]
+pub fn get_roles(principal: Principal) -> Vec<Role> {
+    get_principal_roles(principal)
+}
+
+#[query]
+pub fn list_role_assignments() -> Vec<RoleAssignment> {
+    ROLE_ASSIGNMENTS.with(|assignments| {
+        assignments.borrow().iter().map(|(_, assignment)| assignment).collect()
+    })
+}
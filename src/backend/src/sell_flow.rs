@@ -1,6 +1,9 @@
 use ic_cdk::api::msg_caller;
 use crate::types::*;
-use crate::{icrc151_client, icrc2_client};
+use crate::{icrc151_client, icrc2_client, settlement};
+
+pub const PROCEEDS_STEP: &str = "proceeds";
+pub const BURN_STEP: &str = "burn";
 
 pub async fn confirm_resolver_payment_and_complete_sell(request_id: u64) -> Result<(), String> {
     let caller = msg_caller();
@@ -12,32 +15,83 @@ pub async fn confirm_resolver_payment_and_complete_sell(request_id: u64) -> Resu
         return Err("Only assigned resolver can confirm payment".to_string());
     }
 
+    let _guard = crate::guard::TransactionGuard::acquire(transaction.id)?;
+
     match transaction.operation {
         OperationType::Sell { .. } => {},
         _ => return Err("This function is only for sell operations".to_string()),
     }
 
+    crate::pause_manager::ensure_not_paused(
+        PausableOperation::ResolverConfirmations,
+        Some(transaction.bundle_id),
+    )?;
+
     let bundle = crate::bundle_manager::get_bundle(transaction.bundle_id)?;
 
-    let ckusdc_ledger = candid::Principal::from_text(icrc2_client::CKUSDC_LEDGER_CANISTER)
-        .map_err(|e| format!("Invalid ckUSDC ledger: {}", e))?;
+    match transaction.status {
+        TransactionStatus::WaitingForResolver => {
+            crate::transaction_manager::update_transaction_status(
+                transaction.id,
+                TransactionStatus::InProgress,
+                caller,
+            )?;
+        }
+        TransactionStatus::InProgress => {
+            ic_cdk::println!("Resuming sell settlement for tx {}", transaction.id);
+        }
+        ref status => {
+            return Err(format!("Transaction is not awaiting payment: {:?}", status));
+        }
+    }
 
-    let canister_id = ic_cdk::api::id();
-    let allowance = icrc2_client::icrc2_allowance(
-        ckusdc_ledger,
-        assignment.resolver,
-        canister_id,
-    ).await?;
+    settle_resolver_payment(&transaction, &assignment, &bundle).await?;
 
-    let available_allowance: u64 = allowance.allowance.0.try_into()
-        .map_err(|_| "Allowance amount too large".to_string())?;
+    crate::transaction_manager::update_transaction_status(
+        transaction.id,
+        TransactionStatus::AssetsTransferred,
+        caller,
+    )?;
 
-    if available_allowance < assignment.ckusdc_amount {
-        return Err(format!(
-            "Insufficient allowance: resolver has {} but needs {}",
-            available_allowance,
-            assignment.ckusdc_amount
-        ));
+    let token = crate::payments::transaction_payment_token(&transaction)?;
+    let points = crate::payments::points_for(assignment.ckusdc_amount, token.decimals);
+    crate::memory::subtract_points(transaction.user, points);
+
+    Ok(())
+}
+
+// Pulls the resolver's payment into escrow and pays the fee and user proceeds out of it; every leg is a
+// settlement step, so calling this again only finishes what is missing
+pub(crate) async fn settle_resolver_payment(
+    transaction: &Transaction,
+    assignment: &QuoteAssignment,
+    bundle: &BundleConfig,
+) -> Result<(), String> {
+    let token = crate::payments::transaction_payment_token(transaction)?;
+
+    let already_pulled = matches!(
+        settlement::get_step(transaction.id, crate::escrow::PAYMENT_ESCROW_STEP).map(|step| step.state),
+        Some(SettlementStepState::Succeeded { .. })
+    );
+
+    if !already_pulled {
+        let canister_id = ic_cdk::api::canister_self();
+        let allowance = icrc2_client::icrc2_allowance(
+            token.ledger,
+            assignment.resolver,
+            canister_id,
+        ).await?;
+
+        let available_allowance: u64 = allowance.allowance.0.try_into()
+            .map_err(|_| "Allowance amount too large".to_string())?;
+
+        if available_allowance < assignment.ckusdc_amount {
+            return Err(format!(
+                "Insufficient allowance: resolver has {} but needs {}",
+                available_allowance,
+                assignment.ckusdc_amount
+            ));
+        }
     }
 
     let pull_memo = format!(
@@ -45,22 +99,27 @@ pub async fn confirm_resolver_payment_and_complete_sell(request_id: u64) -> Resu
         transaction.id
     ).into_bytes();
 
-    let pull_result = icrc2_client::icrc2_transfer_from(
-        ckusdc_ledger,
+    let pull_result = crate::escrow::escrow_payment(
+        &token,
+        transaction.id,
+        crate::escrow::PAYMENT_ESCROW_STEP,
         assignment.resolver,
-        ic_cdk::api::id(),
         assignment.ckusdc_amount,
-        Some(pull_memo),
+        pull_memo,
     ).await?;
 
     ic_cdk::println!(
-        "Pulled {} ICRC-2 ckUSDC from resolver {} (tx: {})",
+        "Pulled {} {} from resolver {} (tx: {})",
         assignment.ckusdc_amount,
+        token.asset_id,
         assignment.resolver,
         pull_result
     );
 
-    let fees = if assignment.fees > 0 {
+    let ledger_fee = icrc2_client::icrc1_fee(token.ledger).await?;
+
+    // The fee leg pays its own ledger fee out of assignment.fees; dust below one ledger fee stays in escrow
+    if assignment.fees > ledger_fee {
         let treasury = crate::memory::GLOBAL_STATE.with(|state| {
             state.borrow().get().platform_treasury
         });
@@ -72,60 +131,61 @@ pub async fn confirm_resolver_payment_and_complete_sell(request_id: u64) -> Resu
                 bundle.platform_fee_bps.unwrap_or(50)
             ).into_bytes();
 
-            let fee_result = icrc2_client::icrc1_transfer(
-                ckusdc_ledger,
+            let fee_amount = crate::escrow::net_payout(assignment.fees, 0, ledger_fee, 1)?;
+
+            let fee_result = crate::escrow::pay_from_payment_escrow(
+                &token,
+                transaction.id,
+                "fee",
                 treasury_principal,
-                assignment.fees,
-                Some(fee_memo),
+                fee_amount,
+                fee_memo,
             ).await?;
 
             ic_cdk::println!(
-                "Transferred {} ckUSDC platform fee to treasury {} (tx: {})",
-                assignment.fees,
+                "Transferred {} {} platform fee to treasury {} (tx: {})",
+                fee_amount,
+                token.asset_id,
                 treasury_principal,
                 fee_result
             );
-            assignment.fees
         } else {
             ic_cdk::println!(
                 "Warning: Platform fee {} calculated but treasury not configured",
                 assignment.fees
             );
-            0
         }
-    } else {
-        0
-    };
+    }
+
+    let user_proceeds = crate::escrow::net_payout(
+        assignment.ckusdc_amount,
+        assignment.fees,
+        ledger_fee,
+        1,
+    )?;
 
-    let user_proceeds = assignment.ckusdc_amount - fees;
     let user_payment_memo = format!(
         "Sell tx {} - user proceeds",
         transaction.id
     ).into_bytes();
 
-    let user_payment_result = icrc2_client::icrc1_transfer(
-        ckusdc_ledger,
+    let user_payment_result = crate::escrow::pay_from_payment_escrow(
+        &token,
+        transaction.id,
+        PROCEEDS_STEP,
         transaction.user,
         user_proceeds,
-        Some(user_payment_memo),
+        user_payment_memo,
     ).await?;
 
     ic_cdk::println!(
-        "Paid {} ICRC-2 ckUSDC to user {} (tx: {})",
+        "Paid {} {} to user {} (tx: {})",
         user_proceeds,
+        token.asset_id,
         transaction.user,
         user_payment_result
     );
 
-    crate::transaction_manager::update_transaction_status(
-        transaction.id,
-        TransactionStatus::Completed,
-    )?;
-
-    let usdc_amount_e6 = assignment.ckusdc_amount;
-    let points = usdc_amount_e6 / 10_000;
-    crate::memory::subtract_points(transaction.user, points);
-
     Ok(())
 }
 
@@ -139,15 +199,47 @@ pub async fn dissolve_nav_tokens(request_id: u64) -> Result<(), String> {
         return Err("Only assigned resolver can dissolve NAV tokens".to_string());
     }
 
+    let _guard = crate::guard::TransactionGuard::acquire(transaction.id)?;
+
     match transaction.operation {
         OperationType::Sell { .. } => {},
         _ => return Err("This function is only for sell operations".to_string()),
     }
 
-    if transaction.status != TransactionStatus::Completed {
-        return Err("Transaction must be completed before dissolving".to_string());
+    crate::pause_manager::ensure_not_paused(
+        PausableOperation::ResolverConfirmations,
+        Some(transaction.bundle_id),
+    )?;
+
+    if transaction.status != TransactionStatus::AssetsTransferred {
+        return Err("Resolver payment must be confirmed before dissolving".to_string());
     }
 
+    complete_dissolution(&transaction, &assignment).await?;
+
+    crate::transaction_manager::unlock_user_funds(
+        transaction.id,
+        &LockedFundType::NAVTokens { bundle_id: transaction.bundle_id },
+    )?;
+
+    crate::transaction_manager::update_transaction_status(
+        transaction.id,
+        TransactionStatus::Completed,
+        caller,
+    )?;
+
+    ic_cdk::println!(
+        "Dissolved NAV tokens for sell tx {}",
+        transaction.id
+    );
+
+    Ok(())
+}
+// Burns the escrowed NAV tokens and releases the underlying assets to the resolver
+pub(crate) async fn complete_dissolution(
+    transaction: &Transaction,
+    assignment: &QuoteAssignment,
+) -> Result<Vec<(AssetId, u64)>, String> {
     let bundle = crate::bundle_manager::get_bundle(transaction.bundle_id)?;
 
     let (bundle_ledger, bundle_token_id) = bundle.get_token_location()?;
@@ -158,25 +250,38 @@ pub async fn dissolve_nav_tokens(request_id: u64) -> Result<(), String> {
         assignment.nav_tokens
     ).into_bytes();
 
-    let burn_tx_id = icrc151_client::burn_icrc151(
-        bundle_ledger,
-        bundle_token_id,
-        icrc151_client::Account {
-            owner: transaction.user,
-            subaccount: None,
-        },
-        assignment.nav_tokens,
-        Some(burn_memo),
-    ).await?;
+    let burn_call = LedgerCall {
+        kind: LedgerCallKind::Icrc151Burn,
+        ledger: bundle_ledger,
+        token_id: Some(bundle_token_id.clone()),
+        from: Some(ic_cdk::api::canister_self()),
+        from_subaccount: Some(crate::escrow::transaction_subaccount(transaction.id).to_vec()),
+        to: None,
+        to_subaccount: None,
+        amount: assignment.nav_tokens,
+        memo: Some(burn_memo.clone()),
+    };
+
+    let burn_result = settlement::run_ledger_step(transaction.id, BURN_STEP, burn_call, |_| {
+        icrc151_client::burn_icrc151(
+            bundle_ledger,
+            bundle_token_id,
+            crate::escrow::escrow_account(transaction.id),
+            assignment.nav_tokens,
+            Some(burn_memo),
+        )
+    }).await?;
 
     ic_cdk::println!(
-        "Burned {} ICRC-151 bundle tokens from user {} via ledger {} (tx: {})",
+        "Burned {} escrowed ICRC-151 bundle tokens of user {} via ledger {} (tx: {})",
         assignment.nav_tokens,
         transaction.user,
         bundle_ledger,
-        burn_tx_id
+        burn_result.block_index
     );
 
+    let mut released = Vec::new();
+
     for asset_amount in &assignment.asset_amounts {
         let asset = crate::asset_registry::get_asset(asset_amount.asset_id.clone())?;
 
@@ -189,16 +294,40 @@ pub async fn dissolve_nav_tokens(request_id: u64) -> Result<(), String> {
             asset_amount.asset_id
         ).into_bytes();
 
-        let transfer_result = icrc151_client::transfer_icrc151(
+        let transfer_call = LedgerCall {
+            kind: LedgerCallKind::Icrc151Transfer,
             ledger,
-            token_id,
-            icrc151_client::Account {
-                owner: assignment.resolver,
-                subaccount: None,
-            },
-            asset_amount.amount,
-            Some(transfer_memo),
-        ).await?;
+            token_id: Some(token_id.clone()),
+            from: Some(ic_cdk::api::canister_self()),
+            from_subaccount: None,
+            to: Some(assignment.resolver),
+            to_subaccount: None,
+            amount: asset_amount.amount,
+            memo: Some(transfer_memo.clone()),
+        };
+
+        let step = format!("release:{}", asset_amount.asset_id);
+
+        let transfer_result = settlement::run_ledger_step(transaction.id, &step, transfer_call, |created_at_time| {
+            icrc151_client::transfer_icrc151(
+                ledger,
+                token_id,
+                None,
+                icrc151_client::Account {
+                    owner: assignment.resolver,
+                    subaccount: None,
+                },
+                asset_amount.amount,
+                Some(transfer_memo),
+                Some(created_at_time),
+            )
+        }).await?;
+
+        released.push((asset_amount.asset_id.clone(), asset_amount.amount));
+
+        if transfer_result.replayed {
+            continue;
+        }
 
         ic_cdk::println!(
             "Transferred {} ICRC-151 {} to resolver {} via ledger {} (tx: {})",
@@ -206,7 +335,7 @@ pub async fn dissolve_nav_tokens(request_id: u64) -> Result<(), String> {
             asset_amount.asset_id,
             assignment.resolver,
             ledger,
-            transfer_result
+            transfer_result.block_index
         );
 
         crate::holdings_tracker::update_bundle_holdings(
@@ -216,15 +345,5 @@ pub async fn dissolve_nav_tokens(request_id: u64) -> Result<(), String> {
         )?;
     }
 
-    crate::transaction_manager::unlock_user_funds(
-        transaction.id,
-        &LockedFundType::NAVTokens { bundle_id: transaction.bundle_id },
-    )?;
-
-    ic_cdk::println!(
-        "Dissolved NAV tokens for sell tx {}",
-        transaction.id
-    );
-
-    Ok(())
-}
\ No newline at end of file
+    Ok(released)
+}
@@ -0,0 +1,134 @@
+use std::future::Future;
+use ic_cdk::api::time;
+use crate::types::*;
+use crate::memory::*;
+
+pub struct StepResult {
+    pub block_index: u64,
+    pub replayed: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StepAction {
+    Skip { block_index: u64 },
+    Execute { created_at_time: u64 },
+}
+
+fn step_key(transaction_id: u64, step: &str) -> String {
+    format!("{}:{}", transaction_id, step)
+}
+
+pub fn get_step(transaction_id: u64, step: &str) -> Option<SettlementStep> {
+    SETTLEMENT_STEPS.with(|steps| steps.borrow().get(&step_key(transaction_id, step)))
+}
+
+fn save_step(step: SettlementStep) {
+    SETTLEMENT_STEPS.with(|steps| {
+        steps.borrow_mut().insert(step_key(step.transaction_id, &step.step), step)
+    });
+}
+
+pub fn get_settlement_steps(transaction_id: u64) -> Vec<SettlementStep> {
+    let prefix = format!("{}:", transaction_id);
+
+    SETTLEMENT_STEPS.with(|steps| {
+        steps.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, step)| step)
+            .collect()
+    })
+}
+
+// ICRC-151 mint and burn take no created_at_time, so the ledger cannot dedup a retried call
+pub fn ledger_deduplicates(kind: &LedgerCallKind) -> bool {
+    !matches!(kind, LedgerCallKind::Icrc151Mint | LedgerCallKind::Icrc151Burn)
+}
+
+pub fn next_step_action(
+    existing: Option<&SettlementStep>,
+    kind: &LedgerCallKind,
+    now: u64,
+) -> Result<StepAction, String> {
+    let step = match existing {
+        Some(step) => step,
+        None => return Ok(StepAction::Execute { created_at_time: now }),
+    };
+
+    match &step.state {
+        SettlementStepState::Succeeded { block_index } => Ok(StepAction::Skip { block_index: *block_index }),
+        // The ledger answered with an error, so nothing landed and a fresh attempt is safe
+        SettlementStepState::Failed { .. } => Ok(StepAction::Execute { created_at_time: now }),
+        // The call went out but its outcome was never recorded; replay it with the same
+        // created_at_time so the ledger reports a duplicate instead of moving funds twice
+        SettlementStepState::Pending if ledger_deduplicates(kind) => {
+            Ok(StepAction::Execute { created_at_time: step.created_at_time })
+        }
+        SettlementStepState::Pending => Err(format!(
+            "Settlement step {} for transaction {} has an unknown outcome and must be reconciled before retrying",
+            step.step,
+            step.transaction_id
+        )),
+    }
+}
+
+pub async fn run_ledger_step<F, Fut>(
+    transaction_id: u64,
+    step: &str,
+    call: LedgerCall,
+    execute: F,
+) -> Result<StepResult, String>
+where
+    F: FnOnce(u64) -> Fut,
+    Fut: Future<Output = Result<u64, String>>,
+{
+    let existing = get_step(transaction_id, step);
+
+    let created_at_time = match next_step_action(existing.as_ref(), &call.kind, time())? {
+        StepAction::Skip { block_index } => {
+            return Ok(StepResult { block_index, replayed: true });
+        }
+        StepAction::Execute { created_at_time } => created_at_time,
+    };
+
+    let mut checkpoint = SettlementStep {
+        transaction_id,
+        step: step.to_string(),
+        created_at_time,
+        state: SettlementStepState::Pending,
+        attempts: existing.map_or(0, |s| s.attempts) + 1,
+        updated_at: time(),
+    };
+    save_step(checkpoint.clone());
+
+    let result = execute(created_at_time).await;
+
+    crate::transaction_events::record_ledger_call(transaction_id, call, &result);
+
+    checkpoint.state = match &result {
+        Ok(block_index) => SettlementStepState::Succeeded { block_index: *block_index },
+        Err(e) => SettlementStepState::Failed { error: e.clone() },
+    };
+    checkpoint.updated_at = time();
+    save_step(checkpoint);
+
+    result.map(|block_index| StepResult { block_index, replayed: false })
+}
+
+pub fn resolve_pending_step(transaction_id: u64, step: &str, block_index: Option<u64>) -> Result<(), String> {
+    let mut checkpoint = get_step(transaction_id, step)
+        .ok_or_else(|| format!("Settlement step {} not found for transaction {}", step, transaction_id))?;
+
+    if checkpoint.state != SettlementStepState::Pending {
+        return Err(format!("Settlement step {} is not pending: {:?}", step, checkpoint.state));
+    }
+
+    checkpoint.state = match block_index {
+        Some(block_index) => SettlementStepState::Succeeded { block_index },
+        None => SettlementStepState::Failed { error: "Reconciled as not executed".to_string() },
+    };
+    checkpoint.updated_at = time();
+    save_step(checkpoint);
+
+    Ok(())
+}
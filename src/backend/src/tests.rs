@@ -10,9 +10,7 @@ mod tests {
             id: "ckBTC".to_string(),
             symbol: "ckBTC".to_string(),
             name: "Chain Key Bitcoin".to_string(),
-            standard: AssetStandard::ICRC2,
-            ledger_canister: mock_principal(),
-            minter_canister: Some(mock_principal()),
+            token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
             oracle_ticker: Some("BTC".to_string()),
             decimals: 8,
             is_active: true,
@@ -30,9 +28,7 @@ mod tests {
             id: "ckETH".to_string(),
             symbol: "ckETH".to_string(),
             name: "Chain Key Ethereum".to_string(),
-            standard: AssetStandard::ICRC2,
-            ledger_canister: mock_principal(),
-            minter_canister: Some(mock_principal()),
+            token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
             oracle_ticker: Some("ETH".to_string()),
             decimals: 18,
             is_active: true,
@@ -50,9 +46,7 @@ mod tests {
             id: "ckUSDC".to_string(),
             symbol: "ckUSDC".to_string(),
             name: "Chain Key USD Coin".to_string(),
-            standard: AssetStandard::ICRC2,
-            ledger_canister: mock_principal(),
-            minter_canister: Some(mock_principal()),
+            token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
             oracle_ticker: Some("USDC".to_string()),
             decimals: 6,
             is_active: true,
@@ -83,9 +77,7 @@ mod tests {
             id: mock_asset_id(),
             symbol: "ckBTC".to_string(),
             name: "Chain Key Bitcoin".to_string(),
-            standard: AssetStandard::ICRC2,
-            ledger_canister: mock_principal(),
-            minter_canister: Some(mock_principal()),
+            token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
             oracle_ticker: Some("BTC".to_string()),
             decimals: 8,
             is_active: true,
@@ -106,13 +98,14 @@ mod tests {
         assert_eq!(asset_info.symbol, "ckBTC");
         assert_eq!(asset_info.decimals, 8);
         assert!(asset_info.is_active);
-        assert!(matches!(asset_info.standard, AssetStandard::ICRC2));
+        assert!(matches!(asset_info.token_location, TokenLocation::ICRC2 { .. }));
         assert!(matches!(asset_info.metadata.category, AssetCategory::Cryptocurrency));
     }
     #[test]
     fn test_bundle_allocation_validation() {
         let allocation = AssetAllocation {
             asset_id: mock_asset_id(),
+            token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
             percentage: 50,
         };
         assert_eq!(allocation.percentage, 50);
@@ -146,10 +139,10 @@ mod tests {
     }
     #[test]
     fn test_locked_fund_types() {
-        let ckusdc_lock = LockedFundType::CkUSDC;
+        let ckusdc_lock = LockedFundType::payment("ckUSDC");
         let nav_lock = LockedFundType::NAVTokens { bundle_id: 1 };
         match ckusdc_lock {
-            LockedFundType::CkUSDC => assert!(true),
+            LockedFundType::Payment { asset_id } => assert_eq!(asset_id, "ckUSDC"),
             _ => assert!(false),
         }
         match nav_lock {
@@ -160,7 +153,7 @@ mod tests {
     #[test]
     fn test_precise_nav_calculation() {
         let total_value = 1000_00000000u64;
-        let total_tokens = 500u64;
+        let total_tokens = 500_00000000u64;
         let decimals = 8u8;
         let nav_per_token = crate::nav_calculator::calculate_precise_nav_per_token(
             total_value,
@@ -179,7 +172,7 @@ mod tests {
     }
     #[test]
     fn test_operation_types() {
-        let buy_op = OperationType::Buy { nav_tokens: 100 };
+        let buy_op = OperationType::Buy { ckusdc_amount: 100 };
         let sell_op = OperationType::Sell { nav_tokens: 50 };
         let initial_buy_op = OperationType::InitialBuy { usd_amount: 1000, nav_tokens: 500 };
 
@@ -228,23 +221,27 @@ mod tests {
         ).is_err());
     }
     #[test]
-    fn test_quote_request_structure() {
-        let user = mock_principal();
-        let quote_request = QuoteRequest {
-            request_id: 1,
-            user,
+    fn test_quote_object_structure() {
+        let resolver = mock_principal();
+        let quote = QuoteObject {
             bundle_id: 1,
-            operation: OperationType::Buy { nav_tokens: 100 },
-            max_slippage: 5,
-            created_at: 1699000000000000000,
-            expires_at: 1699000300000000000,
+            operation: OperationType::Buy { ckusdc_amount: 100 },
+            resolver,
+            nav_tokens: 50,
+            payment_asset: None,
+            ckusdc_amount: 100,
+            asset_amounts: vec![AssetAmount { asset_id: mock_asset_id(), amount: 1_000 }],
+            fees: 5,
+            valid_until: 1699000300000000000,
+            nonce: 1,
+            coordinator_signature: vec![0u8; 64],
         };
-        assert_eq!(quote_request.request_id, 1);
-        assert_eq!(quote_request.user, user);
-        assert_eq!(quote_request.bundle_id, 1);
-        assert!(matches!(quote_request.operation, OperationType::Buy { .. }));
-        assert_eq!(quote_request.max_slippage, 5);
-        assert!(quote_request.expires_at > quote_request.created_at);
+        assert_eq!(quote.bundle_id, 1);
+        assert_eq!(quote.resolver, resolver);
+        assert!(matches!(quote.operation, OperationType::Buy { .. }));
+        assert_eq!(quote.asset_amounts.len(), 1);
+        assert!(quote.fees < quote.ckusdc_amount);
+        assert_eq!(quote.coordinator_signature.len(), 64);
     }
     #[test]
     fn test_quote_assignment_structure() {
@@ -253,7 +250,9 @@ mod tests {
             request_id: 1,
             resolver,
             nav_tokens: 500_00000000u64,
+            payment_asset: None,
             ckusdc_amount: 1000_00000000u64,
+            asset_amounts: vec![],
             estimated_nav: 2_00000000u64,
             fees: 5_00000000u64,
             assigned_at: 1699000000000000000,
@@ -279,9 +278,10 @@ mod tests {
             user,
             resolver,
             bundle_id: 1,
-            operation: OperationType::Buy { nav_tokens: 500_00000000u64 },
+            operation: OperationType::Buy { ckusdc_amount: 500_00000000u64 },
             status: TransactionStatus::Pending,
             nav_tokens: 500_00000000u64,
+            payment_asset: None,
             ckusdc_amount: 1000_00000000u64,
             created_at: 1699000000000000000,
             updated_at: 1699000000000000000,
@@ -307,14 +307,14 @@ mod tests {
         let locked_funds = LockedFunds {
             user,
             transaction_id: 1,
-            fund_type: LockedFundType::CkUSDC,
+            fund_type: LockedFundType::payment("ckUSDC"),
             amount: 1000_00000000u64,
             locked_at: 1699000000000000000,
             expires_at: 1699001800000000000,
         };
         assert_eq!(locked_funds.user, user);
         assert_eq!(locked_funds.transaction_id, 1);
-        assert!(matches!(locked_funds.fund_type, LockedFundType::CkUSDC));
+        assert!(matches!(locked_funds.fund_type, LockedFundType::Payment { .. }));
         assert_eq!(locked_funds.amount, 1000_00000000u64);
         assert!(locked_funds.expires_at > locked_funds.locked_at);
         let nav_locked_funds = LockedFunds {
@@ -433,9 +433,10 @@ mod tests {
             user: mock_principal(),
             resolver: mock_principal(),
             bundle_id: 1,
-            operation: OperationType::Buy { nav_tokens: 100_00000000u64 },
+            operation: OperationType::Buy { ckusdc_amount: 100_00000000u64 },
             status: TransactionStatus::FundsLocked,
             nav_tokens: 100_00000000u64,
+            payment_asset: None,
             ckusdc_amount: 200_00000000u64,
             created_at: base_time,
             updated_at: base_time,
@@ -458,13 +459,13 @@ mod tests {
         let insufficient_lock = LockedFunds {
             user,
             transaction_id: 1,
-            fund_type: LockedFundType::CkUSDC,
+            fund_type: LockedFundType::payment("ckUSDC"),
             amount: 1000_00000000u64,
             locked_at: 1699000000000000000,
             expires_at: 1699001800000000000,
         };
         assert_eq!(insufficient_lock.amount, 1000_00000000u64);
-        assert!(matches!(insufficient_lock.fund_type, LockedFundType::CkUSDC));
+        assert!(matches!(insufficient_lock.fund_type, LockedFundType::Payment { .. }));
         let insufficient_nav_lock = LockedFunds {
             user,
             transaction_id: 2,
@@ -532,9 +533,10 @@ mod tests {
             user,
             resolver: mock_principal(),
             bundle_id: 1,
-            operation: OperationType::Buy { nav_tokens: 100_00000000u64 },
+            operation: OperationType::Buy { ckusdc_amount: 100_00000000u64 },
             status: TransactionStatus::Failed,
             nav_tokens: 100_00000000u64,
+            payment_asset: None,
             ckusdc_amount: 200_00000000u64,
             created_at: base_time,
             updated_at: base_time + 300_000_000_000u64,
@@ -547,7 +549,7 @@ mod tests {
         let locked_funds_to_unlock = LockedFunds {
             user: failed_transaction.user,
             transaction_id: failed_transaction.id,
-            fund_type: LockedFundType::CkUSDC,
+            fund_type: LockedFundType::payment("ckUSDC"),
             amount: failed_transaction.ckusdc_amount,
             locked_at: failed_transaction.created_at,
             expires_at: failed_transaction.timeout_at,
@@ -559,14 +561,18 @@ mod tests {
     fn test_quote_expiration_handling() {
         let base_time = 1699000000000000000u64;
         let quote_duration = 300_000_000_000u64;
-        let quote_request = QuoteRequest {
-            request_id: 1,
-            user: mock_principal(),
+        let quote = QuoteObject {
             bundle_id: 1,
-            operation: OperationType::Buy { nav_tokens: 200_00000000u64 },
-            max_slippage: 5,
-            created_at: base_time,
-            expires_at: base_time + quote_duration,
+            operation: OperationType::Buy { ckusdc_amount: 200_00000000u64 },
+            resolver: mock_principal(),
+            nav_tokens: 100_00000000u64,
+            payment_asset: None,
+            ckusdc_amount: 200_00000000u64,
+            asset_amounts: vec![],
+            fees: 0,
+            valid_until: base_time + quote_duration,
+            nonce: 1,
+            coordinator_signature: vec![],
         };
         let time_scenarios = vec![
             (base_time + 120_000_000_000u64, false),
@@ -575,7 +581,7 @@ mod tests {
             (base_time + 600_000_000_000u64, true),
         ];
         for (current_time, should_be_expired) in time_scenarios {
-            let is_expired = current_time > quote_request.expires_at;
+            let is_expired = current_time > quote.valid_until;
             assert_eq!(is_expired, should_be_expired,
                 "Quote should {} at time offset {}ns",
                 if should_be_expired { "be expired" } else { "not be expired" },
@@ -598,7 +604,7 @@ mod tests {
         assert_eq!(nav_zero_value, 0u64);
         let nav_small = crate::nav_calculator::calculate_precise_nav_per_token(
             1u64,
-            1u64,
+            1_00000000u64,
             8u8
         );
         assert_eq!(nav_small, 1u64);
@@ -649,7 +655,7 @@ mod tests {
     #[test]
     fn test_nav_per_token_calculation_precision() {
         let total_usd_value = 1000_00000000u64;
-        let total_tokens = 1000u64;
+        let total_tokens = 1000_00000000u64;
         let precision_decimals = 8u8;
         let nav_per_token = crate::nav_calculator::calculate_precise_nav_per_token(
             total_usd_value,
@@ -659,7 +665,7 @@ mod tests {
         assert_eq!(nav_per_token, 100_000_000u64, "NAV should be $1.00 per token with 8 decimals");
         let nav_per_token_2 = crate::nav_calculator::calculate_precise_nav_per_token(
             500_00000000u64,
-            100u64,
+            100_00000000u64,
             precision_decimals,
         );
         assert_eq!(nav_per_token_2, 500_000_000u64, "NAV should be $5.00 per token with 8 decimals");
@@ -679,7 +685,7 @@ mod tests {
     #[test]
     fn test_nav_calculation_overflow_protection() {
         let large_usd_value = u64::MAX / 1_000_000;
-        let total_tokens = 1u64;
+        let total_tokens = 1_00000000u64;
         let precision_decimals = 8u8;
         let nav_result = crate::nav_calculator::calculate_nav_per_token_with_supply_validation(
             large_usd_value,
@@ -918,10 +924,12 @@ mod tests {
         let test_allocations = vec![
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 60u8,
             },
             AssetAllocation {
                 asset_id: "ckETH".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 40u8,
             },
         ];
@@ -930,10 +938,12 @@ mod tests {
         let invalid_allocations = vec![
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 60u8,
             },
             AssetAllocation {
                 asset_id: "ckETH".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 50u8,
             },
         ];
@@ -981,7 +991,7 @@ mod tests {
         ];
         for asset_id in &asset_ids {
             let _cloned_id = asset_id.clone();
-            assert_eq!(asset_id, _cloned_id, "Asset ID cloning should be consistent");
+            assert_eq!(asset_id, &_cloned_id, "Asset ID cloning should be consistent");
         }
         let bundle_ids = vec![1u64, 2u64, 3u64];
         for bundle_id in &bundle_ids {
@@ -1042,10 +1052,12 @@ mod tests {
         let allocations = vec![
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 60,
             },
             AssetAllocation {
                 asset_id: "ckETH".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 40,
             },
         ];
@@ -1054,10 +1066,12 @@ mod tests {
         let invalid_percentage_allocations = vec![
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 60,
             },
             AssetAllocation {
                 asset_id: "ckETH".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 50,
             },
         ];
@@ -1067,10 +1081,12 @@ mod tests {
         let duplicate_asset_allocations = vec![
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 50,
             },
             AssetAllocation {
                 asset_id: "ckBTC".to_string(),
+                token_location: TokenLocation::ICRC2 { ledger: mock_principal() },
                 percentage: 50,
             },
         ];
@@ -1104,7 +1120,7 @@ mod tests {
         assert_eq!(eth_value, 1000_00_000_000u64, "0.25 ETH at $4k = $1k");
         let total_usd_value = btc_value + eth_value;
         assert_eq!(total_usd_value, 101_000_00_000_000u64, "Total portfolio = $101k");
-        let total_nav_tokens = 1000u64;
+        let total_nav_tokens = 1000_00000000u64;
         let nav_per_token = crate::nav_calculator::calculate_precise_nav_per_token(
             total_usd_value,
             total_nav_tokens,
@@ -1113,9 +1129,9 @@ mod tests {
         assert_eq!(nav_per_token, 10_100_000_000u64, "NAV should be $101.00 per token");
         let formatted_nav = crate::nav_calculator::format_nav_with_precision(nav_per_token, 8);
         assert_eq!(formatted_nav, "101.00000000", "Formatted NAV should display as $101.00000000");
-        let user_nav_tokens = 100u64;
+        let user_nav_tokens = 100_00000000u64;
         let user_portfolio_value_raw = user_nav_tokens as u128 * nav_per_token as u128;
-        let user_portfolio_value_usd = user_portfolio_value_raw;
+        let user_portfolio_value_usd = user_portfolio_value_raw / 100_000_000u128;
         assert_eq!(user_portfolio_value_usd, 1_010_000_000_000u128, "User should own $10.1k worth of assets (10% of $101k)");
         let user_btc_proportion = (user_nav_tokens as u128 * btc_holding_amount as u128) / total_nav_tokens as u128;
         let user_eth_proportion = (user_nav_tokens as u128 * eth_holding_amount as u128) / total_nav_tokens as u128;
@@ -1125,18 +1141,1318 @@ mod tests {
     #[test]
     fn test_nav_calculation_edge_cases_mathematical() {
         let tiny_value = 1u64;
-        let many_tokens = 1000000u64;
+        let many_tokens = 1_000_000_00000000u64;
         let tiny_nav = crate::nav_calculator::calculate_precise_nav_per_token(tiny_value, many_tokens, 8);
         assert_eq!(tiny_nav, 0u64, "Very small value should result in zero NAV due to rounding");
         let large_value = 1000000_00000000u64;
-        let single_token = 1u64;
+        let single_token = 1_00000000u64;
         let large_nav = crate::nav_calculator::calculate_precise_nav_per_token(large_value, single_token, 8);
         assert_eq!(large_nav, 1000000_00000000u64, "Single token worth $1M should have $1M NAV");
         let value = 1000_00000000u64;
-        let tokens = 3u64;
+        let tokens = 3_00000000u64;
         let nav_8_decimals = crate::nav_calculator::calculate_precise_nav_per_token(value, tokens, 8);
         let nav_6_decimals = crate::nav_calculator::calculate_precise_nav_per_token(value, tokens, 6);
         assert_eq!(nav_8_decimals, 333_33333333u64, "8-decimal precision should be 333.33333333");
         assert_eq!(nav_6_decimals, 333_333333u64, "6-decimal precision should be 333.333333");
     }
+    #[test]
+    fn test_pause_state_blocks_operations() {
+        use crate::pause_manager::check_not_paused;
+        let mut state = PauseState::default();
+        assert!(check_not_paused(&state, PausableOperation::NewBuys, Some(1)).is_ok());
+        state.set_paused(&PausableOperation::NewBuys, true);
+        assert_eq!(
+            check_not_paused(&state, PausableOperation::NewBuys, Some(1)),
+            Err(PauseError::OperationPaused { operation: PausableOperation::NewBuys })
+        );
+        assert!(check_not_paused(&state, PausableOperation::NewSells, Some(1)).is_ok());
+        assert!(check_not_paused(&state, PausableOperation::ResolverConfirmations, Some(1)).is_ok());
+        state.set_paused(&PausableOperation::NewBuys, false);
+        assert!(check_not_paused(&state, PausableOperation::NewBuys, None).is_ok());
+    }
+    #[test]
+    fn test_pause_state_blocks_bundles() {
+        use crate::pause_manager::check_not_paused;
+        let state = PauseState { paused_bundles: vec![7], ..Default::default() };
+        assert_eq!(
+            check_not_paused(&state, PausableOperation::ResolverConfirmations, Some(7)),
+            Err(PauseError::BundlePaused { bundle_id: 7 })
+        );
+        assert!(check_not_paused(&state, PausableOperation::ResolverConfirmations, Some(8)).is_ok());
+        assert!(check_not_paused(&state, PausableOperation::BundleCreation, None).is_ok());
+        let error: String = PauseError::BundlePaused { bundle_id: 7 }.into();
+        assert!(error.contains("Bundle paused"));
+    }
+    #[test]
+    fn test_pause_operation_mapping() {
+        use crate::pause_manager::operation_for;
+        assert_eq!(operation_for(&OperationType::InitialBuy { usd_amount: 1, nav_tokens: 1 }), PausableOperation::InitialFunding);
+        assert_eq!(operation_for(&OperationType::Buy { ckusdc_amount: 1 }), PausableOperation::NewBuys);
+        assert_eq!(operation_for(&OperationType::Sell { nav_tokens: 1 }), PausableOperation::NewSells);
+    }
+    #[test]
+    fn test_transaction_transition_table() {
+        use crate::transaction_manager::{is_valid_transition, SETTLEMENT_PATH};
+        for step in SETTLEMENT_PATH.windows(2) {
+            assert!(is_valid_transition(&step[0], &step[1]), "{:?} -> {:?} should be allowed", step[0], step[1]);
+            assert!(!is_valid_transition(&step[1], &step[0]), "{:?} -> {:?} should be rejected", step[1], step[0]);
+        }
+        assert!(!is_valid_transition(&TransactionStatus::Pending, &TransactionStatus::Completed));
+        assert!(!is_valid_transition(&TransactionStatus::FundsLocked, &TransactionStatus::InProgress));
+        assert!(!is_valid_transition(&TransactionStatus::InProgress, &TransactionStatus::InProgress));
+        assert!(is_valid_transition(&TransactionStatus::InProgress, &TransactionStatus::Failed));
+        assert!(is_valid_transition(&TransactionStatus::WaitingForResolver, &TransactionStatus::TimedOut));
+        for terminal in [TransactionStatus::Completed, TransactionStatus::Failed, TransactionStatus::TimedOut] {
+            assert!(!is_valid_transition(&terminal, &TransactionStatus::Pending));
+            assert!(!is_valid_transition(&terminal, &TransactionStatus::Failed));
+        }
+    }
+    #[test]
+    fn test_furthest_settlement_status() {
+        use crate::transaction_manager::furthest_settlement_status;
+        let transition = |from: TransactionStatus, to: TransactionStatus| TransactionTransition {
+            transaction_id: 1,
+            sequence: 0,
+            from,
+            to,
+            actor: mock_principal(),
+            timestamp: 0,
+        };
+        assert_eq!(furthest_settlement_status(&[]), TransactionStatus::Pending);
+        let transitions = vec![
+            transition(TransactionStatus::Pending, TransactionStatus::FundsLocked),
+            transition(TransactionStatus::FundsLocked, TransactionStatus::WaitingForResolver),
+            transition(TransactionStatus::WaitingForResolver, TransactionStatus::InProgress),
+            transition(TransactionStatus::InProgress, TransactionStatus::TimedOut),
+        ];
+        assert_eq!(furthest_settlement_status(&transitions), TransactionStatus::InProgress);
+    }
+    #[test]
+    fn test_settlement_step_replay_rules() {
+        use crate::settlement::{next_step_action, StepAction};
+        let step = |state: SettlementStepState| SettlementStep {
+            transaction_id: 9,
+            step: "payment".to_string(),
+            created_at_time: 1_000,
+            state,
+            attempts: 1,
+            updated_at: 1_000,
+        };
+        assert_eq!(
+            next_step_action(None, &LedgerCallKind::Icrc1Transfer, 5_000),
+            Ok(StepAction::Execute { created_at_time: 5_000 })
+        );
+        let succeeded = step(SettlementStepState::Succeeded { block_index: 42 });
+        assert_eq!(
+            next_step_action(Some(&succeeded), &LedgerCallKind::Icrc151Mint, 5_000),
+            Ok(StepAction::Skip { block_index: 42 })
+        );
+        let failed = step(SettlementStepState::Failed { error: "TemporarilyUnavailable".to_string() });
+        assert_eq!(
+            next_step_action(Some(&failed), &LedgerCallKind::Icrc1Transfer, 5_000),
+            Ok(StepAction::Execute { created_at_time: 5_000 })
+        );
+        let pending = step(SettlementStepState::Pending);
+        assert_eq!(
+            next_step_action(Some(&pending), &LedgerCallKind::Icrc151TransferFrom, 5_000),
+            Ok(StepAction::Execute { created_at_time: 1_000 }),
+            "Uncertain steps on deduplicating ledgers replay with the original created_at_time"
+        );
+        assert!(next_step_action(Some(&pending), &LedgerCallKind::Icrc151Mint, 5_000).is_err());
+        assert!(next_step_action(Some(&pending), &LedgerCallKind::Icrc151Burn, 5_000).is_err());
+    }
+    #[test]
+    fn test_settlement_guards_block_concurrent_calls() {
+        use crate::guard::{TransactionGuard, UserGuard};
+        let guard = TransactionGuard::acquire(77).expect("First caller should get the guard");
+        let second = TransactionGuard::acquire(77);
+        assert!(second.is_err());
+        assert!(second.err().unwrap().contains("Settlement in progress"));
+        assert!(TransactionGuard::acquire(78).is_ok(), "Other transactions are not blocked");
+        drop(guard);
+        assert!(TransactionGuard::acquire(77).is_ok(), "Guard is released on drop");
+        let user = mock_principal();
+        let user_guard = UserGuard::acquire(user).unwrap();
+        assert!(UserGuard::acquire(user).is_err());
+        drop(user_guard);
+        assert!(UserGuard::acquire(user).is_ok());
+    }
+    #[test]
+    fn test_settlement_guard_released_on_error() {
+        use crate::guard::TransactionGuard;
+        fn failing_settlement() -> Result<(), String> {
+            let _guard = TransactionGuard::acquire(91)?;
+            Err("Ledger unavailable".to_string())
+        }
+        assert!(failing_settlement().is_err());
+        assert!(TransactionGuard::acquire(91).is_ok(), "Guard must not leak when settlement fails");
+    }
+    #[test]
+    fn test_transaction_escrow_subaccounts() {
+        use crate::escrow::transaction_subaccount;
+        let first = transaction_subaccount(1);
+        assert_eq!(first, transaction_subaccount(1), "Subaccount derivation must be deterministic");
+        assert_ne!(first, transaction_subaccount(2));
+        assert_ne!(transaction_subaccount(0), [0u8; 32], "Escrow must never be the default subaccount");
+        assert_eq!(&transaction_subaccount(258)[24..], &258u64.to_be_bytes());
+    }
+    #[test]
+    fn test_escrow_net_payout() {
+        use crate::escrow::net_payout;
+        assert_eq!(net_payout(1_000_000, 5_000, 10_000, 1), Ok(985_000));
+        assert_eq!(net_payout(1_000_000, 0, 10_000, 2), Ok(980_000));
+        assert!(net_payout(15_000, 5_000, 10_000, 1).is_err(), "Payout must not be zero");
+        assert!(net_payout(5_000, 10_000, 10_000, 1).is_err(), "Payout must not underflow");
+    }
+    #[test]
+    fn test_buy_recovery_action() {
+        use crate::error_recovery::{buy_recovery_action, BuyRecoveryAction};
+        use crate::transaction_manager::is_recoverable;
+        let paid = SettlementStepState::Succeeded { block_index: 7 };
+        let failed = SettlementStepState::Failed { error: "Insufficient allowance".to_string() };
+        let pending = SettlementStepState::Pending;
+
+        assert_eq!(buy_recovery_action(&[], None), BuyRecoveryAction::RefundPayment);
+        assert_eq!(buy_recovery_action(std::slice::from_ref(&failed), None), BuyRecoveryAction::RefundPayment);
+        assert_eq!(buy_recovery_action(&[paid.clone(), failed.clone()], None), BuyRecoveryAction::UnwindCollateral);
+        assert_eq!(buy_recovery_action(std::slice::from_ref(&pending), None), BuyRecoveryAction::UnwindCollateral);
+        assert_eq!(buy_recovery_action(std::slice::from_ref(&paid), Some(&failed)), BuyRecoveryAction::UnwindCollateral);
+        assert_eq!(buy_recovery_action(std::slice::from_ref(&paid), Some(&pending)), BuyRecoveryAction::CompleteSettlement,
+            "A mint with an unknown outcome may have landed, so the collateral must stay");
+        assert_eq!(buy_recovery_action(&[paid], Some(&SettlementStepState::Succeeded { block_index: 9 })), BuyRecoveryAction::CompleteSettlement);
+
+        let buy = |status| Transaction { operation: OperationType::Buy { ckusdc_amount: 1_000_000 }, status, ..mock_sell_transaction() };
+        assert!(is_recoverable(&buy(TransactionStatus::InProgress)));
+        assert!(is_recoverable(&buy(TransactionStatus::AssetsTransferred)));
+        assert!(!is_recoverable(&buy(TransactionStatus::Completed)));
+        assert!(!is_recoverable(&buy(TransactionStatus::TimedOut)));
+    }
+    #[test]
+    fn test_sell_recovery_action() {
+        use crate::error_recovery::{sell_recovery_action, SellRecoveryAction};
+        let paid = SettlementStepState::Succeeded { block_index: 7 };
+        let failed = SettlementStepState::Failed { error: "Insufficient funds".to_string() };
+        let pending = SettlementStepState::Pending;
+
+        assert_eq!(sell_recovery_action(None, None, None), SellRecoveryAction::ReturnNavTokens);
+        assert_eq!(sell_recovery_action(Some(&failed), None, None), SellRecoveryAction::ReturnNavTokens);
+        assert_eq!(sell_recovery_action(Some(&paid), None, None), SellRecoveryAction::RefundResolver);
+        assert_eq!(sell_recovery_action(Some(&pending), None, None), SellRecoveryAction::RefundResolver);
+        assert_eq!(sell_recovery_action(Some(&paid), Some(&failed), None), SellRecoveryAction::RefundResolver);
+        assert_eq!(sell_recovery_action(Some(&paid), Some(&pending), None), SellRecoveryAction::CompleteDissolution,
+            "A user payment with unknown outcome must never be unwound");
+        assert_eq!(sell_recovery_action(Some(&paid), Some(&paid), None), SellRecoveryAction::CompleteDissolution);
+        assert_eq!(sell_recovery_action(Some(&paid), Some(&paid), Some(&pending)), SellRecoveryAction::CompleteDissolution);
+    }
+    fn mock_sell_transaction() -> Transaction {
+        Transaction {
+            id: 5,
+            request_id: 5,
+            user: mock_principal(),
+            resolver: mock_principal(),
+            bundle_id: 1,
+            operation: OperationType::Sell { nav_tokens: 100 },
+            status: TransactionStatus::TimedOut,
+            nav_tokens: 100,
+            payment_asset: None,
+            ckusdc_amount: 1_000_000,
+            created_at: 0,
+            updated_at: 0,
+            completed_at: None,
+            timeout_at: 0,
+        }
+    }
+    #[test]
+    fn test_recovery_log_entry_results() {
+        use crate::error_recovery::recovery_log_entry;
+        let tx = mock_sell_transaction();
+
+        let success = recovery_log_entry(0, &tx, 10, 20, &Ok(RecoveryOutcome::NavTokensReturned { amount: 100 }), 1);
+        assert_eq!(success.result, RecoveryResult::Success);
+        assert_eq!(success.nav_tokens_moved, 100);
+        assert_eq!(success.ckusdc_moved, 0);
+
+        let partial = recovery_log_entry(1, &tx, 10, 20, &Err("Ledger unavailable".to_string()), 1);
+        assert_eq!(partial.result, RecoveryResult::Partial { reason: "Ledger unavailable".to_string() });
+        assert!(partial.outcome.is_none());
+
+        let failed = recovery_log_entry(2, &tx, 10, 20, &Err("Ledger unavailable".to_string()), 0);
+        assert_eq!(failed.result, RecoveryResult::Failed { reason: "Ledger unavailable".to_string() });
+    }
+    #[test]
+    fn test_recovery_statistics_from_log() {
+        use crate::error_recovery::{compute_recovery_statistics, recovery_log_entry, tally_recovery_log};
+        let tx = mock_sell_transaction();
+        let other = Transaction { id: 6, ..mock_sell_transaction() };
+        let third = Transaction { id: 7, ..mock_sell_transaction() };
+
+        let empty = compute_recovery_statistics(&tally_recovery_log(&[]), 0);
+        assert_eq!(empty.total_recoveries, 0);
+        assert_eq!(empty.success_rate, 0.0);
+
+        let mut entries = vec![
+            recovery_log_entry(0, &tx, 0, 4_000_000, &Err("Timeout".to_string()), 0),
+            recovery_log_entry(1, &other, 0, 2_000_000, &Err("Timeout".to_string()), 1),
+        ];
+        for id in 2..50 {
+            entries.push(recovery_log_entry(id, &third, 0, 1_000_000, &Err("Ledger unavailable".to_string()), 0));
+        }
+        entries.push(recovery_log_entry(50, &tx, 0, 2_000_000, &Ok(RecoveryOutcome::CkUSDCRefunded { amount: 5 }), 1));
+
+        let counters = tally_recovery_log(&entries);
+        assert_eq!(counters.transactions, 3, "Each transaction counts once");
+        assert_eq!(counters.successful, 1, "Only the latest attempt counts");
+        assert_eq!(counters.partial, 1);
+        assert_eq!(counters.failed, 1, "Repeated failures of one transaction count once");
+        assert_eq!(counters.total_duration_ns, 5_000_000);
+
+        let stats = compute_recovery_statistics(&counters, 2);
+        assert_eq!(stats.total_recoveries, 3);
+        assert_eq!(stats.recoveries_last_24h, 2);
+        assert_eq!(stats.successful_recoveries, 1);
+        assert_eq!(stats.partial_recoveries, 1);
+        assert_eq!(stats.failed_recoveries, 1);
+        assert!((stats.success_rate - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.average_recovery_time_ms, 1);
+    }
+    #[test]
+    fn test_automatic_recovery_attempts_are_capped() {
+        use crate::error_recovery::{failed_attempts, get_transaction_recovery_log, recovery_log_entry, MAX_RETRY_ATTEMPTS};
+        let tx = mock_sell_transaction();
+        let store = |id: u64, result: &Result<RecoveryOutcome, String>| {
+            let entry = recovery_log_entry(id, &tx, 0, 1, result, 0);
+            crate::memory::RECOVERY_LOG.with(|log| log.borrow_mut().insert(id, entry));
+            crate::memory::RECOVERY_LOG_BY_TRANSACTION.with(|index| index.borrow_mut().insert((tx.id, id), ()));
+        };
+
+        assert_eq!(failed_attempts(tx.id), 0);
+        store(0, &Err("Ledger unavailable".to_string()));
+        store(1, &Ok(RecoveryOutcome::NothingToRecover));
+        store(2, &Err("Ledger unavailable".to_string()));
+        assert_eq!(failed_attempts(tx.id), 1, "Attempts before a success don't count");
+
+        for id in 3..10 {
+            store(id, &Err("Ledger unavailable".to_string()));
+        }
+        assert_eq!(failed_attempts(tx.id), MAX_RETRY_ATTEMPTS, "Counting stops at the cap");
+
+        let log = get_transaction_recovery_log(tx.id);
+        assert_eq!(log.len(), 10);
+        assert!(log.windows(2).all(|pair| pair[0].id < pair[1].id), "Entries come back oldest first");
+        assert!(get_transaction_recovery_log(tx.id + 1).is_empty());
+    }
+    fn assign_test_roles(principal: Principal, roles: Vec<Role>) {
+        crate::memory::ROLE_ASSIGNMENTS.with(|assignments| {
+            assignments.borrow_mut().insert(principal, RoleAssignment {
+                principal,
+                roles,
+                updated_at: 0,
+                updated_by: principal,
+            });
+        });
+    }
+    #[test]
+    fn test_lock_endpoints_reject_anonymous_and_unrelated_callers() {
+        use crate::authorization::*;
+        let owner = Principal::from_text("aaaaa-aa").unwrap();
+        let user = mock_principal();
+        let resolver = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+        let stranger = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        assign_test_roles(owner, vec![Role::Owner]);
+        let parties = TransactionParties { user: Some(user), resolver: Some(resolver) };
+
+        for policy in [LOCK_FUNDS_PARTIES, UNLOCK_FUNDS_PARTIES, EXTEND_LOCK_PARTIES] {
+            assert!(authorize(Principal::anonymous(), policy, &parties).is_err());
+            assert!(authorize(stranger, policy, &parties).is_err());
+            assert!(authorize(owner, policy, &parties).is_ok(), "Owner must pass every lock policy");
+        }
+
+        assert!(authorize(user, LOCK_FUNDS_PARTIES, &parties).is_ok());
+        assert!(authorize(resolver, LOCK_FUNDS_PARTIES, &parties).is_err());
+        assert!(authorize(user, UNLOCK_FUNDS_PARTIES, &parties).is_err(), "Users must not release their own locks");
+        assert!(authorize(resolver, UNLOCK_FUNDS_PARTIES, &parties).is_err());
+        assert!(authorize(resolver, EXTEND_LOCK_PARTIES, &parties).is_ok());
+        assert!(authorize(user, EXTEND_LOCK_PARTIES, &parties).is_err());
+    }
+    #[test]
+    fn test_authorization_without_configured_parties() {
+        use crate::authorization::*;
+        let user = mock_principal();
+        let unset = TransactionParties::default();
+        assert!(authorize(user, UNLOCK_FUNDS_PARTIES, &unset).is_err(), "No role must not grant access");
+        assign_test_roles(Principal::anonymous(), vec![Role::Owner]);
+        assert!(authorize(Principal::anonymous(), &[Party::Role(Role::Owner)], &unset).is_err(),
+            "Anonymous must be rejected even if assigned a role");
+    }
+    #[test]
+    fn test_emergency_recovery_authorization() {
+        use crate::authorization::authorize_recovery_for;
+        let operator = Principal::from_text("aaaaa-aa").unwrap();
+        let user = mock_principal();
+        let stranger = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        assign_test_roles(operator, vec![Role::Operator]);
+
+        assert!(authorize_recovery_for(user, user).is_ok());
+        assert!(authorize_recovery_for(operator, user).is_ok());
+        assert!(authorize_recovery_for(stranger, user).is_err());
+        assert!(authorize_recovery_for(Principal::anonymous(), user).is_err());
+        assert!(authorize_recovery_for(Principal::anonymous(), Principal::anonymous()).is_err());
+    }
+    #[test]
+    fn test_granted_roles_manage_locks_and_recovery() {
+        use crate::authorization::*;
+        let owner = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+        let operator = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let pauser = Principal::from_text("aaaaa-aa").unwrap();
+        let user = mock_principal();
+        let parties = TransactionParties { user: Some(user), resolver: None };
+
+        assert!(crate::memory::get_admin_principal().is_none(), "Roles must not depend on the admin principal");
+        assert!(authorize(owner, UNLOCK_FUNDS_PARTIES, &parties).is_err());
+        assert!(authorize_recovery_for(operator, user).is_err());
+
+        assign_test_roles(owner, vec![Role::Owner]);
+        assign_test_roles(operator, vec![Role::Operator]);
+        assign_test_roles(pauser, vec![Role::Pauser]);
+
+        assert!(authorize(owner, UNLOCK_FUNDS_PARTIES, &parties).is_ok(), "Granted Owner must unlock funds");
+        assert!(authorize_recovery_for(owner, user).is_ok(), "Owner satisfies Operator");
+        assert!(authorize_recovery_for(operator, user).is_ok(), "Granted Operator must recover for users");
+        assert!(authorize(operator, UNLOCK_FUNDS_PARTIES, &parties).is_err(), "Lock management is Owner-only");
+        assert!(authorize_recovery_for(pauser, user).is_err());
+    }
+    struct LockEndpointParties {
+        owner: Principal,
+        operator: Principal,
+        user: Principal,
+        resolver: Principal,
+        stranger: Principal,
+    }
+    // Stores a buy transaction with its resolver assignment and grants the admin roles, as the endpoints see them
+    fn store_lock_endpoint_transaction() -> (u64, LockEndpointParties) {
+        let parties = LockEndpointParties {
+            owner: Principal::from_text("aaaaa-aa").unwrap(),
+            operator: Principal::from_text("qoctq-giaaa-aaaaa-aaaea-cai").unwrap(),
+            user: mock_principal(),
+            resolver: Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap(),
+            stranger: Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap(),
+        };
+        let transaction = Transaction {
+            resolver: parties.resolver,
+            operation: OperationType::Buy { ckusdc_amount: 1_000_000 },
+            status: TransactionStatus::WaitingForResolver,
+            ..mock_sell_transaction()
+        };
+        let assignment = QuoteAssignment {
+            request_id: transaction.request_id,
+            resolver: parties.resolver,
+            nav_tokens: transaction.nav_tokens,
+            payment_asset: None,
+            ckusdc_amount: transaction.ckusdc_amount,
+            asset_amounts: vec![],
+            estimated_nav: 100,
+            fees: 0,
+            valid_until: 0,
+            assigned_at: 0,
+        };
+
+        crate::memory::TRANSACTIONS.with(|t| t.borrow_mut().insert(transaction.id, transaction.clone()));
+        crate::memory::QUOTE_ASSIGNMENTS.with(|a| a.borrow_mut().insert(assignment.request_id, assignment));
+        assign_test_roles(parties.owner, vec![Role::Owner]);
+        assign_test_roles(parties.operator, vec![Role::Operator]);
+
+        (transaction.id, parties)
+    }
+    #[test]
+    fn test_lock_user_funds_with_validation_access() {
+        use crate::authorization::{authorize_transaction_party, LOCK_FUNDS_PARTIES};
+        let (tx_id, p) = store_lock_endpoint_transaction();
+
+        assert!(authorize_transaction_party(p.user, tx_id, LOCK_FUNDS_PARTIES).is_ok());
+        assert!(authorize_transaction_party(p.owner, tx_id, LOCK_FUNDS_PARTIES).is_ok());
+        assert!(authorize_transaction_party(p.resolver, tx_id, LOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.operator, tx_id, LOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.stranger, tx_id, LOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(Principal::anonymous(), tx_id, LOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.user, tx_id + 1, LOCK_FUNDS_PARTIES).is_err(), "Unknown transactions must be rejected");
+    }
+    #[test]
+    fn test_unlock_all_transaction_funds_access() {
+        use crate::authorization::{authorize_transaction_party, UNLOCK_FUNDS_PARTIES};
+        let (tx_id, p) = store_lock_endpoint_transaction();
+
+        assert!(authorize_transaction_party(p.owner, tx_id, UNLOCK_FUNDS_PARTIES).is_ok());
+        assert!(authorize_transaction_party(p.user, tx_id, UNLOCK_FUNDS_PARTIES).is_err(), "Users must not release their own locks");
+        assert!(authorize_transaction_party(p.resolver, tx_id, UNLOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.operator, tx_id, UNLOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.stranger, tx_id, UNLOCK_FUNDS_PARTIES).is_err());
+        assert!(authorize_transaction_party(Principal::anonymous(), tx_id, UNLOCK_FUNDS_PARTIES).is_err());
+    }
+    #[test]
+    fn test_extend_lock_expiration_access() {
+        use crate::authorization::{authorize_transaction_party, EXTEND_LOCK_PARTIES};
+        let (tx_id, p) = store_lock_endpoint_transaction();
+
+        assert!(authorize_transaction_party(p.resolver, tx_id, EXTEND_LOCK_PARTIES).is_ok());
+        assert!(authorize_transaction_party(p.owner, tx_id, EXTEND_LOCK_PARTIES).is_ok());
+        assert!(authorize_transaction_party(p.user, tx_id, EXTEND_LOCK_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.operator, tx_id, EXTEND_LOCK_PARTIES).is_err());
+        assert!(authorize_transaction_party(p.stranger, tx_id, EXTEND_LOCK_PARTIES).is_err());
+        assert!(authorize_transaction_party(Principal::anonymous(), tx_id, EXTEND_LOCK_PARTIES).is_err());
+    }
+    #[test]
+    fn test_resolver_cannot_extend_lock_past_cap() {
+        use crate::authorization::{authorize_transaction_party, EXTEND_LOCK_PARTIES};
+        use crate::transaction_manager::{extend_lock_expiration, generate_lock_key, get_lock_expiration_time, MAX_LOCK_DURATION_NS};
+        let (tx_id, p) = store_lock_endpoint_transaction();
+        let fund_type = LockedFundType::Payment { asset_id: "ckUSDC".to_string() };
+        let locked_at = 1_000;
+        crate::memory::LOCKED_FUNDS.with(|locks| locks.borrow_mut().insert(
+            generate_lock_key(&p.user, tx_id, &fund_type),
+            LockedFunds { user: p.user, transaction_id: tx_id, fund_type: fund_type.clone(), amount: 1_000_000, locked_at, expires_at: locked_at + 60 },
+        ));
+
+        assert!(authorize_transaction_party(p.resolver, tx_id, EXTEND_LOCK_PARTIES).is_ok());
+        assert!(extend_lock_expiration(tx_id, &fund_type, locked_at + MAX_LOCK_DURATION_NS).is_ok());
+        assert!(extend_lock_expiration(tx_id, &fund_type, locked_at + MAX_LOCK_DURATION_NS + 1).is_err());
+        assert!(extend_lock_expiration(tx_id, &fund_type, u64::MAX).is_err(), "Repeated extensions must not lift the cap");
+        assert_eq!(get_lock_expiration_time(tx_id, &fund_type), Ok(locked_at + MAX_LOCK_DURATION_NS));
+    }
+    #[test]
+    fn test_emergency_recovery_access() {
+        use crate::authorization::authorize_recovery_for;
+        let (_, p) = store_lock_endpoint_transaction();
+
+        assert!(authorize_recovery_for(p.user, p.user).is_ok());
+        assert!(authorize_recovery_for(p.operator, p.user).is_ok());
+        assert!(authorize_recovery_for(p.owner, p.user).is_ok());
+        assert!(authorize_recovery_for(p.resolver, p.user).is_err(), "Resolvers must not recover user funds");
+        assert!(authorize_recovery_for(p.stranger, p.user).is_err());
+        assert!(authorize_recovery_for(p.user, p.stranger).is_err(), "Users may only recover their own funds");
+        assert!(authorize_recovery_for(Principal::anonymous(), p.user).is_err());
+    }
+    #[test]
+    fn test_role_satisfies() {
+        use crate::roles::role_satisfies;
+        assert!(role_satisfies(&[Role::Owner], Role::Pauser), "Owner must satisfy every role");
+        assert!(role_satisfies(&[Role::Owner], Role::AssetManager));
+        assert!(role_satisfies(&[Role::FeeManager, Role::Pauser], Role::Pauser));
+        assert!(!role_satisfies(&[Role::FeeManager], Role::AssetManager));
+        assert!(!role_satisfies(&[Role::Operator], Role::Owner), "No role may escalate to Owner");
+        assert!(!role_satisfies(&[], Role::Operator));
+    }
+    fn mock_proposal(action: GovernedAction, required_approvals: u32) -> Proposal {
+        Proposal {
+            id: 1,
+            action,
+            proposer: mock_principal(),
+            approvals: vec![mock_principal()],
+            required_approvals,
+            created_at: 100,
+            executable_at: 200,
+            status: ProposalStatus::Pending,
+            resolved_at: None,
+            resolved_by: None,
+        }
+    }
+    #[test]
+    fn test_governance_action_validation_and_roles() {
+        use crate::governance::{required_role, validate_action};
+        assert!(validate_action(&GovernedAction::SetDefaultPlatformFeeBps { fee_bps: 10001 }).is_err());
+        assert!(validate_action(&GovernedAction::SetBundlePlatformFee { bundle_id: 1, fee_bps: 75 }).is_ok());
+        assert!(validate_action(&GovernedAction::SetCoordinatorPublicKey { public_key_hex: "abcd".to_string() }).is_err());
+        assert!(validate_action(&GovernedAction::SetCoordinatorPublicKey { public_key_hex: "ab".repeat(32) }).is_ok());
+        assert!(validate_action(&GovernedAction::SetAdmin { new_admin: Principal::anonymous() }).is_err());
+        assert!(validate_action(&GovernedAction::SetGovernanceConfig {
+            config: GovernanceConfig { delay_ns: 0, required_approvals: 0 },
+        }).is_err());
+        assert!(validate_action(&GovernedAction::UpdateAssetTokenLocation {
+            asset_id: "ckBTC".to_string(),
+            token_location: TokenLocation::ICRC151 { ledger: mock_principal(), token_id: vec![0u8; 31] },
+        }).is_err());
+
+        assert_eq!(required_role(&GovernedAction::SetDefaultPlatformFeeBps { fee_bps: 1 }), Role::FeeManager);
+        assert_eq!(required_role(&GovernedAction::SetAdmin { new_admin: mock_principal() }), Role::Owner);
+        assert_eq!(required_role(&GovernedAction::SetPlatformTreasury { treasury: mock_principal() }), Role::Owner);
+    }
+    #[test]
+    fn test_governance_bootstrap_applies_unset_values() {
+        use crate::governance::is_unset;
+        let config_action = GovernedAction::SetGovernanceConfig {
+            config: GovernanceConfig { delay_ns: 3_600_000_000_000, required_approvals: 1 },
+        };
+
+        assert!(is_unset(&config_action), "Governance settings start unset");
+        assert!(is_unset(&GovernedAction::SetCoordinatorPublicKey { public_key_hex: "ab".repeat(32) }));
+        assert!(is_unset(&GovernedAction::SetPlatformTreasury { treasury: mock_principal() }));
+        assert!(!is_unset(&GovernedAction::SetDefaultPlatformFeeBps { fee_bps: 60 }), "The default fee ships configured");
+        assert!(!is_unset(&GovernedAction::SetBundlePlatformFee { bundle_id: 99, fee_bps: 60 }), "Unknown bundles never bootstrap");
+        assert!(!is_unset(&GovernedAction::UpdateAssetTokenLocation {
+            asset_id: mock_asset_id(),
+            token_location: TokenLocation::ICRC151 { ledger: mock_principal(), token_id: vec![0u8; 32] },
+        }));
+
+        crate::memory::set_governance_config(GovernanceConfig::default());
+        assert!(!is_unset(&config_action), "Configured values go through the timelock");
+    }
+    #[test]
+    fn test_governance_approvals_bounded_by_owners() {
+        use crate::governance::validate_action;
+        let two_approvals = GovernedAction::SetGovernanceConfig {
+            config: GovernanceConfig { delay_ns: 0, required_approvals: 2 },
+        };
+
+        assign_test_roles(mock_principal(), vec![Role::Owner]);
+        assign_test_roles(Principal::from_text("aaaaa-aa").unwrap(), vec![Role::FeeManager]);
+        assert!(validate_action(&two_approvals).is_err(), "Non-owners cannot approve governance changes");
+
+        assign_test_roles(Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap(), vec![Role::Owner]);
+        assert!(validate_action(&two_approvals).is_ok());
+    }
+    #[test]
+    fn test_proposal_timelock_and_approvals() {
+        use crate::governance::{add_approval, is_executable};
+        let second = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+
+        let single = mock_proposal(GovernedAction::SetDefaultPlatformFeeBps { fee_bps: 60 }, 1);
+        assert!(!is_executable(&single, 199), "Must wait for the delay");
+        assert!(is_executable(&single, 200));
+
+        let mut multi = mock_proposal(GovernedAction::SetDefaultPlatformFeeBps { fee_bps: 60 }, 2);
+        assert!(!is_executable(&multi, 500), "Must wait for enough approvals");
+        assert!(add_approval(&mut multi, mock_principal()).is_err(), "Approvals must be unique");
+        assert!(add_approval(&mut multi, second).is_ok());
+        assert!(is_executable(&multi, 500));
+
+        multi.status = ProposalStatus::Cancelled;
+        assert!(!is_executable(&multi, 500), "Cancelled proposals never execute");
+        assert!(add_approval(&mut multi, Principal::from_text("aaaaa-aa").unwrap()).is_err());
+    }
+    #[test]
+    fn test_nomination_acceptance() {
+        use crate::admin::{check_acceptance, new_nomination};
+        let nominee = mock_principal();
+        let admin = Principal::from_text("aaaaa-aa").unwrap();
+        let nomination = new_nomination(nominee, admin, 1_000);
+
+        assert!(check_acceptance(None, nominee, 1_000).is_err(), "Nothing to accept without a nomination");
+        assert!(check_acceptance(Some(&nomination), admin, 1_000).is_err(), "Only the nominee can accept");
+        assert!(check_acceptance(Some(&nomination), nominee, 1_000).is_ok());
+        assert!(check_acceptance(Some(&nomination), nominee, nomination.expires_at).is_ok());
+        assert!(check_acceptance(Some(&nomination), nominee, nomination.expires_at + 1).is_err(), "Expired nominations must be rejected");
+    }
+    #[test]
+    fn test_admin_action_filter() {
+        use crate::audit::matches_filter;
+        use crate::governance::{action_name, action_target};
+        let action = AdminAction {
+            id: 0,
+            action_type: "force_deactivate_bundle".to_string(),
+            target: "bundle:7".to_string(),
+            admin: mock_principal(),
+            timestamp: 1_000,
+            details: Some("Compromised asset".to_string()),
+            before: Some("is_active: true".to_string()),
+            after: Some("is_active: false".to_string()),
+        };
+
+        assert!(matches_filter(&action, &AdminActionFilter::default()));
+        assert!(matches_filter(&action, &AdminActionFilter {
+            action_type: Some("force_deactivate_bundle".to_string()),
+            target: Some("bundle:7".to_string()),
+            admin: Some(mock_principal()),
+            from_time: Some(1_000),
+            to_time: Some(1_000),
+        }));
+        assert!(!matches_filter(&action, &AdminActionFilter { target: Some("bundle:8".to_string()), ..Default::default() }));
+        assert!(!matches_filter(&action, &AdminActionFilter { admin: Some(Principal::anonymous()), ..Default::default() }));
+        assert!(!matches_filter(&action, &AdminActionFilter { from_time: Some(1_001), ..Default::default() }));
+        assert!(!matches_filter(&action, &AdminActionFilter { to_time: Some(999), ..Default::default() }));
+
+        let fee_change = GovernedAction::SetBundlePlatformFee { bundle_id: 7, fee_bps: 75 };
+        assert_eq!(action_name(&fee_change), "set_bundle_platform_fee");
+        assert_eq!(action_target(&fee_change), "bundle:7");
+    }
+    #[test]
+    fn test_migration_plan_ordering() {
+        use crate::migrations::{latest_version, plan_migrations, Migration, MIGRATIONS, REGIONS};
+        fn noop(_dry_run: bool) -> Result<u64, String> { Ok(0) }
+        let step = |region: &'static str, from_version: u32| Migration { region, from_version, description: "", run: noop };
+        let regions = ["global_state", "transactions"];
+
+        let steps = [step("global_state", 1), step("transactions", 1), step("global_state", 2)];
+        assert_eq!(latest_version(&steps, "global_state"), 3);
+        assert_eq!(latest_version(&steps, "transactions"), 2);
+        assert_eq!(latest_version(&steps, "bundles"), 1);
+
+        let pending = plan_migrations(&steps, &regions, |_| 1).unwrap();
+        assert_eq!(pending.len(), 3);
+        let pending = plan_migrations(&steps, &regions, |region| if region == "global_state" { 2 } else { 1 }).unwrap();
+        assert_eq!(pending.iter().map(|m| (m.region, m.from_version)).collect::<Vec<_>>(), vec![("transactions", 1), ("global_state", 2)]);
+        assert!(plan_migrations(&steps, &regions, |_| 3).is_err(), "Stored versions newer than the build must be rejected");
+
+        assert!(plan_migrations(&[step("global_state", 2)], &regions, |_| 1).is_err(), "Steps must start at v1");
+        assert!(plan_migrations(&[step("global_state", 1), step("global_state", 1)], &regions, |_| 1).is_err());
+        assert!(plan_migrations(&[step("bundles", 1)], &regions, |_| 1).is_err(), "Unknown regions must be rejected");
+
+        let names: Vec<&str> = REGIONS.iter().map(|r| r.name).collect();
+        assert!(plan_migrations(MIGRATIONS, &names, |_| 1).is_ok(), "Shipped migrations must form a valid chain");
+        assert_eq!(latest_version(MIGRATIONS, "global_state"), GlobalState::default().version);
+    }
+    #[test]
+    fn test_migrate_map_rewrites_legacy_records() {
+        use crate::migrations::{migrate_map, RawRecord};
+        use candid::CandidType;
+        use ic_stable_structures::{StableBTreeMap, Storable, VectorMemory};
+        use serde::Deserialize;
+
+        #[derive(CandidType, Deserialize)]
+        struct LegacyRoleAssignment {
+            principal: Principal,
+            roles: Vec<Role>,
+        }
+
+        let memory = VectorMemory::default();
+        let legacy = candid::encode_one(LegacyRoleAssignment { principal: mock_principal(), roles: vec![Role::Operator] }).unwrap();
+        assert!(candid::decode_one::<RoleAssignment>(&legacy).is_err(), "Legacy bytes would trap in from_bytes");
+        {
+            let mut raw: StableBTreeMap<u64, RawRecord<RoleAssignment>, _> = StableBTreeMap::init(memory.clone());
+            raw.insert(1, RawRecord::new(legacy.clone()));
+        }
+
+        let convert = |old: LegacyRoleAssignment| Ok(RoleAssignment {
+            principal: old.principal,
+            roles: old.roles,
+            updated_at: 0,
+            updated_by: old.principal,
+        });
+
+        assert_eq!(migrate_map::<u64, LegacyRoleAssignment, RoleAssignment, _>(memory.clone(), convert, true), Ok(1));
+        let raw: StableBTreeMap<u64, RawRecord<RoleAssignment>, _> = StableBTreeMap::init(memory.clone());
+        assert_eq!(raw.get(&1).unwrap().bytes, legacy, "Dry runs must not write");
+
+        assert_eq!(migrate_map::<u64, LegacyRoleAssignment, RoleAssignment, _>(memory.clone(), convert, false), Ok(1));
+        let typed: StableBTreeMap<u64, RoleAssignment, _> = StableBTreeMap::init(memory.clone());
+        let migrated = typed.get(&1).unwrap();
+        assert_eq!(migrated.roles, vec![Role::Operator]);
+        assert_eq!(migrated.updated_by, mock_principal());
+        assert_eq!(RoleAssignment::from_bytes(migrated.to_bytes()).updated_at, 0);
+
+        assert!(
+            migrate_map::<u64, Transaction, Transaction, _>(memory, Ok, true).is_err(),
+            "Records that don't decode must fail the step instead of trapping"
+        );
+    }
+    #[test]
+    fn test_global_state_v2_migration() {
+        use crate::migrations::{global_state_v2, migrate_cell};
+        use ic_stable_structures::{StableCell, VectorMemory};
+
+        let memory = VectorMemory::default();
+        let v1 = GlobalState { version: 1, bundle_counter: 7, ..Default::default() };
+        StableCell::init(memory.clone(), v1).unwrap();
+
+        assert_eq!(migrate_cell::<GlobalState, GlobalState, _>(memory.clone(), |s| Ok(global_state_v2(s)), false), Ok(1));
+        let cell = StableCell::init(memory, GlobalState::default()).unwrap();
+        assert_eq!(cell.get().version, 2);
+        assert_eq!(cell.get().bundle_counter, 7);
+
+        assert_eq!(migrate_cell::<GlobalState, GlobalState, _>(VectorMemory::default(), Ok, true), Ok(0), "Empty memory has nothing to migrate");
+    }
+    #[test]
+    fn test_stablecoin_ledger_validation() {
+        use crate::icrc2_client::{stablecoin_ledger_info, MetadataValue};
+        use candid::Nat;
+        let ledger = mock_principal();
+        let metadata = vec![
+            ("icrc1:symbol".to_string(), MetadataValue::Text("ckUSDC".to_string())),
+            ("icrc1:name".to_string(), MetadataValue::Text("ckUSDC".to_string())),
+            ("icrc1:decimals".to_string(), MetadataValue::Nat(Nat::from(6u8))),
+            ("icrc1:fee".to_string(), MetadataValue::Nat(Nat::from(10_000u64))),
+        ];
+
+        let info = stablecoin_ledger_info(ledger, &metadata, 6).unwrap();
+        assert_eq!(info.symbol, "ckUSDC");
+        assert_eq!(info.fee, 10_000);
+
+        assert!(stablecoin_ledger_info(ledger, &metadata, 8).is_err(), "Metadata and icrc1_decimals must agree");
+        let mut eight = metadata.clone();
+        eight[2].1 = MetadataValue::Nat(Nat::from(8u8));
+        assert!(stablecoin_ledger_info(ledger, &eight, 8).is_err(), "Only 6-decimal stablecoins are supported");
+        assert!(stablecoin_ledger_info(ledger, &metadata[1..], 6).is_err(), "A symbol is required");
+        assert!(stablecoin_ledger_info(ledger, &metadata[..3], 6).is_err(), "A fee is required");
+    }
+    #[test]
+    fn test_global_state_v3_seeds_ckusdc_ledger() {
+        use crate::migrations::global_state_v3;
+        let mainnet = Principal::from_text(crate::icrc2_client::MAINNET_CKUSDC_LEDGER_CANISTER).unwrap();
+
+        let migrated = global_state_v3(GlobalState { version: 2, ..Default::default() });
+        assert_eq!(migrated.version, 3);
+        assert_eq!(migrated.icrc2_ckusdc_ledger, Some(mainnet));
+
+        let local = global_state_v3(GlobalState { version: 2, icrc2_ckusdc_ledger: Some(mock_principal()), ..Default::default() });
+        assert_eq!(local.icrc2_ckusdc_ledger, Some(mock_principal()), "A configured ledger is kept");
+        assert_eq!(GlobalState::default().icrc2_ckusdc_ledger, None, "Fresh installs must configure the ledger");
+    }
+    #[test]
+    fn test_payment_amount_normalization() {
+        use crate::payments::{normalize_amount, points_for};
+        assert_eq!(normalize_amount(1_000_000, 6, 6), 1_000_000);
+        assert_eq!(normalize_amount(1_000_000_000_000_000_000, 18, 6), 1_000_000);
+        assert_eq!(normalize_amount(1_000_000, 6, 8), 100_000_000);
+        assert_eq!(normalize_amount(u64::MAX, 6, 18), u64::MAX);
+        assert_eq!(points_for(1_000_000, 6), points_for(1_000_000_000_000_000_000, 18));
+        assert_eq!(points_for(10_000_000, 6), 1000);
+    }
+    #[test]
+    fn test_payment_token_from_asset() {
+        use crate::payments::payment_token_from_asset;
+        let mut asset = create_cketh_asset();
+        let token = payment_token_from_asset(&asset).unwrap();
+        assert_eq!(token.asset_id, "ckETH");
+        assert_eq!(token.ledger, mock_principal());
+        assert_eq!(token.decimals, 18);
+
+        asset.is_active = false;
+        assert!(payment_token_from_asset(&asset).is_err());
+
+        asset.is_active = true;
+        asset.token_location = TokenLocation::ICRC151 { ledger: mock_principal(), token_id: vec![0; 32] };
+        assert!(payment_token_from_asset(&asset).is_err());
+    }
+    #[test]
+    fn test_usd_value_of_deactivated_payment_asset() {
+        use crate::payments::{payment_decimals, usd_value_e6};
+        let mut asset = create_cketh_asset();
+        asset.is_active = false;
+        crate::memory::ASSET_REGISTRY.with(|registry| registry.borrow_mut().insert(asset.id.clone(), asset));
+
+        assert_eq!(payment_decimals("ckETH"), 18, "Deactivated assets keep their decimals");
+        assert_eq!(usd_value_e6(&Some("ckETH".to_string()), 1_000_000_000_000_000_000), 1_000_000);
+        assert_eq!(payment_decimals("ckUSDC"), 6);
+        assert_eq!(usd_value_e6(&None, 1_000_000), 1_000_000);
+        assert_eq!(payment_decimals("removed"), 6);
+    }
+    #[test]
+    fn test_locked_funds_v2_migration() {
+        use crate::migrations::{locked_funds_v2, migrate_map, LegacyLockedFundType, LegacyLockedFunds, RawRecord};
+        use ic_stable_structures::{StableBTreeMap, VectorMemory};
+
+        let legacy = |fund_type| LegacyLockedFunds {
+            user: mock_principal(),
+            transaction_id: 1,
+            fund_type,
+            amount: 500,
+            locked_at: 1,
+            expires_at: 2,
+        };
+
+        let memory = VectorMemory::default();
+        {
+            let mut raw: StableBTreeMap<String, RawRecord<LockedFunds>, _> = StableBTreeMap::init(memory.clone());
+            raw.insert("a:1:ckusdc".to_string(), RawRecord::new(candid::encode_one(legacy(LegacyLockedFundType::CkUSDC)).unwrap()));
+            raw.insert("a:1:nav:3".to_string(), RawRecord::new(candid::encode_one(legacy(LegacyLockedFundType::NAVTokens { bundle_id: 3 })).unwrap()));
+        }
+
+        let convert = |old: LegacyLockedFunds| Ok(locked_funds_v2(old));
+        assert_eq!(migrate_map::<String, LegacyLockedFunds, LockedFunds, _>(memory.clone(), convert, false), Ok(2));
+
+        let typed: StableBTreeMap<String, LockedFunds, _> = StableBTreeMap::init(memory);
+        assert_eq!(typed.get(&"a:1:ckusdc".to_string()).unwrap().fund_type, LockedFundType::payment("ckUSDC"));
+        let nav = typed.get(&"a:1:nav:3".to_string()).unwrap();
+        assert_eq!(nav.fund_type, LockedFundType::NAVTokens { bundle_id: 3 });
+        assert_eq!(nav.amount, 500);
+    }
+    #[test]
+    fn test_transaction_without_payment_asset_decodes() {
+        use candid::CandidType;
+        use ic_stable_structures::Storable;
+        use serde::Deserialize;
+
+        #[derive(CandidType, Deserialize)]
+        struct LegacyTransaction {
+            id: u64,
+            request_id: u64,
+            user: Principal,
+            resolver: Principal,
+            bundle_id: u64,
+            operation: OperationType,
+            status: TransactionStatus,
+            nav_tokens: u64,
+            ckusdc_amount: u64,
+            created_at: u64,
+            updated_at: u64,
+            completed_at: Option<u64>,
+            timeout_at: u64,
+        }
+
+        let legacy = candid::encode_one(LegacyTransaction {
+            id: 1,
+            request_id: 1,
+            user: mock_principal(),
+            resolver: mock_principal(),
+            bundle_id: 1,
+            operation: OperationType::Buy { ckusdc_amount: 1_000_000 },
+            status: TransactionStatus::Completed,
+            nav_tokens: 10,
+            ckusdc_amount: 1_000_000,
+            created_at: 0,
+            updated_at: 0,
+            completed_at: None,
+            timeout_at: 0,
+        }).unwrap();
+
+        let transaction = Transaction::from_bytes(std::borrow::Cow::Owned(legacy));
+        assert_eq!(transaction.payment_asset, None);
+        assert_eq!(crate::payments::payment_asset(&transaction.payment_asset), "ckUSDC");
+    }
+    #[test]
+    fn test_oracle_price_policy() {
+        use crate::oracle::{effective_policy, validate_asset_price_policy, validate_oracle_config};
+        let config = OracleConfig {
+            oracle_canister: mock_principal(),
+            cache_duration_ns: 30_000_000_000,
+            max_staleness_ns: 120_000_000_000,
+            fallback_enabled: false,
+            max_price_move_bps: Some(1_000),
+        };
+        assert!(validate_oracle_config(&config).is_ok());
+        assert!(validate_oracle_config(&OracleConfig { cache_duration_ns: 0, ..config.clone() }).is_err());
+        assert!(validate_oracle_config(&OracleConfig { max_staleness_ns: 10, ..config.clone() }).is_err());
+        assert!(validate_oracle_config(&OracleConfig { oracle_canister: Principal::anonymous(), ..config.clone() }).is_err());
+
+        let defaults = effective_policy(None, None);
+        assert!(defaults.fallback_enabled);
+        assert!(defaults.max_staleness_ns >= defaults.cache_duration_ns);
+
+        let global = effective_policy(Some(&config), None);
+        assert_eq!(global, PricePolicy {
+            cache_duration_ns: 30_000_000_000,
+            max_staleness_ns: 120_000_000_000,
+            fallback_enabled: false,
+            max_price_move_bps: Some(1_000),
+            stablecoin_fallback: false,
+        });
+
+        let overrides = AssetPricePolicy { cache_duration_ns: Some(5_000_000_000), max_staleness_ns: None, max_price_move_bps: Some(500), stablecoin_fallback: None };
+        assert!(validate_asset_price_policy(&overrides).is_ok());
+        let asset = effective_policy(Some(&config), Some(&overrides));
+        assert_eq!(asset.cache_duration_ns, 5_000_000_000);
+        assert_eq!(asset.max_staleness_ns, 120_000_000_000);
+        assert_eq!(asset.max_price_move_bps, Some(500));
+
+        assert!(validate_asset_price_policy(&AssetPricePolicy { cache_duration_ns: Some(0), ..Default::default() }).is_err());
+        assert!(validate_asset_price_policy(&AssetPricePolicy { cache_duration_ns: Some(10), max_staleness_ns: Some(5), ..Default::default() }).is_err());
+        assert!(validate_asset_price_policy(&AssetPricePolicy { max_price_move_bps: Some(0), ..Default::default() }).is_err());
+    }
+    #[test]
+    fn test_stablecoin_fallback_price() {
+        use crate::oracle::{effective_policy, stablecoin_fallback_price};
+        let default_policy = effective_policy(None, None);
+        assert!(!default_policy.stablecoin_fallback);
+        assert_eq!(stablecoin_fallback_price("ckUSDC", &default_policy), None, "The $1 substitute needs an explicit opt-in");
+
+        let opted_in = effective_policy(None, Some(&AssetPricePolicy { stablecoin_fallback: Some(true), ..Default::default() }));
+        assert_eq!(stablecoin_fallback_price("ckUSDC", &opted_in), Some(1_00000000));
+        assert_eq!(stablecoin_fallback_price("USDC", &opted_in), Some(1_00000000));
+        assert_eq!(stablecoin_fallback_price("BTC", &opted_in), None);
+    }
+    #[test]
+    fn test_price_median_and_outliers() {
+        use crate::oracle::{aggregate_prices, median, within_deviation};
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3, 1, 2]), Some(2));
+        assert_eq!(median(&[4, 1, 3, 2]), Some(2));
+        assert_eq!(median(&[u64::MAX, u64::MAX]), Some(u64::MAX));
+        assert!(within_deviation(102, 100, 200));
+        assert!(!within_deviation(103, 100, 200));
+
+        let sample = |source: &str, price_usd| PriceSample { source: source.to_string(), price_usd, timestamp: 0 };
+        let samples = vec![
+            sample("a", 100_00000000),
+            sample("b", 101_00000000),
+            sample("c", 150_00000000),
+            sample("reference", 100_50000000),
+        ];
+
+        let aggregated = aggregate_prices(&samples, 4, 2, 200).unwrap();
+        assert_eq!(aggregated.price_usd, 100_50000000);
+        assert_eq!(aggregated.source, "median:3/4");
+        assert_eq!(aggregated.confidence, 75);
+
+        assert!(aggregate_prices(&samples, 4, 4, 200).is_err(), "The outlier must not count towards the minimum");
+        assert!(aggregate_prices(&[], 3, 1, 200).is_err());
+
+        let single = aggregate_prices(&samples[..1], 3, 1, 200).unwrap();
+        assert_eq!(single.confidence, 33, "Sources that did not respond lower the confidence");
+    }
+    #[test]
+    fn test_price_aggregation_config_validation() {
+        use crate::oracle::validate_price_aggregation_config;
+        let config = PriceAggregationConfig {
+            additional_oracles: vec![mock_principal()],
+            min_sources: 2,
+            max_deviation_bps: 200,
+        };
+        assert!(validate_price_aggregation_config(&config).is_ok());
+        assert!(validate_price_aggregation_config(&PriceAggregationConfig { min_sources: 0, ..config.clone() }).is_err());
+        assert!(validate_price_aggregation_config(&PriceAggregationConfig { min_sources: 4, ..config.clone() }).is_err());
+        assert!(validate_price_aggregation_config(&PriceAggregationConfig { max_deviation_bps: 0, ..config.clone() }).is_err());
+        assert!(validate_price_aggregation_config(&PriceAggregationConfig {
+            additional_oracles: vec![mock_principal(), mock_principal()],
+            ..config.clone()
+        }).is_err());
+        assert!(validate_price_aggregation_config(&PriceAggregationConfig {
+            additional_oracles: vec![Principal::anonymous()],
+            ..config
+        }).is_err());
+    }
+    #[test]
+    fn test_price_move_circuit_breaker() {
+        use crate::oracle::{check_price_move, price_move_bps};
+        assert_eq!(price_move_bps(100, 110), 1_000);
+        assert_eq!(price_move_bps(100, 90), 1_000);
+        assert_eq!(price_move_bps(0, 1), u64::MAX);
+
+        let policy = PricePolicy {
+            cache_duration_ns: 60,
+            max_staleness_ns: 300,
+            fallback_enabled: true,
+            max_price_move_bps: Some(1_000),
+            stablecoin_fallback: false,
+        };
+        let previous = AssetPrice {
+            asset_id: mock_asset_id(),
+            price_usd: 100_00000000,
+            timestamp: 0,
+            source: "median:1/1".to_string(),
+            confidence: 100,
+        };
+
+        assert_eq!(check_price_move(Some(&previous), 110_00000000, &policy), None);
+        assert_eq!(
+            check_price_move(Some(&previous), 111_00000000, &policy),
+            Some(PriceHaltReason::PriceMove { previous_usd: 100_00000000, observed_usd: 111_00000000, move_bps: 1_100 })
+        );
+        assert_eq!(check_price_move(None, 1, &policy), None, "The first observation has nothing to compare against");
+        assert_eq!(check_price_move(Some(&previous), 1, &PricePolicy { max_price_move_bps: None, ..policy }), None);
+    }
+    #[test]
+    fn test_price_halt_blocks_trading() {
+        use crate::oracle::trading_block;
+        let policy = PricePolicy {
+            cache_duration_ns: 60,
+            max_staleness_ns: 300,
+            fallback_enabled: true,
+            max_price_move_bps: None,
+            stablecoin_fallback: false,
+        };
+        let price = AssetPrice {
+            asset_id: mock_asset_id(),
+            price_usd: 100_00000000,
+            timestamp: 1_000,
+            source: "median:1/1".to_string(),
+            confidence: 100,
+        };
+        let mut halt = PriceHalt {
+            asset_id: mock_asset_id(),
+            reason: PriceHaltReason::NoValidPrice { detail: "No price source returned a fresh price".to_string() },
+            halted_at: 1_000,
+            overridden_by: None,
+        };
+
+        assert_eq!(trading_block(None, Some(&price), &policy, 1_200), None);
+        assert!(trading_block(None, Some(&price), &policy, 1_301).is_some(), "Stale prices block trading");
+        assert!(trading_block(None, None, &policy, 1_200).is_some());
+        assert!(trading_block(Some(&halt), Some(&price), &policy, 1_200).is_some());
+
+        halt.overridden_by = Some(mock_principal());
+        assert_eq!(trading_block(Some(&halt), Some(&price), &policy, 1_200), None);
+        assert!(trading_block(Some(&halt), None, &policy, 1_200).is_some(), "Overrides must not skip the missing-price check");
+        assert!(trading_block(Some(&halt), Some(&price), &policy, 1_301).is_some(), "Overrides must not skip the staleness check");
+    }
+    #[test]
+    fn test_halt_override_cleared_when_reason_changes() {
+        use crate::oracle::next_halt;
+        let no_price = PriceHaltReason::NoValidPrice { detail: "No price source returned a fresh price".to_string() };
+        let moved = PriceHaltReason::PriceMove { previous_usd: 100, observed_usd: 150, move_bps: 5_000 };
+
+        let halt = next_halt(None, &mock_asset_id(), no_price.clone(), 1_000);
+        assert_eq!(halt.halted_at, 1_000);
+        assert_eq!(halt.overridden_by, None);
+
+        let overridden = PriceHalt { overridden_by: Some(mock_principal()), ..halt };
+        let same = next_halt(Some(overridden.clone()), &mock_asset_id(), no_price, 2_000);
+        assert_eq!(same.overridden_by, Some(mock_principal()), "The same condition keeps its override");
+        assert_eq!(same.halted_at, 1_000);
+
+        let changed = next_halt(Some(overridden), &mock_asset_id(), moved.clone(), 2_000);
+        assert_eq!(changed.overridden_by, None, "A new reason must re-arm the halt");
+        assert_eq!(changed.reason, moved);
+        assert_eq!(changed.halted_at, 1_000);
+    }
+    #[test]
+    fn test_twap_weights_by_time() {
+        use crate::price_history::twap;
+        let obs = |price_usd, timestamp| PriceObservation { price_usd, timestamp };
+        let observations = vec![obs(100, 0), obs(200, 900), obs(1_000, 990)];
+
+        assert_eq!(twap(&observations, 1_000, 1_000), Some(118), "A late spike only counts for its duration");
+        assert_eq!(twap(&observations, 100, 1_000), Some(280), "The observation before the window covers its start");
+        assert_eq!(twap(&observations[..1], 1_000, 1_000), Some(100));
+        assert_eq!(twap(&[obs(150, 1_000)], 1_000, 1_000), Some(150));
+        assert_eq!(twap(&[], 1_000, 1_000), None);
+    }
+    #[test]
+    fn test_ema() {
+        use crate::price_history::ema;
+        let obs = |price_usd, timestamp| PriceObservation { price_usd, timestamp };
+
+        assert_eq!(ema(&[], 60), None);
+        assert_eq!(ema(&[obs(100, 0), obs(100, 60)], 60), Some(100));
+        let smoothed = ema(&[obs(100, 0), obs(200, 60)], 60).unwrap();
+        assert!(smoothed > 100 && smoothed < 200);
+        assert!(ema(&[obs(100, 0), obs(200, 6_000)], 60).unwrap() >= 199, "Old values decay with elapsed time");
+    }
+    #[test]
+    fn test_price_observations_window_and_retention() {
+        use crate::price_history::{observations_since, record, OBSERVATION_RETENTION_NS};
+        let asset_id = "ckOBSERVED".to_string();
+        let price = |price_usd, timestamp| AssetPrice {
+            asset_id: asset_id.clone(),
+            price_usd,
+            timestamp,
+            source: "median:1/1".to_string(),
+            confidence: 100,
+        };
+        let obs = |price_usd, timestamp| PriceObservation { price_usd, timestamp };
+        for (price_usd, timestamp) in [(100, 1_000), (110, 2_000), (120, 3_000), (130, 4_000)] {
+            record(&price(price_usd, timestamp));
+        }
+
+        assert_eq!(observations_since(&asset_id, 2_500), vec![obs(110, 2_000), obs(120, 3_000), obs(130, 4_000)]);
+        assert_eq!(observations_since(&asset_id, 3_000), vec![obs(110, 2_000), obs(120, 3_000), obs(130, 4_000)]);
+        assert_eq!(observations_since(&asset_id, 5_000), vec![obs(130, 4_000)], "The last observation covers a window after it");
+        assert_eq!(observations_since(&"ckOTHER".to_string(), 0), Vec::new());
+
+        record(&price(140, 3_500 + OBSERVATION_RETENTION_NS));
+        assert_eq!(
+            observations_since(&asset_id, 0),
+            vec![obs(120, 3_000), obs(130, 4_000), obs(140, 3_500 + OBSERVATION_RETENTION_NS)],
+            "Only the newest expired observation is kept, to cover the start of the longest window"
+        );
+    }
+    #[test]
+    fn test_bundle_valuation_mode_is_governed() {
+        use crate::governance::{required_role, validate_action};
+        let twap = GovernedAction::SetBundleValuationMode { bundle_id: 1, mode: ValuationMode::Twap { window_ns: 3_600_000_000_000 } };
+        assert_eq!(required_role(&twap), Role::Operator);
+        assert!(validate_action(&twap).is_ok());
+        assert!(validate_action(&GovernedAction::SetBundleValuationMode { bundle_id: 1, mode: ValuationMode::Spot }).is_ok());
+        assert!(validate_action(&GovernedAction::SetBundleValuationMode {
+            bundle_id: 1,
+            mode: ValuationMode::Twap { window_ns: 1 },
+        }).is_err());
+    }
+    #[test]
+    fn test_candle_aggregation() {
+        use crate::price_history::apply_sample;
+        let minute = CandleInterval::Minute.duration_ns();
+        assert_eq!(CandleInterval::Minute.bucket_start(minute * 5 + 7), minute * 5);
+        assert_eq!(CandleInterval::Day.bucket_start(CandleInterval::Day.duration_ns() - 1), 0);
+
+        let candle = [120, 90, 150, 110].into_iter()
+            .fold(None, |candle, price| Some(apply_sample(candle, minute, price)))
+            .unwrap();
+        assert_eq!(candle, PriceCandle { open_time: minute, open: 120, high: 150, low: 90, close: 110, samples: 4 });
+    }
+    #[test]
+    fn test_price_history_retention_and_range() {
+        use crate::price_history::{price_history, record_candle};
+        let asset = "ckHIST".to_string();
+        let minute = CandleInterval::Minute.duration_ns();
+
+        record_candle(&asset, CandleInterval::Minute, 100, 0);
+        record_candle(&asset, CandleInterval::Minute, 105, minute + 1);
+        record_candle(&asset, CandleInterval::Minute, 95, minute + 2);
+        record_candle(&"ckOTHER".to_string(), CandleInterval::Minute, 1, minute);
+
+        let candles = price_history(&asset, CandleInterval::Minute, 0, u64::MAX).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!((candles[1].open, candles[1].close, candles[1].samples), (105, 95, 2));
+        assert_eq!(price_history(&asset, CandleInterval::Minute, minute, minute).unwrap().len(), 1);
+        assert!(price_history(&asset, CandleInterval::Hour, 0, u64::MAX).unwrap().is_empty());
+        assert!(price_history(&asset, CandleInterval::Minute, 2, 1).is_err());
+
+        // A sample past the retention window prunes older candles
+        record_candle(&asset, CandleInterval::Minute, 110, CandleInterval::Minute.retention_ns() + minute + 1);
+        let candles = price_history(&asset, CandleInterval::Minute, 0, u64::MAX).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].open_time, minute);
+        assert_eq!(price_history(&"ckOTHER".to_string(), CandleInterval::Minute, 0, u64::MAX).unwrap().len(), 1);
+    }
+    #[test]
+    fn test_nav_history_downsampling_and_returns() {
+        use crate::nav_history::{bundle_return, nav_history, record, return_bps};
+        let minute = CandleInterval::Minute.duration_ns();
+        let nav = |nav_per_token, calculated_at| BundleNAV {
+            bundle_id: 9_001,
+            nav_per_token,
+            total_nav_usd: nav_per_token * 10,
+            total_tokens: 10,
+            asset_values: Vec::new(),
+            calculated_at,
+            fallback_assets: Vec::new(),
+        };
+
+        record(&nav(100, 0));
+        record(&nav(110, minute + 1));
+        record(&nav(120, minute * 2 + 5));
+        record(&BundleNAV { total_tokens: 0, ..nav(0, minute * 3) });
+        record(&BundleNAV { fallback_assets: vec!["ckUSDC".to_string()], ..nav(500, minute * 4) });
+
+        let minutes = nav_history(9_001, CandleInterval::Minute, 0, u64::MAX).unwrap();
+        assert_eq!(minutes.iter().map(|n| n.nav_per_token).collect::<Vec<_>>(), vec![100, 110, 120]);
+        let hours = nav_history(9_001, CandleInterval::Hour, 0, u64::MAX).unwrap();
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].nav_per_token, 120, "A bucket keeps its latest NAV");
+        assert!(nav_history(9_001, CandleInterval::Minute, 2, 1).is_err());
+
+        let r = bundle_return(9_001, minute + 1, minute * 2 + 5).unwrap();
+        assert_eq!((r.start.nav_per_token, r.end.nav_per_token, r.return_bps), (110, 120, 909));
+        assert_eq!(bundle_return(9_001, minute + 30, minute * 2).unwrap().return_bps, 0);
+        assert_eq!(bundle_return(9_001, 0, u64::MAX).unwrap().return_bps, 2_000);
+        assert!(bundle_return(9_002, 0, u64::MAX).is_err());
+
+        assert_eq!(return_bps(200, 100), Some(-5_000));
+        assert_eq!(return_bps(0, 100), None);
+    }
+    #[test]
+    fn test_performance_metrics() {
+        use crate::analytics::*;
+        const DAY: u64 = 86_400_000_000_000;
+        assert_eq!(year_start_ns(20_744 * DAY + 5), 20_454 * DAY);
+        assert_eq!(year_start_ns(19_782 * DAY), 19_723 * DAY);
+        assert_eq!(year_start_ns(19_722 * DAY), 19_358 * DAY);
+        assert_eq!(year_start_ns(10_957 * DAY), 10_957 * DAY);
+
+        assert_eq!(max_drawdown_bps(&[100, 120, 90, 130, 117]), Some(2_500));
+        assert_eq!(max_drawdown_bps(&[100, 110, 120]), Some(0));
+        assert_eq!(max_drawdown_bps(&[]), None);
+
+        let hour = CandleInterval::Hour.duration_ns();
+        let nav = |nav_per_token, hours: u64| BundleNAV {
+            bundle_id: 1,
+            nav_per_token,
+            total_nav_usd: 0,
+            total_tokens: 1,
+            asset_values: Vec::new(),
+            calculated_at: hours * hour + 1,
+            fallback_assets: Vec::new(),
+        };
+        let returns: Vec<f64> = hourly_returns(&[nav(100, 0), nav(110, 1), nav(99, 2)]).into_iter().map(|(_, r)| r).collect();
+        assert!((returns[0] - 0.1).abs() < 1e-9 && (returns[1] + 0.1).abs() < 1e-9);
+        assert_eq!(annualized_volatility_bps(&returns, 1.0), Some(1_414));
+        assert_eq!(annualized_volatility_bps(&returns[..1], 1.0), None);
+        assert_eq!(
+            hourly_returns(&[nav(100, 0), nav(200, 5), nav(220, 6)]),
+            vec![(6 * hour, 0.1)],
+            "A gap between points must not become one hourly return"
+        );
+
+        assert!((sharpe_ratio(&[0.02, 0.04], 1.0, 100).unwrap() - std::f64::consts::SQRT_2).abs() < 1e-9);
+        assert_eq!(sharpe_ratio(&[0.01, 0.01], 1.0, 0), None);
+
+        let a = [0.01, -0.02, 0.03];
+        assert!((correlation(&a, &a).unwrap() - 1.0).abs() < 1e-9);
+        assert!((correlation(&a, &[-0.01, 0.02, -0.03]).unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(correlation(&a, &[0.0, 0.0, 0.0]), None);
+        assert_eq!(correlation(&a, &a[..2]), None);
+
+        let (x, y) = aligned_returns(
+            &[nav(100, 0), nav(50, 1), nav(110, 2), nav(121, 3)],
+            &[nav(200, 0), nav(220, 2), nav(242, 3)],
+        );
+        assert_eq!(x.len(), 1, "Only hours both series cover end to end are compared");
+        assert_eq!(y.len(), 1);
+        assert!((x[0] - 0.1).abs() < 1e-9 && (y[0] - 0.1).abs() < 1e-9);
+    }
+    #[test]
+    fn test_sort_bundle_list() {
+        use crate::query_api::sort_bundle_list;
+        let item = |id, return_24h_bps| BundleListItem {
+            id,
+            name: format!("Bundle {}", id),
+            symbol: format!("B{}", id),
+            description: None,
+            allocations: Vec::new(),
+            nav_per_token: id * 10,
+            total_nav_usd: 0,
+            holders: 0,
+            created_at: 0,
+            is_active: true,
+            token_location: TokenLocation::ICRC151 { ledger: mock_principal(), token_id: vec![0u8; 32] },
+            analytics: BundleAnalytics { bundle_id: id, return_24h_bps, ..Default::default() },
+        };
+        let ids = |items: &[BundleListItem]| items.iter().map(|i| i.id).collect::<Vec<_>>();
+        let mut items = vec![item(1, Some(-50)), item(2, None), item(3, Some(200)), item(4, Some(10))];
+
+        sort_bundle_list(&mut items, BundleSortKey::Return24h, true);
+        assert_eq!(ids(&items), vec![3, 4, 1, 2]);
+        sort_bundle_list(&mut items, BundleSortKey::Return24h, false);
+        assert_eq!(ids(&items), vec![1, 4, 3, 2]);
+        sort_bundle_list(&mut items, BundleSortKey::NavPerToken, true);
+        assert_eq!(ids(&items), vec![4, 3, 2, 1]);
+    }
+    #[test]
+    fn test_portfolio_cost_basis_and_valuation() {
+        use crate::nav_calculator::{cost_basis_by_bundle, nav_token_value_usd, value_position, CostBasis};
+        const TOKEN: u64 = 100_000_000;
+        const USD: u64 = 100_000_000;
+        let transaction = |id, operation, status, nav_tokens, ckusdc_amount| Transaction {
+            operation,
+            status,
+            nav_tokens,
+            ckusdc_amount,
+            id,
+            completed_at: Some(id),
+            ..mock_sell_transaction()
+        };
+
+        assert_eq!(nav_token_value_usd(TOKEN / 2, 180 * USD).unwrap(), 90 * USD);
+        assert!(nav_token_value_usd(u64::MAX, u64::MAX).is_err());
+
+        let basis = cost_basis_by_bundle(&[
+            transaction(3, OperationType::Sell { nav_tokens: TOKEN }, TransactionStatus::Completed, TOKEN, 0),
+            transaction(1, OperationType::Buy { ckusdc_amount: 100_000_000 }, TransactionStatus::Completed, TOKEN, 100_000_000),
+            transaction(2, OperationType::Buy { ckusdc_amount: 200_000_000 }, TransactionStatus::Completed, TOKEN, 200_000_000),
+            transaction(4, OperationType::Buy { ckusdc_amount: 500_000_000 }, TransactionStatus::Failed, TOKEN, 500_000_000),
+        ]);
+        let position_basis = basis.get(&1).unwrap();
+        assert_eq!(position_basis, &CostBasis { nav_tokens: TOKEN, cost_usd: 150 * USD }, "A sell releases cost at the average price");
+
+        let bundle = BundleConfig {
+            id: 1,
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            token_location: TokenLocation::ICRC151 { ledger: mock_principal(), token_id: vec![0u8; 32] },
+            description: None,
+            creator: mock_principal(),
+            allocations: vec![],
+            created_at: 0,
+            is_active: true,
+            platform_fee_bps: None,
+            valuation_mode: None,
+        };
+        let position = value_position(&bundle, TOKEN, Some(180 * USD), Some(position_basis)).unwrap();
+        assert_eq!(position.value_usd, Some(180 * USD));
+        assert_eq!(position.cost_basis_usd, Some(150 * USD));
+        assert_eq!(position.unrealized_pnl_usd, Some(30 * USD as i64));
+
+        let received = value_position(&bundle, 2 * TOKEN, Some(180 * USD), Some(position_basis)).unwrap();
+        assert_eq!((received.cost_basis_usd, received.unrealized_pnl_usd), (None, None));
+        let unpriced = value_position(&bundle, TOKEN, None, Some(position_basis)).unwrap();
+        assert_eq!((unpriced.value_usd, unpriced.cost_basis_usd), (None, Some(150 * USD)));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+use ic_cdk::api::time;
+use crate::types::*;
+use crate::memory::*;
+
+pub fn record_ledger_call(transaction_id: u64, call: LedgerCall, result: &Result<u64, String>) {
+    let (block_index, error) = match result {
+        Ok(block_index) => (Some(*block_index), None),
+        Err(e) => (None, Some(e.clone())),
+    };
+
+    TRANSACTION_EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+
+        let sequence = events
+            .range((transaction_id, 0)..=(transaction_id, u32::MAX))
+            .last()
+            .map_or(0, |((_, sequence), _)| sequence + 1);
+
+        events.insert((transaction_id, sequence), TransactionEvent {
+            transaction_id,
+            sequence,
+            call,
+            block_index,
+            error,
+            timestamp: time(),
+        });
+    });
+}
+
+pub fn get_transaction_events(transaction_id: u64) -> Vec<TransactionEvent> {
+    TRANSACTION_EVENTS.with(|events| {
+        events.borrow()
+            .range((transaction_id, 0)..=(transaction_id, u32::MAX))
+            .map(|(_, event)| event)
+            .collect()
+    })
+}
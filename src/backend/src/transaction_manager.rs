@@ -4,6 +4,8 @@ use crate::types::*;
 use crate::memory::*;
 
 const TRANSACTION_TIMEOUT_NS: u64 = 1_800_000_000_000;
+// A lock can be extended to at most this long after it was taken, so a resolver cannot hold back a refund
+pub const MAX_LOCK_DURATION_NS: u64 = 2 * TRANSACTION_TIMEOUT_NS;
 
 pub fn create_transaction_from_quote(quote: &QuoteObject, user: Principal) -> Result<u64, String> {
     let transaction_id = generate_transaction_id();
@@ -18,6 +20,7 @@ pub fn create_transaction_from_quote(quote: &QuoteObject, user: Principal) -> Re
         operation: quote.operation.clone(),
         status: TransactionStatus::Pending,
         nav_tokens: quote.nav_tokens,
+        payment_asset: Some(crate::payments::payment_asset(&quote.payment_asset)),
         ckusdc_amount: quote.ckusdc_amount,
         created_at: current_time,
         updated_at: current_time,
@@ -40,26 +43,111 @@ pub fn get_transaction(transaction_id: u64) -> Result<Transaction, String> {
     })
 }
 
-pub fn update_transaction_status(transaction_id: u64, status: TransactionStatus) -> Result<(), String> {
+// Every operation walks the same steps; AssetsTransferred means collateral deposited and NAV minted
+// for buys, and the user paid with the NAV tokens still to be dissolved for sells.
+pub const SETTLEMENT_PATH: [TransactionStatus; 6] = [
+    TransactionStatus::Pending,
+    TransactionStatus::FundsLocked,
+    TransactionStatus::WaitingForResolver,
+    TransactionStatus::InProgress,
+    TransactionStatus::AssetsTransferred,
+    TransactionStatus::Completed,
+];
+
+pub fn is_valid_transition(from: &TransactionStatus, to: &TransactionStatus) -> bool {
+    if from.is_terminal() {
+        return false;
+    }
+
+    if matches!(to, TransactionStatus::Failed | TransactionStatus::TimedOut) {
+        return true;
+    }
+
+    SETTLEMENT_PATH.iter()
+        .position(|status| status == from)
+        .and_then(|index| SETTLEMENT_PATH.get(index + 1))
+        .is_some_and(|next| next == to)
+}
+
+// Settlement can stall at any step, including after the resolver started; recovery refunds,
+// unwinds or finishes the transaction from its settlement steps
+pub fn can_time_out(status: &TransactionStatus) -> bool {
+    !status.is_terminal()
+}
+
+pub fn is_recoverable(transaction: &Transaction) -> bool {
+    can_time_out(&transaction.status)
+}
+
+pub fn update_transaction_status(transaction_id: u64, status: TransactionStatus, actor: Principal) -> Result<(), String> {
+    let mut transaction = get_transaction(transaction_id)?;
+
+    if !is_valid_transition(&transaction.status, &status) {
+        return Err(format!(
+            "Invalid status transition for transaction {}: {:?} -> {:?}",
+            transaction_id,
+            transaction.status,
+            status
+        ));
+    }
+
+    let current_time = time();
+    let from = transaction.status.clone();
+
+    transaction.status = status.clone();
+    transaction.updated_at = current_time;
+
+    if transaction.status.is_terminal() {
+        transaction.completed_at = Some(current_time);
+    }
+
     TRANSACTIONS.with(|transactions| {
-        let mut transactions = transactions.borrow_mut();
+        transactions.borrow_mut().insert(transaction_id, transaction)
+    });
 
-        if let Some(mut transaction) = transactions.get(&transaction_id) {
-            transaction.status = status;
-            transaction.updated_at = time();
+    record_transition(transaction_id, from, status, actor, current_time);
 
-            if matches!(transaction.status, TransactionStatus::Completed | TransactionStatus::Failed | TransactionStatus::TimedOut) {
-                transaction.completed_at = Some(time());
-            }
+    Ok(())
+}
 
-            transactions.insert(transaction_id, transaction);
-            Ok(())
-        } else {
-            Err("Transaction not found".to_string())
-        }
+fn record_transition(transaction_id: u64, from: TransactionStatus, to: TransactionStatus, actor: Principal, timestamp: u64) {
+    TRANSACTION_TRANSITIONS.with(|transitions| {
+        let mut transitions = transitions.borrow_mut();
+
+        let sequence = transitions
+            .range((transaction_id, 0)..=(transaction_id, u32::MAX))
+            .last()
+            .map_or(0, |((_, sequence), _)| sequence + 1);
+
+        transitions.insert((transaction_id, sequence), TransactionTransition {
+            transaction_id,
+            sequence,
+            from,
+            to,
+            actor,
+            timestamp,
+        });
+    });
+}
+
+pub fn get_transaction_transitions(transaction_id: u64) -> Vec<TransactionTransition> {
+    TRANSACTION_TRANSITIONS.with(|transitions| {
+        transitions.borrow()
+            .range((transaction_id, 0)..=(transaction_id, u32::MAX))
+            .map(|(_, transition)| transition)
+            .collect()
     })
 }
 
+pub fn furthest_settlement_status(transitions: &[TransactionTransition]) -> TransactionStatus {
+    transitions.iter()
+        .rev()
+        .map(|transition| &transition.to)
+        .find(|status| !matches!(status, TransactionStatus::Failed | TransactionStatus::TimedOut))
+        .cloned()
+        .unwrap_or(TransactionStatus::Pending)
+}
+
 pub fn lock_user_funds(transaction_id: u64, fund_type: LockedFundType, amount: u64) -> Result<(), String> {
     let transaction = get_transaction(transaction_id)?;
 
@@ -83,7 +171,7 @@ pub fn lock_user_funds(transaction_id: u64, fund_type: LockedFundType, amount: u
         locks.borrow_mut().insert(lock_key, locked_funds)
     });
 
-    update_transaction_status(transaction_id, TransactionStatus::FundsLocked)?;
+    update_transaction_status(transaction_id, TransactionStatus::FundsLocked, transaction.user)?;
 
     Ok(())
 }
@@ -122,13 +210,14 @@ pub fn get_user_locked_funds(user: Principal) -> Vec<LockedFunds> {
 
 pub async fn validate_sufficient_balance_async(user: Principal, fund_type: &LockedFundType, amount: u64) -> Result<(), String> {
     match fund_type {
-        LockedFundType::CkUSDC => {
-            let user_balance = crate::nav_token::get_user_ckusdc_balance(user).await?;
+        LockedFundType::Payment { asset_id } => {
+            let token = crate::payments::payment_token(asset_id)?;
+            let user_balance = crate::icrc2_client::icrc1_balance_of(token.ledger, user, None).await?;
             let locked_amount = get_user_total_locked_amount(user, fund_type);
             let available_balance = user_balance.saturating_sub(locked_amount);
 
             if available_balance < amount {
-                return Err(format!("Insufficient ckUSDC balance: {} required, {} available", amount, available_balance));
+                return Err(format!("Insufficient {} balance: {} required, {} available", asset_id, amount, available_balance));
             }
         }
         LockedFundType::NAVTokens { bundle_id } => {
@@ -146,16 +235,15 @@ pub async fn validate_sufficient_balance_async(user: Principal, fund_type: &Lock
 
 pub async fn validate_sufficient_balance(user: Principal, fund_type: &LockedFundType, amount: u64) -> Result<(), String> {
     match fund_type {
-        LockedFundType::CkUSDC => {
+        LockedFundType::Payment { .. } => {
             Ok(())
         }
+        // Locked NAV tokens sit in escrow subaccounts, so the wallet balance is already net of them
         LockedFundType::NAVTokens { bundle_id } => {
             let user_balance = crate::nav_token::get_user_nav_token_balance(user, *bundle_id).await?;
-            let locked_amount = get_user_total_locked_amount(user, fund_type);
-            let available_balance = user_balance.saturating_sub(locked_amount);
 
-            if available_balance < amount {
-                return Err(format!("Insufficient NAV token balance for bundle {}: {} required, {} available", bundle_id, amount, available_balance));
+            if user_balance < amount {
+                return Err(format!("Insufficient NAV token balance for bundle {}: {} required, {} available", bundle_id, amount, user_balance));
             }
             Ok(())
         }
@@ -166,7 +254,7 @@ pub fn get_user_total_locked_amount(user: Principal, fund_type: &LockedFundType)
     let user_locked_funds = get_user_locked_funds(user);
     user_locked_funds
         .iter()
-        .filter(|locked| std::mem::discriminant(&locked.fund_type) == std::mem::discriminant(fund_type))
+        .filter(|locked| locked.fund_type == *fund_type)
         .map(|locked| locked.amount)
         .sum()
 }
@@ -231,6 +319,12 @@ pub fn extend_lock_expiration(transaction_id: u64, fund_type: &LockedFundType, n
         let mut locks = locks.borrow_mut();
 
         if let Some(mut locked_fund) = locks.get(&lock_key) {
+            if new_expiration.saturating_sub(locked_fund.locked_at) > MAX_LOCK_DURATION_NS {
+                return Err(format!(
+                    "Locks cannot be extended beyond {} seconds after they were taken",
+                    MAX_LOCK_DURATION_NS / 1_000_000_000
+                ));
+            }
             locked_fund.expires_at = new_expiration;
             locks.insert(lock_key, locked_fund);
             Ok(())
@@ -267,103 +361,9 @@ pub fn cleanup_expired_locks() -> u32 {
     cleaned_count
 }
 
-pub async fn transfer_ckusdc_to_canister(user: Principal, amount: u64, transaction_id: u64) -> Result<u64, String> {
-    let memo = format!("Buy transaction {}", transaction_id).into_bytes();
-    // Use ICRC-2 to pull from user (requires prior approval)
-    crate::icrc2_client::pull_ckusdc_from_user(user, amount, Some(memo)).await
-}
-
-pub async fn transfer_ckusdc_from_canister(recipient: Principal, amount: u64, transaction_id: u64) -> Result<u64, String> {
-    let memo = format!("Payment for transaction {}", transaction_id).into_bytes();
-    // Use ICRC-1 to send from canister
-    crate::icrc2_client::send_ckusdc_to_user(recipient, amount, Some(memo)).await
-}
-
-pub async fn lock_and_transfer_ckusdc(transaction_id: u64, user: Principal, amount: u64) -> Result<u64, String> {
-    let fund_type = LockedFundType::CkUSDC;
-
-    validate_sufficient_balance_async(user, &fund_type, amount).await?;
-
-    let block_index = transfer_ckusdc_to_canister(user, amount, transaction_id).await?;
-
-    lock_user_funds(transaction_id, fund_type, amount)?;
-
-    ic_cdk::println!("ckUSDC locked and transferred: user={}, amount={}, block_index={}",
-        user.to_text(), amount, block_index);
-
-    Ok(block_index)
-}
-
-pub async fn pay_resolver_ckusdc(transaction_id: u64, resolver: Principal, amount: u64) -> Result<u64, String> {
-    let transaction = get_transaction(transaction_id)?;
-
-    if !matches!(transaction.status, TransactionStatus::InProgress) {
-        return Err("Transaction not in progress state".to_string());
-    }
-
-    let fund_type = LockedFundType::CkUSDC;
-    let locked_amount = unlock_user_funds(transaction_id, &fund_type)?;
-
-    if locked_amount != amount {
-        return Err(format!("Locked amount mismatch: expected {}, got {}", amount, locked_amount));
-    }
-
-    let block_index = transfer_ckusdc_from_canister(resolver, amount, transaction_id).await?;
-
-    ic_cdk::println!("Resolver paid: resolver={}, amount={}, block_index={}",
-        resolver.to_text(), amount, block_index);
-
-    Ok(block_index)
-}
-
+// Shares the heartbeat's recovery path, so refunds run before the terminal status and failures are retried
 pub async fn cleanup_expired_transactions() -> u32 {
-    let current_time = time();
-    let mut cleaned_count = 0;
-
-    let expired_transaction_ids: Vec<u64> = TRANSACTIONS.with(|transactions| {
-        transactions.borrow()
-            .iter()
-            .filter_map(|(transaction_id, transaction)| {
-                if transaction.timeout_at <= current_time &&
-                   !matches!(transaction.status, TransactionStatus::Completed) {
-                    Some(transaction_id)
-                } else {
-                    None
-                }
-            })
-            .collect()
-    });
-
-    for transaction_id in expired_transaction_ids {
-        if let Ok(transaction) = get_transaction(transaction_id) {
-            // Refund locked funds to users
-            match transaction.operation {
-                OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
-                    if let Ok(locked_amount) = unlock_user_funds(transaction_id, &LockedFundType::CkUSDC) {
-                        if locked_amount > 0 {
-                            if let Err(e) = transfer_ckusdc_from_canister(transaction.user, locked_amount, transaction_id).await {
-                                ic_cdk::println!("Failed to refund ckUSDC for expired transaction {}: {}", transaction_id, e);
-                                continue;
-                            }
-                            ic_cdk::println!("Refunded {} ckUSDC to user {} for expired transaction {}",
-                                locked_amount, transaction.user.to_text(), transaction_id);
-                        }
-                    }
-                }
-                OperationType::Sell { .. } => {
-                    let _ = unlock_user_funds(transaction_id, &LockedFundType::NAVTokens {
-                        bundle_id: transaction.bundle_id
-                    });
-                }
-            }
-
-            // Mark transaction as timed out
-            let _ = update_transaction_status(transaction_id, TransactionStatus::TimedOut);
-            cleaned_count += 1;
-        }
-    }
-
-    cleaned_count
+    crate::error_recovery::detect_and_handle_timeouts().await.unwrap_or(0)
 }
 
 pub fn get_user_transactions(user: Principal) -> Vec<Transaction> {
@@ -400,10 +400,11 @@ fn generate_transaction_id() -> u64 {
     get_next_transaction_id()
 }
 
-fn generate_lock_key(user: &Principal, transaction_id: u64, fund_type: &LockedFundType) -> String {
+pub(crate) fn generate_lock_key(user: &Principal, transaction_id: u64, fund_type: &LockedFundType) -> String {
     match fund_type {
-        LockedFundType::CkUSDC => {
-            format!("{}:{}:ckusdc", user.to_text(), transaction_id)
+        // Lower-cased so existing ckUSDC locks keep their original "ckusdc" key
+        LockedFundType::Payment { asset_id } => {
+            format!("{}:{}:{}", user.to_text(), transaction_id, asset_id.to_lowercase())
         }
         LockedFundType::NAVTokens { bundle_id } => {
             format!("{}:{}:nav:{}", user.to_text(), transaction_id, bundle_id)
@@ -445,6 +446,7 @@ pub fn get_transaction_summary(transaction_id: u64) -> Result<TransactionSummary
         operation: transaction.operation,
         status: transaction.status,
         nav_tokens: transaction.nav_tokens,
+        payment_asset: transaction.payment_asset,
         ckusdc_amount: transaction.ckusdc_amount,
         created_at: transaction.created_at,
         duration_ms,
@@ -469,7 +471,7 @@ pub fn get_transaction_stats() -> TransactionStats {
     TRANSACTIONS.with(|transactions| {
         for (_, transaction) in transactions.borrow().iter() {
             stats.total_transactions += 1;
-            stats.total_volume_ckusdc += transaction.ckusdc_amount;
+            stats.total_volume_ckusdc += crate::payments::usd_value_e6(&transaction.payment_asset, transaction.ckusdc_amount);
 
             match transaction.operation {
                 OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
@@ -526,13 +528,13 @@ pub fn get_bundle_transaction_history(bundle_id: u64) -> BundleTransactionHistor
                 OperationType::InitialBuy { .. } | OperationType::Buy { .. } => {
                     history.total_buy_transactions += 1;
                     if matches!(transaction.status, TransactionStatus::Completed) {
-                        history.total_volume_bought += transaction.ckusdc_amount;
+                        history.total_volume_bought += crate::payments::usd_value_e6(&transaction.payment_asset, transaction.ckusdc_amount);
                     }
                 }
                 OperationType::Sell { .. } => {
                     history.total_sell_transactions += 1;
                     if matches!(transaction.status, TransactionStatus::Completed) {
-                        history.total_volume_sold += transaction.ckusdc_amount;
+                        history.total_volume_sold += crate::payments::usd_value_e6(&transaction.payment_asset, transaction.ckusdc_amount);
                     }
                 }
             }
@@ -579,7 +581,7 @@ pub fn get_user_transaction_summary(user: Principal) -> UserTransactionSummary {
             }
 
             if matches!(transaction.status, TransactionStatus::Completed) {
-                summary.total_volume_ckusdc += transaction.ckusdc_amount;
+                summary.total_volume_ckusdc += crate::payments::usd_value_e6(&transaction.payment_asset, transaction.ckusdc_amount);
             }
 
             if summary.last_transaction_at.is_none() || Some(transaction.created_at) > summary.last_transaction_at {
@@ -592,8 +594,8 @@ pub fn get_user_transaction_summary(user: Principal) -> UserTransactionSummary {
     let locked_funds = get_user_locked_funds(user);
     for locked_fund in locked_funds {
         match locked_fund.fund_type {
-            LockedFundType::CkUSDC => {
-                summary.current_locked_funds += locked_fund.amount;
+            LockedFundType::Payment { asset_id } => {
+                summary.current_locked_funds += crate::payments::usd_value_e6(&Some(asset_id), locked_fund.amount);
             }
             LockedFundType::NAVTokens { .. } => {
                 // For NAV tokens, we could convert to USD value, but for now just count the amount
@@ -648,6 +650,7 @@ pub fn get_recent_transactions(limit: usize) -> Vec<TransactionSummary> {
                 operation: transaction.operation,
                 status: transaction.status,
                 nav_tokens: transaction.nav_tokens,
+                payment_asset: transaction.payment_asset,
                 ckusdc_amount: transaction.ckusdc_amount,
                 created_at: transaction.created_at,
                 duration_ms,
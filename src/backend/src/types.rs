@@ -19,12 +19,19 @@ pub struct GlobalState {
     pub coordinator_public_key: Option<Vec<u8>>,
     pub platform_treasury: Option<Principal>,
     pub default_platform_fee_bps: Option<u64>,
+    pub pause_state: Option<PauseState>,
+    pub governance_config: Option<GovernanceConfig>,
+    pub pending_admin: Option<Nomination>,
+    pub pending_treasury: Option<Nomination>,
+    pub price_aggregation: Option<PriceAggregationConfig>,
+    pub analytics_config: Option<AnalyticsConfig>,
+    pub recovery_counters: Option<RecoveryCounters>,
 }
 
 impl Default for GlobalState {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: 3,
             bundle_counter: 0,
             quote_counter: 0,
             transaction_counter: 0,
@@ -38,6 +45,13 @@ impl Default for GlobalState {
             coordinator_public_key: None,
             platform_treasury: None,
             default_platform_fee_bps: Some(50),
+            pause_state: None,
+            governance_config: None,
+            pending_admin: None,
+            pending_treasury: None,
+            price_aggregation: None,
+            analytics_config: None,
+            recovery_counters: None,
         }
     }
 }
@@ -52,13 +66,82 @@ impl Storable for GlobalState {
         decode_one(&bytes).expect("Failed to deserialize GlobalState")
     }
 
-    const BOUND: Bound = Bound::Bounded {
-        max_size: 1024,
-        is_fixed_size: false,
-    };
+    const BOUND: Bound = Bound::Unbounded;
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PausableOperation {
+    NewBuys,
+    NewSells,
+    InitialFunding,
+    ResolverConfirmations,
+    BundleCreation,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PauseState {
+    pub new_buys: bool,
+    pub new_sells: bool,
+    pub initial_funding: bool,
+    pub resolver_confirmations: bool,
+    pub bundle_creation: bool,
+    pub paused_bundles: Vec<u64>,
+    pub updated_at: u64,
+    pub updated_by: Option<Principal>,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, operation: &PausableOperation) -> bool {
+        match operation {
+            PausableOperation::NewBuys => self.new_buys,
+            PausableOperation::NewSells => self.new_sells,
+            PausableOperation::InitialFunding => self.initial_funding,
+            PausableOperation::ResolverConfirmations => self.resolver_confirmations,
+            PausableOperation::BundleCreation => self.bundle_creation,
+        }
+    }
+
+    pub fn set_paused(&mut self, operation: &PausableOperation, paused: bool) {
+        match operation {
+            PausableOperation::NewBuys => self.new_buys = paused,
+            PausableOperation::NewSells => self.new_sells = paused,
+            PausableOperation::InitialFunding => self.initial_funding = paused,
+            PausableOperation::ResolverConfirmations => self.resolver_confirmations = paused,
+            PausableOperation::BundleCreation => self.bundle_creation = paused,
+        }
+    }
+
+    pub fn is_bundle_paused(&self, bundle_id: u64) -> bool {
+        self.paused_bundles.contains(&bundle_id)
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PauseError {
+    OperationPaused { operation: PausableOperation },
+    BundlePaused { bundle_id: u64 },
+}
+
+impl std::fmt::Display for PauseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseError::OperationPaused { operation } => {
+                write!(f, "Operation paused: {:?} is temporarily disabled", operation)
+            }
+            PauseError::BundlePaused { bundle_id } => {
+                write!(f, "Bundle paused: bundle {} is temporarily disabled", bundle_id)
+            }
+        }
+    }
+}
+
+impl From<PauseError> for String {
+    fn from(error: PauseError) -> Self {
+        error.to_string()
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum TokenLocation {
     ICRC2 {
         ledger: Principal,
@@ -197,6 +280,14 @@ pub struct BundleConfig {
     pub created_at: u64,
     pub is_active: bool,
     pub platform_fee_bps: Option<u64>,
+    pub valuation_mode: Option<ValuationMode>,
+}
+
+// None on a bundle values holdings at spot
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ValuationMode {
+    Spot,
+    Twap { window_ns: u64 },
 }
 
 impl Storable for BundleConfig {
@@ -298,6 +389,45 @@ pub struct BundleListItem {
     pub created_at: u64,
     pub is_active: bool,
     pub token_location: TokenLocation,
+    pub analytics: BundleAnalytics,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AnalyticsConfig {
+    pub risk_free_rate_bps: u64,
+    pub benchmark_bundle_id: Option<u64>,
+}
+
+// Returns are over the named period; risk metrics cover the last 30 days of hourly NAV points
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BundleAnalytics {
+    pub bundle_id: u64,
+    pub return_24h_bps: Option<i64>,
+    pub return_7d_bps: Option<i64>,
+    pub return_30d_bps: Option<i64>,
+    pub return_ytd_bps: Option<i64>,
+    pub return_inception_bps: Option<i64>,
+    pub volatility_bps: Option<u64>,
+    pub max_drawdown_bps: Option<u64>,
+    pub sharpe_ratio: Option<f64>,
+    pub benchmark_bundle_id: Option<u64>,
+    pub correlation: Option<f64>,
+    pub calculated_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BundleSortKey {
+    NavPerToken,
+    TotalNav,
+    Holders,
+    Return24h,
+    Return7d,
+    Return30d,
+    ReturnYtd,
+    ReturnInception,
+    Volatility,
+    MaxDrawdown,
+    Sharpe,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -317,6 +447,29 @@ pub struct UserPortfolio {
     pub total_nav_tokens_held: u64,
 }
 
+// USD amounts use the oracle's 8 decimals; `nav_per_token` is None when the bundle couldn't be valued
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PortfolioPosition {
+    pub bundle_id: u64,
+    pub bundle_name: String,
+    pub symbol: String,
+    pub balance: u64,
+    pub nav_per_token: Option<u64>,
+    pub value_usd: Option<u64>,
+    pub cost_basis_usd: Option<u64>,
+    pub unrealized_pnl_usd: Option<i64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PortfolioValuation {
+    pub user: Principal,
+    pub positions: Vec<PortfolioPosition>,
+    pub total_value_usd: u64,
+    pub total_cost_basis_usd: Option<u64>,
+    pub fully_valued: bool,
+    pub valued_at: u64,
+}
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct CanisterStatus {
     pub is_admin_set: bool,
@@ -365,6 +518,23 @@ pub struct BundleNAV {
     pub total_tokens: u64,
     pub asset_values: Vec<AssetValue>,
     pub calculated_at: u64,
+    // Assets valued at a substitute rather than an oracle price; such a NAV is not kept in history
+    pub fallback_assets: Vec<AssetId>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NavPointKey {
+    pub bundle_id: u64,
+    pub interval: CandleInterval,
+    pub open_time: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BundleReturn {
+    pub bundle_id: u64,
+    pub start: BundleNAV,
+    pub end: BundleNAV,
+    pub return_bps: i64,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -381,6 +551,100 @@ pub struct OracleConfig {
     pub cache_duration_ns: u64,
     pub max_staleness_ns: u64,
     pub fallback_enabled: bool,
+    pub max_price_move_bps: Option<u64>,
+}
+
+// Per-asset overrides of the OracleConfig windows; None keeps the global value
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AssetPricePolicy {
+    pub cache_duration_ns: Option<u64>,
+    pub max_staleness_ns: Option<u64>,
+    pub max_price_move_bps: Option<u64>,
+    // Opts a USDC ticker into the $1 substitute when no price is available; off unless set
+    pub stablecoin_fallback: Option<bool>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PricePolicy {
+    pub cache_duration_ns: u64,
+    pub max_staleness_ns: u64,
+    pub fallback_enabled: bool,
+    pub max_price_move_bps: Option<u64>,
+    pub stablecoin_fallback: bool,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PriceObservation {
+    pub price_usd: u64,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PriceObservationKey {
+    pub asset_id: AssetId,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CandleInterval {
+    Minute,
+    Hour,
+    Day,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PriceCandleKey {
+    pub asset_id: AssetId,
+    pub interval: CandleInterval,
+    pub open_time: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PriceCandle {
+    pub open_time: u64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    pub samples: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PriceHaltReason {
+    NoValidPrice { detail: String },
+    PriceMove { previous_usd: u64, observed_usd: u64, move_bps: u64 },
+}
+
+// An overridden halt stops blocking trading until its reason changes; any accepted price clears the halt
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PriceHalt {
+    pub asset_id: AssetId,
+    pub reason: PriceHaltReason,
+    pub halted_at: u64,
+    pub overridden_by: Option<Principal>,
+}
+
+// Oracles queried in addition to OracleConfig.oracle_canister
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PriceAggregationConfig {
+    pub additional_oracles: Vec<Principal>,
+    pub min_sources: u32,
+    pub max_deviation_bps: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReferencePrice {
+    pub asset_id: AssetId,
+    pub price_usd: u64,
+    pub timestamp: u64,
+    pub set_by: Principal,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PriceSample {
+    pub source: String,
+    pub price_usd: u64,
+    pub timestamp: u64,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -391,6 +655,8 @@ pub struct CacheStatistics {
     pub cache_hit_rate: f64,
     pub oldest_entry_age_seconds: u64,
     pub cache_duration_seconds: u64,
+    pub max_staleness_seconds: u64,
+    pub oracle_canister: Principal,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -485,6 +751,8 @@ pub struct QuoteObject {
     pub operation: OperationType,
     pub resolver: Principal,
     pub nav_tokens: u64,
+    // Denominated in the payment asset's own units; None pays in ckUSDC
+    pub payment_asset: Option<AssetId>,
     pub ckusdc_amount: u64,
     pub asset_amounts: Vec<AssetAmount>,
     pub fees: u64,
@@ -498,6 +766,7 @@ pub struct QuoteAssignment {
     pub request_id: u64,
     pub resolver: Principal,
     pub nav_tokens: u64,
+    pub payment_asset: Option<AssetId>,
     pub ckusdc_amount: u64,
     pub asset_amounts: Vec<AssetAmount>,
     pub estimated_nav: u64,
@@ -532,6 +801,7 @@ pub struct Transaction {
     pub operation: OperationType,
     pub status: TransactionStatus,
     pub nav_tokens: u64,
+    pub payment_asset: Option<AssetId>,
     pub ckusdc_amount: u64,
     pub created_at: u64,
     pub updated_at: u64,
@@ -551,6 +821,213 @@ pub enum TransactionStatus {
     TimedOut,
 }
 
+impl TransactionStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TransactionStatus::Completed | TransactionStatus::Failed | TransactionStatus::TimedOut)
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TransactionTransition {
+    pub transaction_id: u64,
+    pub sequence: u32,
+    pub from: TransactionStatus,
+    pub to: TransactionStatus,
+    pub actor: Principal,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum LedgerCallKind {
+    Icrc1Transfer,
+    Icrc2TransferFrom,
+    Icrc151Transfer,
+    Icrc151TransferFrom,
+    Icrc151Mint,
+    Icrc151Burn,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct LedgerCall {
+    pub kind: LedgerCallKind,
+    pub ledger: Principal,
+    pub token_id: Option<Vec<u8>>,
+    pub from: Option<Principal>,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub to: Option<Principal>,
+    pub to_subaccount: Option<Vec<u8>>,
+    pub amount: u64,
+    pub memo: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TransactionEvent {
+    pub transaction_id: u64,
+    pub sequence: u32,
+    pub call: LedgerCall,
+    pub block_index: Option<u64>,
+    pub error: Option<String>,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SettlementStepState {
+    Pending,
+    Succeeded { block_index: u64 },
+    Failed { error: String },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SettlementStep {
+    pub transaction_id: u64,
+    pub step: String,
+    pub created_at_time: u64,
+    pub state: SettlementStepState,
+    pub attempts: u32,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RecoveryOutcome {
+    NothingToRecover,
+    CkUSDCRefunded { amount: u64 },
+    NavTokensReturned { amount: u64 },
+    DissolutionCompleted { assets: Vec<(AssetId, u64)> },
+    ResolverRefunded { ckusdc: u64, nav_tokens: u64 },
+    CollateralReturned { ckusdc: u64, assets: Vec<(AssetId, u64)> },
+    BuyCompleted,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RecoveryResult {
+    Success,
+    Partial { reason: String },
+    Failed { reason: String },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RecoveryLogEntry {
+    pub id: u64,
+    pub transaction_id: u64,
+    pub user: Principal,
+    pub operation: OperationType,
+    pub result: RecoveryResult,
+    pub outcome: Option<RecoveryOutcome>,
+    pub ckusdc_moved: u64,
+    pub nav_tokens_moved: u64,
+    pub assets_moved: Vec<(AssetId, u64)>,
+    pub ledger_calls_succeeded: u32,
+    pub started_at: u64,
+    pub completed_at: u64,
+}
+
+// Running recovery totals, counting each transaction once by its latest attempt
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RecoveryCounters {
+    pub transactions: u32,
+    pub successful: u32,
+    pub partial: u32,
+    pub failed: u32,
+    pub total_duration_ns: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Owner,
+    AssetManager,
+    FeeManager,
+    ResolverManager,
+    Operator,
+    Pauser,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RoleAssignment {
+    pub principal: Principal,
+    pub roles: Vec<Role>,
+    pub updated_at: u64,
+    pub updated_by: Principal,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AdminAction {
+    pub id: u64,
+    pub action_type: String,
+    pub target: String,
+    pub admin: Principal,
+    pub timestamp: u64,
+    pub details: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminActionFilter {
+    pub action_type: Option<String>,
+    pub target: Option<String>,
+    pub admin: Option<Principal>,
+    pub from_time: Option<u64>,
+    pub to_time: Option<u64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Nomination {
+    pub nominee: Principal,
+    pub nominated_by: Principal,
+    pub nominated_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GovernanceConfig {
+    pub delay_ns: u64,
+    pub required_approvals: u32,
+}
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            delay_ns: 86_400_000_000_000,
+            required_approvals: 1,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GovernedAction {
+    SetCoordinatorPublicKey { public_key_hex: String },
+    SetPlatformTreasury { treasury: Principal },
+    SetDefaultPlatformFeeBps { fee_bps: u64 },
+    SetBundlePlatformFee { bundle_id: u64, fee_bps: u64 },
+    UpdateAssetTokenLocation { asset_id: AssetId, token_location: TokenLocation },
+    SetAdmin { new_admin: Principal },
+    SetGovernanceConfig { config: GovernanceConfig },
+    SetCkusdcLedger { ledger: Principal },
+    SetBundleValuationMode { bundle_id: u64, mode: ValuationMode },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ProposalStatus {
+    Pending,
+    Executed,
+    Cancelled,
+    Failed { error: String },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Proposal {
+    pub id: u64,
+    pub action: GovernedAction,
+    pub proposer: Principal,
+    pub approvals: Vec<Principal>,
+    pub required_approvals: u32,
+    pub created_at: u64,
+    pub executable_at: u64,
+    pub status: ProposalStatus,
+    pub resolved_at: Option<u64>,
+    pub resolved_by: Option<Principal>,
+}
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct LockedFunds {
     pub user: Principal,
@@ -563,10 +1040,16 @@ pub struct LockedFunds {
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum LockedFundType {
-    CkUSDC,
+    Payment { asset_id: AssetId },
     NAVTokens { bundle_id: u64 },
 }
 
+impl LockedFundType {
+    pub fn payment(asset_id: &str) -> Self {
+        LockedFundType::Payment { asset_id: asset_id.to_string() }
+    }
+}
+
 impl Storable for Transaction {
     fn to_bytes(&self) -> Cow<'_, [u8]> {
         let serialized = encode_one(self).expect("Failed to serialize Transaction");
@@ -583,6 +1066,204 @@ impl Storable for Transaction {
     };
 }
 
+impl Storable for TransactionTransition {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize TransactionTransition");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize TransactionTransition")
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 256,
+        is_fixed_size: false,
+    };
+}
+
+impl Storable for TransactionEvent {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize TransactionEvent");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize TransactionEvent")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RecoveryLogEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize RecoveryLogEntry");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize RecoveryLogEntry")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for NavPointKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize NavPointKey");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize NavPointKey")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for BundleNAV {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize BundleNAV");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize BundleNAV")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for PriceCandleKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize PriceCandleKey");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize PriceCandleKey")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for PriceCandle {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize PriceCandle");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize PriceCandle")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for PriceObservationKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize PriceObservationKey");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize PriceObservationKey")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for PriceHalt {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize PriceHalt");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize PriceHalt")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for ReferencePrice {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize ReferencePrice");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize ReferencePrice")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AssetPricePolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize AssetPricePolicy");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize AssetPricePolicy")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RoleAssignment {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize RoleAssignment");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize RoleAssignment")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AdminAction {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize AdminAction");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize AdminAction")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Proposal {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize Proposal");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize Proposal")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SettlementStep {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let serialized = encode_one(self).expect("Failed to serialize SettlementStep");
+        Cow::Owned(serialized)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        decode_one(&bytes).expect("Failed to deserialize SettlementStep")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 impl Storable for LockedFunds {
     fn to_bytes(&self) -> Cow<'_, [u8]> {
         let serialized = encode_one(self).expect("Failed to serialize LockedFunds");
@@ -607,6 +1288,7 @@ pub struct TransactionSummary {
     pub operation: OperationType,
     pub status: TransactionStatus,
     pub nav_tokens: u64,
+    pub payment_asset: Option<AssetId>,
     pub ckusdc_amount: u64,
     pub created_at: u64,
     pub duration_ms: Option<u64>,
@@ -652,4 +1334,50 @@ pub struct QuoteStatistics {
     pub assigned_requests: u32,
     pub expired_requests: u32,
     pub expired_assignments: u32,
-}
\ No newline at end of file
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SchemaVersion {
+    pub region: String,
+    pub version: u32,
+    pub latest_version: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct MigrationStep {
+    pub region: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub description: String,
+    pub records: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RegionCheck {
+    pub region: String,
+    pub records: u64,
+    pub error: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub steps: Vec<MigrationStep>,
+    pub checks: Vec<RegionCheck>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StablecoinLedgerInfo {
+    pub ledger: Principal,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub fee: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PaymentToken {
+    pub asset_id: AssetId,
+    pub ledger: Principal,
+    pub decimals: u8,
+}
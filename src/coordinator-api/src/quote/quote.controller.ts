@@ -6,15 +6,15 @@ export class QuoteController {
   constructor(private readonly quoteService: QuoteService) {}
 
   @Post('get')
-  async getQuote(@Body() body: { bundleId: number; operation: any; user: string }) {
+  async getQuote(@Body() body: { bundleId: number; operation: any; user: string; paymentAsset?: string }) {
     try {
-      const { bundleId, operation, user } = body;
+      const { bundleId, operation, user, paymentAsset } = body;
 
       if (!bundleId || !operation || !user) {
         throw new HttpException('Missing required fields', HttpStatus.BAD_REQUEST);
       }
 
-      const quote = await this.quoteService.getQuote(bundleId, operation, user);
+      const quote = await this.quoteService.getQuote(bundleId, operation, user, paymentAsset);
       return quote;
     } catch (error) {
       throw new HttpException(
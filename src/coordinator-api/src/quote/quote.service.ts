@@ -13,7 +13,7 @@ export class QuoteService {
     private readonly signerService: SignerService,
   ) {}
 
-  async getQuote(bundleId: number, operation: any, user: string) {
+  async getQuote(bundleId: number, operation: any, user: string, paymentAsset?: string) {
     const startTime = Date.now();
     const operationType = Object.keys(operation)[0];
     console.log(`[Quote] Request for bundle ${bundleId}, operation: ${operationType}, user: ${user.slice(0, 8)}...`);
@@ -24,6 +24,7 @@ export class QuoteService {
       bundleId,
       operation,
       user,
+      paymentAsset,
     );
 
     console.log(`[Quote] Received ${quotes.length} quote(s) from resolvers`);
@@ -60,6 +61,7 @@ export class QuoteService {
       fees: platformFee,
       valid_until: validUntil,
       nonce,
+      payment_asset: paymentAsset ?? null,
       coordinator_signature: [],
     };
 
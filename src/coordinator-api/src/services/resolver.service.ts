@@ -58,6 +58,7 @@ export class ResolverService implements OnModuleInit {
     bundleId: number,
     operation: any,
     user: string,
+    paymentAsset?: string,
   ): Promise<ResolverQuote[]> {
     console.log(`[Resolver] Querying ${this.resolvers.length} resolver(s)...`);
 
@@ -72,6 +73,7 @@ export class ResolverService implements OnModuleInit {
               bundleId,
               operation,
               user,
+              paymentAsset,
             },
             { timeout: 5000 },
           ),
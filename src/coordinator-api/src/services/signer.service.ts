@@ -80,6 +80,10 @@ export class SignerService implements OnModuleInit {
       quote.valid_until.toString(),
       quote.nonce.toString(),
     ];
+    // The backend only signs the payment asset when one is set, so ckUSDC quotes keep the old format
+    if (quote.payment_asset) {
+      parts.push(quote.payment_asset);
+    }
     const message = parts.join('|');
     console.log('[COORDINATOR SERIALIZE]', message);
     return new TextEncoder().encode(message);
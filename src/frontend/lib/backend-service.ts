@@ -139,6 +139,7 @@ class BackendService {
         resolver: typeof quote.resolver === 'string'
           ? Principal.fromText(quote.resolver)
           : quote.resolver,
+        payment_asset: quote.payment_asset ? [quote.payment_asset] : [],
       };
 
       const result = await actor.execute_quote(quoteWithPrincipal);
@@ -182,8 +183,11 @@ class BackendService {
         throw new Error('Not authenticated');
       }
 
-      const portfolio = await actor.get_user_portfolio(principal);
-      return portfolio;
+      const result = await actor.get_user_portfolio(principal);
+      if ('Ok' in result) {
+        return result.Ok;
+      }
+      throw new Error(result.Err || 'Failed to load portfolio');
     } catch (e) {
       console.error('getUserPortfolio failed:', e);
       throw e;
@@ -18,7 +18,47 @@ const icrc151IdlFactory = ({ IDL }: any) => {
   });
 };
 
+// Approval interface, called with the user's authenticated agent
+const icrc151ApproveIdlFactory = ({ IDL }: any) => {
+  const Account = IDL.Record({
+    'owner': IDL.Principal,
+    'subaccount': IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+
+  const ApproveArgs = IDL.Record({
+    'token_id': IDL.Vec(IDL.Nat8),
+    'from_subaccount': IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'spender': Account,
+    'amount': IDL.Nat,
+    'expected_allowance': IDL.Opt(IDL.Nat),
+    'expires_at': IDL.Opt(IDL.Nat64),
+    'fee': IDL.Opt(IDL.Nat),
+    'memo': IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'created_at_time': IDL.Opt(IDL.Nat64),
+  });
+
+  const ApproveResult = IDL.Variant({
+    'Ok': IDL.Nat64,
+    'Err': IDL.Variant({
+      'GenericError': IDL.Record({ 'message': IDL.Text, 'error_code': IDL.Nat }),
+      'TemporarilyUnavailable': IDL.Null,
+      'Duplicate': IDL.Record({ 'duplicate_of': IDL.Nat64 }),
+      'BadFee': IDL.Record({ 'expected_fee': IDL.Nat }),
+      'AllowanceChanged': IDL.Record({ 'current_allowance': IDL.Nat }),
+      'CreatedInFuture': IDL.Record({ 'ledger_time': IDL.Nat64 }),
+      'TooOld': IDL.Null,
+      'Expired': IDL.Record({ 'ledger_time': IDL.Nat64 }),
+      'InsufficientFunds': IDL.Record({ 'balance': IDL.Nat }),
+    }),
+  });
+
+  return IDL.Service({
+    'icrc151_approve': IDL.Func([ApproveArgs], [ApproveResult], []),
+  });
+};
+
 const ICRC151_LEDGER_CANISTER_ID = 'owhk5-ciaaa-aaaae-qfzlq-cai';
+const BACKEND_CANISTER_ID = 'dk3fi-vyaaa-aaaae-qfycq-cai';
 
 class ICRC151Service {
   private actor: any = null;
@@ -51,6 +91,41 @@ class ICRC151Service {
     }
   }
 
+  // Approve the backend to escrow bundle tokens for a sell
+  async approveBackendForToken(ledger: Principal, tokenId: Uint8Array, amount: bigint): Promise<bigint> {
+    const agent = await authService.getAgent();
+    if (!agent) {
+      throw new Error('Not authenticated');
+    }
+
+    const actor: any = Actor.createActor(icrc151ApproveIdlFactory, {
+      agent,
+      canisterId: ledger,
+    });
+
+    const result = await actor.icrc151_approve({
+      token_id: Array.from(tokenId),
+      from_subaccount: [],
+      spender: {
+        owner: Principal.fromText(BACKEND_CANISTER_ID),
+        subaccount: [],
+      },
+      amount,
+      expected_allowance: [],
+      expires_at: [],
+      fee: [],
+      memo: [],
+      created_at_time: [],
+    });
+
+    if ('Ok' in result) {
+      console.log('Bundle token approval successful, block index:', result.Ok.toString());
+      return result.Ok;
+    }
+
+    throw new Error(`Approval failed: ${JSON.stringify(result.Err)}`);
+  }
+
   // Convert token_id (blob) to bundle_id (u64)
   // Token ID is created as bundle_id.to_le_bytes() padded to 32 bytes
   tokenIdToBundleId(tokenId: Uint8Array): number {
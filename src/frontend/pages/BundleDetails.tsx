@@ -6,6 +6,7 @@ import toast from 'react-hot-toast';
 import { useAuth } from '../lib/AuthContext';
 import { backendService } from '../lib/backend-service';
 import { icrc2Service } from '../lib/icrc2-service';
+import { icrc151Service } from '../lib/icrc151-service';
 import { coordinatorService } from '../src/services/coordinator-service';
 import { authService } from '../lib/auth';
 import { LineChart, Line, XAxis, YAxis, ResponsiveContainer } from 'recharts';
@@ -407,6 +408,12 @@ export default function BundleDetails() {
         } else {
           console.log('✓ Sufficient allowance already exists');
         }
+      } else if (bundle && 'ICRC151' in bundle.token_location) {
+        setTradeStatus('Approving NAV token escrow...');
+
+        const { ledger, token_id } = bundle.token_location.ICRC151;
+        const navAmount = BigInt(quoteToExecute.nav_tokens);
+        await icrc151Service.approveBackendForToken(ledger, token_id, navAmount);
       }
 
       setTradeStep(2);
@@ -4,6 +4,7 @@ interface GetQuoteRequest {
   bundleId: number;
   operation: any;
   user: string;
+  paymentAsset?: string;
 }
 
 interface QuoteObject {
@@ -16,6 +17,7 @@ interface QuoteObject {
   fees: number;
   valid_until: number;
   nonce: number;
+  payment_asset: string | null;
   coordinator_signature: number[];
 }
 
@@ -61,12 +63,13 @@ class CoordinatorService {
     );
   }
 
-  async getQuote(bundleId: number, operation: any, user: string): Promise<QuoteObject> {
+  async getQuote(bundleId: number, operation: any, user: string, paymentAsset?: string): Promise<QuoteObject> {
     try {
       const response = await this.client.post<QuoteObject>('/quote/get', {
         bundleId,
         operation,
         user,
+        paymentAsset,
       });
       return response.data;
     } catch (error: any) {
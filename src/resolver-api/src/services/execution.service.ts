@@ -41,6 +41,12 @@ export class ExecutionService {
     }
 
     if ('Sell' in transaction.operation) {
+      // Payment already confirmed on a previous attempt, only dissolution remains
+      if ('AssetsTransferred' in transaction.status) {
+        this.logger.log(`Request ${requestId} payment already confirmed, dissolving NAV tokens...`);
+        await this.backendService.dissolveNavTokens(requestId);
+        return;
+      }
       await this.executeSellAssignment(requestId);
     } else if ('Buy' in transaction.operation || 'InitialBuy' in transaction.operation) {
       await this.executeBuyAssignment(requestId);